@@ -0,0 +1,194 @@
+use crate::util::intersect;
+use crate::v2d::{m4x4::M4x4, v3::V3, v4::V4};
+
+// ----------------------------------------------------------------------------
+// Axis aligned bounding box. An empty box has `min > max` and is hit by
+// nothing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: V3,
+    pub max: V3,
+}
+
+// ----------------------------------------------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingSphere {
+    pub center: V3,
+    pub radius: f32,
+}
+
+// ----------------------------------------------------------------------------
+impl Default for Aabb {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+// ----------------------------------------------------------------------------
+impl Aabb {
+    pub const EMPTY: Aabb = Aabb {
+        min: V3::uniform(f32::INFINITY),
+        max: V3::uniform(f32::NEG_INFINITY),
+    };
+
+    pub const fn new(min: V3, max: V3) -> Self {
+        Self { min, max }
+    }
+
+    pub fn from_points(points: impl IntoIterator<Item = V3>) -> Self {
+        points.into_iter().fold(Self::EMPTY, Self::extend)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x0() > self.max.x0()
+            || self.min.x1() > self.max.x1()
+            || self.min.x2() > self.max.x2()
+    }
+
+    pub fn extend(self, p: V3) -> Self {
+        Self {
+            min: self.min.min(p),
+            max: self.max.max(p),
+        }
+    }
+
+    // Grows the box by `margin` on every side.
+    pub fn expand(self, margin: f32) -> Self {
+        Self {
+            min: self.min - V3::uniform(margin),
+            max: self.max + V3::uniform(margin),
+        }
+    }
+
+    pub fn center(&self) -> V3 {
+        0.5 * (self.min + self.max)
+    }
+
+    // Half the size along each axis.
+    pub fn extents(&self) -> V3 {
+        0.5 * (self.max - self.min)
+    }
+
+    // Points on the surface are inside.
+    pub fn contains(&self, p: V3) -> bool {
+        let (min, max, p) = (self.min.as_array(), self.max.as_array(), p.as_array());
+        (0..3).all(|i| min[i] <= p[i] && p[i] <= max[i])
+    }
+
+    // Touching boxes intersect, empty boxes never do.
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        let (a_min, a_max) = (self.min.as_array(), self.max.as_array());
+        let (b_min, b_max) = (other.min.as_array(), other.max.as_array());
+        (0..3).all(|i| a_min[i] <= b_max[i] && b_min[i] <= a_max[i])
+            && !self.is_empty()
+            && !other.is_empty()
+    }
+
+    pub fn corners(&self) -> [V3; 8] {
+        let (a, b) = (self.min, self.max);
+        [
+            V3::new([a.x0(), a.x1(), a.x2()]),
+            V3::new([b.x0(), a.x1(), a.x2()]),
+            V3::new([a.x0(), b.x1(), a.x2()]),
+            V3::new([b.x0(), b.x1(), a.x2()]),
+            V3::new([a.x0(), a.x1(), b.x2()]),
+            V3::new([b.x0(), a.x1(), b.x2()]),
+            V3::new([a.x0(), b.x1(), b.x2()]),
+            V3::new([b.x0(), b.x1(), b.x2()]),
+        ]
+    }
+
+    // Box around the transformed corners, so it stays axis aligned under
+    // rotation at the cost of growing.
+    pub fn transformed(&self, m: &M4x4) -> Self {
+        if self.is_empty() {
+            return *self;
+        }
+        Self::from_points(self.corners().map(|c| (*m * V4::from_v3(c, 1.0)).into()))
+    }
+
+    pub fn bounding_sphere(&self) -> BoundingSphere {
+        BoundingSphere {
+            center: self.center(),
+            radius: 0.5 * (self.max - self.min).length(),
+        }
+    }
+
+    // Distance along `dir` to the entry point, or 0 if `origin` is inside.
+    pub fn ray_hit(&self, origin: V3, dir: V3) -> Option<f32> {
+        intersect::ray_aabb(origin, dir, self)
+    }
+}
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v2d::affine4x4;
+
+    #[test]
+    fn from_points_and_sphere() {
+        let aabb = Aabb::from_points([V3::new([-1.0, 0.0, 2.0]), V3::new([1.0, 2.0, 0.0])]);
+        assert_eq!(
+            aabb,
+            Aabb::new(V3::new([-1.0, 0.0, 0.0]), V3::new([1.0, 2.0, 2.0]))
+        );
+
+        let sphere = aabb.bounding_sphere();
+        assert_eq!(sphere.center, V3::new([0.0, 1.0, 1.0]));
+        assert_eq!(sphere.radius, 3.0f32.sqrt());
+
+        assert!(Aabb::from_points([]).is_empty());
+    }
+
+    #[test]
+    fn overlapping_and_disjoint_boxes() {
+        let a = Aabb::new(V3::ZERO, V3::uniform(2.0));
+        let overlapping = Aabb::new(V3::uniform(1.0), V3::uniform(3.0));
+        let touching = Aabb::new(V3::new([2.0, 0.0, 0.0]), V3::new([3.0, 1.0, 1.0]));
+        let disjoint = Aabb::new(V3::new([0.0, 2.5, 0.0]), V3::new([1.0, 3.0, 1.0]));
+
+        assert!(a.intersects(&overlapping) && overlapping.intersects(&a));
+        assert!(a.intersects(&touching));
+        assert!(!a.intersects(&disjoint) && !disjoint.intersects(&a));
+        assert!(!a.intersects(&Aabb::EMPTY) && !Aabb::EMPTY.intersects(&a));
+
+        assert!(a.expand(0.5).intersects(&disjoint));
+    }
+
+    #[test]
+    fn contains_and_extents() {
+        let aabb = Aabb::new(V3::new([-1.0, 0.0, 1.0]), V3::new([1.0, 4.0, 2.0]));
+        assert_eq!(aabb.extents(), V3::new([1.0, 2.0, 0.5]));
+        assert_eq!(aabb.expand(1.0).extents(), V3::new([2.0, 3.0, 1.5]));
+
+        assert!(aabb.contains(aabb.center()));
+        assert!(aabb.contains(aabb.max));
+        assert!(!aabb.contains(V3::new([0.0, 4.1, 1.5])));
+        assert!(!Aabb::EMPTY.contains(V3::ZERO));
+    }
+
+    #[test]
+    fn transformed_box_contains_rotated_corners() {
+        let unit = Aabb::new(V3::uniform(-0.5), V3::uniform(0.5));
+        let m = affine4x4::translate(&V4::new([3.0, 0.0, 0.0, 1.0]))
+            * affine4x4::rotate_x1(std::f32::consts::FRAC_PI_4);
+
+        let aabb = unit.transformed(&m);
+        let h = 0.5 * 2.0f32.sqrt();
+        assert_eq!(aabb.min, V3::new([3.0 - h, -0.5, -h]));
+        assert_eq!(aabb.max, V3::new([3.0 + h, 0.5, h]));
+    }
+
+    #[test]
+    fn ray_hits_and_misses() {
+        let aabb = Aabb::new(V3::uniform(-1.0), V3::uniform(1.0));
+        let origin = V3::new([0.0, 0.0, -5.0]);
+
+        assert_eq!(aabb.ray_hit(origin, V3::X2), Some(4.0));
+        assert_eq!(aabb.ray_hit(origin, -V3::X2), None);
+        assert_eq!(aabb.ray_hit(V3::new([2.0, 0.0, -5.0]), V3::X2), None);
+        assert_eq!(aabb.ray_hit(V3::ZERO, V3::X0), Some(0.0));
+        assert_eq!(Aabb::EMPTY.ray_hit(origin, V3::X2), None);
+    }
+}
@@ -1,20 +1,80 @@
 use crate::core::component::{Component, Context};
+use crate::core::coords::{self, FORWARD, RIGHT, UP};
+use crate::core::game_input::GameKey;
 use crate::core::input;
 use crate::error::Result;
-use crate::v2d::{affine4x4, m4x4::M4x4, v4::V4};
+use crate::v2d::{affine4x4, m4x4::M4x4, v3::V3, v4::V4};
+
+// ----------------------------------------------------------------------------
+const MAX_PITCH: f32 = 85.0 * std::f32::consts::PI / 180.0;
+const WHEEL_DELTA: f32 = 120.0; // one wheel notch
+const EYE_HEIGHT: f32 = 1.5;
+const MIN_FOV: f32 = 20.0;
+const MAX_FOV: f32 = 90.0;
+const ORBIT_BUTTON: u32 = 1;
+const FREE_SPEED: f32 = 10.0; // m/s
+
+// ----------------------------------------------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CameraMode {
+    Chase,
+    Orbit,
+    FirstPerson,
+    // Flies with the movement keys and looks with the mouse, ignoring the
+    // target and the terrain.
+    Free,
+}
+
+// ----------------------------------------------------------------------------
+// What the world points the camera at each frame. `Object` follows the visible
+// render object with that name; `Free` stops following and leaves the camera
+// at its last target.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum CameraTarget {
+    Player,
+    #[default]
+    Car,
+    Free,
+    Object(String),
+}
+
+// ----------------------------------------------------------------------------
+// How the camera follows its target position. The spring can overshoot and
+// its result depends on the frame time; exponential smoothing closes the
+// remaining distance by 1 - exp(-rate * dt) and is frame-rate independent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TargetSmoothing {
+    Spring,
+    Exponential { rate: f32 },
+}
 
 // ----------------------------------------------------------------------------
 #[derive(Debug)]
 pub struct Camera {
+    mode: CameraMode,
     position: V4,
     direction: V4,
     velocity: V4,
     target: V4,
     target_forward: V4,
     target_smoothed: V4,
+    smoothing: TargetSmoothing,
+    heading: f32,
     distance: f32,
     stiffness: f32,
     damping: f32,
+    turn_stiffness: f32,
+    sensitivity: f32,
+    mouse_deadzone: f32,
+    mouse_smoothing: f32,
+    mouse_smoothed: (f32, f32),
+    zoom_speed: f32,
+    min_distance: f32,
+    max_distance: f32,
+    fov: f32,
+    near: f32,
+    far: f32,
+    dragging: bool,
 }
 
 // ----------------------------------------------------------------------------
@@ -22,18 +82,35 @@ impl Component for Camera {
     fn update(&mut self, ctx: &Context) -> Result<()> {
         let dt = ctx.dt_secs();
 
-        // Smoothing the target position
-        let d = self.target_smoothed - self.target;
-        let accel = -self.stiffness * d - self.damping * self.velocity;
-        self.velocity += accel * dt;
-        self.target_smoothed += self.velocity * dt;
+        if self.mode == CameraMode::Free {
+            let state = ctx.state;
+            let forward = state.axis(GameKey::MoveBackward, GameKey::MoveForward);
+            let right = state.axis(GameKey::StrafeLeft, GameKey::StrafeRight);
+            self.move_forward(forward * FREE_SPEED * dt);
+            self.strafe_right(right * FREE_SPEED * dt);
+            return Ok(());
+        }
+
+        self.smooth_target(dt);
+        self.smooth_heading(dt);
 
-        // Responsive camera rotation
-        let yaw = affine4x4::rotate_x1(self.direction.x1());
-        let offset = yaw * (-self.target_forward.norm() * self.distance);
+        let position = match self.mode {
+            CameraMode::Chase => {
+                // Responsive camera rotation
+                let yaw = affine4x4::rotate_x1(self.direction.x1());
+                let offset = yaw * (-self.smoothed_forward() * self.distance);
+                self.target_smoothed + offset + V4::new([0.0, 4.0, 0.0, 0.0])
+            }
+            CameraMode::Orbit => {
+                let (azimuth, elevation) = (self.direction.x1(), self.direction.x0());
+                let offset = V3::from_spherical(self.distance, azimuth, elevation);
+                self.target_smoothed + V4::from_v3(offset, 0.0)
+            }
+            CameraMode::FirstPerson => self.target_smoothed + V4::new([0.0, EYE_HEIGHT, 0.0, 0.0]),
+            CameraMode::Free => self.position,
+        };
 
         // Adapt height based on terrain
-        let position = self.target_smoothed + offset + V4::new([0.0, 4.0, 0.0, 0.0]);
         let height = ctx.terrain.height_at(position.x0(), position.x2());
         let target_x1 = position.x1().max(height + 1.0);
 
@@ -47,41 +124,233 @@ impl Camera {
     pub fn new(position: V4, direction: V4) -> Self {
         let target = V4::new([0.0, 0.0, -1.0, 0.0]);
         Self {
+            mode: CameraMode::Chase,
             position,
             direction,
             velocity: V4::new([0.0, 0.0, 0.0, 0.0]),
             target,
-            target_forward: V4::new([0.0, 0.0, -1.0, 0.0]),
+            target_forward: coords::direction(-FORWARD),
             target_smoothed: target,
+            smoothing: TargetSmoothing::Spring,
+            heading: std::f32::consts::PI,
             distance: 4.0,
             stiffness: 50.0,
             damping: 10.0,
+            turn_stiffness: 4.0,
+            sensitivity: 0.01,
+            mouse_deadzone: 0.0,
+            mouse_smoothing: 0.0,
+            mouse_smoothed: (0.0, 0.0),
+            zoom_speed: 0.5,
+            min_distance: 2.0,
+            max_distance: 20.0,
+            fov: 45.0,
+            near: 0.1,
+            far: 100.0,
+            dragging: false,
         }
     }
 
+    pub fn mode(&self) -> CameraMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: CameraMode) {
+        self.mode = mode;
+    }
+
+    pub fn set_sensitivity(&mut self, sensitivity: f32) {
+        self.sensitivity = sensitivity;
+    }
+
+    // Mouse moves of at most this many pixels are ignored as jitter.
+    pub fn set_mouse_deadzone(&mut self, pixels: f32) {
+        self.mouse_deadzone = pixels.max(0.0);
+    }
+
+    // Low-pass filter on mouse moves: each frame's move blends into the
+    // previous one by `1 - smoothing`, so 0 applies moves as they are.
+    pub fn set_mouse_smoothing(&mut self, smoothing: f32) {
+        self.mouse_smoothing = smoothing.clamp(0.0, 0.95);
+    }
+
+    pub fn distance(&self) -> f32 {
+        self.distance
+    }
+
+    pub fn set_distance(&mut self, distance: f32) {
+        self.distance = distance.clamp(self.min_distance, self.max_distance);
+    }
+
+    pub fn smoothing(&self) -> TargetSmoothing {
+        self.smoothing
+    }
+
+    pub fn set_smoothing(&mut self, smoothing: TargetSmoothing) {
+        self.smoothing = smoothing;
+        self.velocity = V4::zero();
+    }
+
+    // Spring constants used to follow the target position.
+    pub fn set_stiffness(&mut self, stiffness: f32) {
+        self.stiffness = stiffness.max(0.0);
+    }
+
+    pub fn set_damping(&mut self, damping: f32) {
+        self.damping = damping.max(0.0);
+    }
+
+    // Rate at which the camera heading follows the target's forward
+    // direction; the remaining angle decays by exp(-turn_stiffness * t).
+    pub fn set_turn_stiffness(&mut self, turn_stiffness: f32) {
+        self.turn_stiffness = turn_stiffness.max(0.0);
+    }
+
+    pub fn set_distance_limits(&mut self, min_distance: f32, max_distance: f32) {
+        self.min_distance = min_distance;
+        self.max_distance = max_distance.max(min_distance);
+        self.distance = self.distance.clamp(self.min_distance, self.max_distance);
+    }
+
+    // Vertical field of view in degrees.
+    pub fn fov(&self) -> f32 {
+        self.fov
+    }
+
+    pub fn near(&self) -> f32 {
+        self.near
+    }
+
+    pub fn far(&self) -> f32 {
+        self.far
+    }
+
+    // Distances of the near and far clip planes, the far plane is kept beyond
+    // the near plane.
+    pub fn set_clip_planes(&mut self, near: f32, far: f32) {
+        self.near = near.max(f32::EPSILON);
+        self.far = far.max(2.0 * self.near);
+    }
+
+    pub fn projection(&self, aspect: f32) -> M4x4 {
+        affine4x4::perspective(self.fov, aspect, self.near, self.far)
+    }
+
+    // World space ray through a point in normalized device coordinates,
+    // starting on the near plane. (0, 0) is the center of the screen.
+    pub fn screen_ray(&self, ndc_x: f32, ndc_y: f32, aspect: f32) -> (V3, V3) {
+        let inv = (self.projection(aspect) * self.transform()).inverse();
+        let unproject = |depth: f32| {
+            let p = inv * V4::new([ndc_x, ndc_y, depth, 1.0]);
+            V3::from(p) / p.x3()
+        };
+
+        // Depth runs from 0 at the near to 1 at the far plane
+        let (near, far) = (unproject(0.0), unproject(1.0));
+        (near, (far - near).norm())
+    }
+
+    pub fn pitch(&self) -> f32 {
+        self.direction.x0()
+    }
+
     pub fn position(&self) -> V4 {
         self.position
     }
 
     pub fn input(&mut self, events: &input::Events) -> Result<()> {
-        // Process input events, e.g., keyboard, mouse, etc.
+        let mut looked = (0.0, 0.0);
         for event in events {
-            #[allow(clippy::single_match)]
             match event {
-                input::Event::MouseMove { x, y } => {
-                    self.yaw(*x as f32 * 0.01);
-                    self.tilt(*y as f32 * 0.01);
+                input::Event::ButtonDown {
+                    button: ORBIT_BUTTON,
+                } => self.dragging = true,
+                input::Event::ButtonUp {
+                    button: ORBIT_BUTTON,
+                } => self.dragging = false,
+                input::Event::MouseMove { x, y }
+                    if matches!(self.mode, CameraMode::Chase | CameraMode::Free)
+                        || self.dragging =>
+                {
+                    let (dx, dy) = self.without_jitter(*x as f32, *y as f32);
+                    looked = (looked.0 + dx, looked.1 + dy);
+                }
+                input::Event::Wheel { delta } => {
+                    self.zoom(*delta as f32 / WHEEL_DELTA);
                 }
                 _ => {}
             }
         }
+
+        self.mouse_look(looked.0, looked.1);
         Ok(())
     }
 
+    // A raw mouse move, or nothing if it is inside the deadzone.
+    fn without_jitter(&self, dx: f32, dy: f32) -> (f32, f32) {
+        if dx.hypot(dy) <= self.mouse_deadzone {
+            (0.0, 0.0)
+        } else {
+            (dx, dy)
+        }
+    }
+
+    // Advances the mouse filter by one frame and orbits by its output. Frames
+    // without moves feed in 0, so what is left in the filter still turns the
+    // camera and the total rotation matches the raw moves.
+    fn mouse_look(&mut self, dx: f32, dy: f32) {
+        let (sx, sy) = self.mouse_smoothed;
+        let k = 1.0 - self.mouse_smoothing;
+        self.mouse_smoothed = (sx + k * (dx - sx), sy + k * (dy - sy));
+        self.orbit(self.mouse_smoothed.0, self.mouse_smoothed.1);
+    }
+
+    // Rotates around the target by a mouse delta, keeping the pitch away from
+    // the poles so the camera can't flip over.
+    pub fn orbit(&mut self, dx: f32, dy: f32) {
+        self.yaw(dx * self.sensitivity);
+        self.tilt(dy * self.sensitivity);
+        let pitch = self.direction.x0().clamp(-MAX_PITCH, MAX_PITCH);
+        self.direction = self.direction.with_x0(pitch);
+    }
+
+    // Moves towards (positive steps) or away from the target. In first-person
+    // mode there is no distance to the target, so the field of view narrows.
+    pub fn zoom(&mut self, steps: f32) {
+        match self.mode {
+            CameraMode::Chase | CameraMode::Orbit => {
+                let distance = self.distance - steps * self.zoom_speed;
+                self.distance = distance.clamp(self.min_distance, self.max_distance);
+            }
+            CameraMode::FirstPerson | CameraMode::Free => {
+                let fov = self.fov - steps * 5.0;
+                self.fov = fov.clamp(MIN_FOV, MAX_FOV);
+            }
+        }
+    }
+
     pub fn transform(&self) -> M4x4 {
+        let up = coords::direction(UP);
         let pitch = affine4x4::rotate_x0(-self.direction.x0());
-        let look_at = affine4x4::look_at(self.position, self.target, V4::new([0.0, 1.0, 0.0, 0.0]));
-        pitch * look_at
+        match self.mode {
+            CameraMode::Chase => pitch * affine4x4::look_at(self.position, self.target, up),
+            CameraMode::Orbit => affine4x4::look_at(self.position, self.target, up),
+            CameraMode::FirstPerson => {
+                let yaw = affine4x4::rotate_x1(self.direction.x1());
+                let forward = yaw * self.smoothed_forward();
+                pitch * affine4x4::look_at(self.position, self.position + forward, up)
+            }
+            CameraMode::Free => {
+                let yaw = affine4x4::rotate_x1(self.direction.x1());
+                let forward = yaw * coords::direction(FORWARD);
+                pitch * affine4x4::look_at(self.position, self.position + forward, up)
+            }
+        }
+    }
+
+    // Position passed to the last `look_at`.
+    pub fn target(&self) -> V4 {
+        self.target
     }
 
     pub fn look_at(&mut self, target: V4, forward: V4) {
@@ -89,25 +358,76 @@ impl Camera {
         self.target_forward = forward;
     }
 
-    fn move_by(&mut self, d: V4) {
+    // Jumps to the current target without smoothing, e.g. after spawning.
+    pub fn snap_to_target(&mut self) {
+        self.target_smoothed = self.target;
+        self.velocity = V4::zero();
+        self.heading = self.target_heading().unwrap_or(self.heading);
+    }
+
+    // Horizontal forward direction the camera currently follows.
+    pub fn smoothed_forward(&self) -> V4 {
+        let (sin, cos) = self.heading.sin_cos();
+        coords::direction(sin * RIGHT + cos * FORWARD)
+    }
+
+    // Heading of `target_forward` in the ground plane, measured from FORWARD
+    // towards RIGHT. None if the target looks straight up or down.
+    fn target_heading(&self) -> Option<f32> {
+        let forward = coords::ground(self.target_forward.into());
+        (forward.length() > f32::EPSILON).then(|| forward.x0().atan2(forward.x1()))
+    }
+
+    fn smooth_target(&mut self, dt: f32) {
+        match self.smoothing {
+            TargetSmoothing::Spring => {
+                let d = self.target_smoothed - self.target;
+                let accel = -self.stiffness * d - self.damping * self.velocity;
+                self.velocity += accel * dt;
+                self.target_smoothed += self.velocity * dt;
+            }
+            TargetSmoothing::Exponential { rate } => {
+                let t = 1.0 - (-rate.max(0.0) * dt).exp();
+                self.target_smoothed = self.target_smoothed.lerp(self.target, t);
+            }
+        }
+    }
+
+    fn smooth_heading(&mut self, dt: f32) {
+        use std::f32::consts::{PI, TAU};
+        if let Some(target) = self.target_heading() {
+            // Turn along the shorter way around
+            let delta = (target - self.heading + PI).rem_euclid(TAU) - PI;
+            self.heading += delta * (1.0 - (-self.turn_stiffness * dt).exp());
+            self.heading = (self.heading + PI).rem_euclid(TAU) - PI;
+        }
+    }
+
+    // Moves by `d` given in view space, which shares the world axes.
+    fn move_by(&mut self, d: V3) {
         let transform = self.transform().inverse();
-        self.position += transform * d;
+        self.position += transform * coords::direction(d);
+    }
+
+    // World direction the view looks along.
+    pub fn forward(&self) -> V4 {
+        self.transform().inverse() * coords::direction(FORWARD)
     }
 
     pub fn move_forward(&mut self, distance: f32) {
-        self.move_by(V4::new([0.0, 0.0, -distance, 0.0]));
+        self.move_by(distance * FORWARD);
     }
 
     pub fn move_backward(&mut self, distance: f32) {
-        self.move_by(V4::new([0.0, 0.0, distance, 0.0]));
+        self.move_by(-distance * FORWARD);
     }
 
     pub fn strafe_left(&mut self, distance: f32) {
-        self.move_by(V4::new([-distance, 0.0, 0.0, 0.0]));
+        self.move_by(-distance * RIGHT);
     }
 
     pub fn strafe_right(&mut self, distance: f32) {
-        self.move_by(V4::new([distance, 0.0, 0.0, 0.0]));
+        self.move_by(distance * RIGHT);
     }
 
     pub fn yaw(&mut self, x: f32) {
@@ -118,3 +438,298 @@ impl Camera {
         self.direction -= V4::new([y, 0.0, 0.0, 0.0]);
     }
 }
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_float_eq;
+    use crate::core::input::Event;
+
+    // ------------------------------------------------------------------------
+    fn orbit_camera() -> Camera {
+        let mut camera = Camera::new(V4::zero(), V4::zero());
+        camera.set_mode(CameraMode::Orbit);
+        camera
+    }
+
+    // ------------------------------------------------------------------------
+    #[test]
+    fn pitch_is_clamped() {
+        let mut camera = orbit_camera();
+        let drag = |y| {
+            vec![
+                Event::ButtonDown { button: 1 },
+                Event::MouseMove { x: 0, y },
+                Event::ButtonUp { button: 1 },
+            ]
+        };
+
+        camera.input(&drag(-10000)).unwrap();
+        assert_eq!(camera.pitch(), MAX_PITCH);
+
+        camera.input(&drag(10000)).unwrap();
+        assert_eq!(camera.pitch(), -MAX_PITCH);
+
+        camera.input(&drag(-10)).unwrap();
+        assert!(camera.pitch() > -MAX_PITCH);
+    }
+
+    // ------------------------------------------------------------------------
+    #[test]
+    fn mouse_jitter_is_ignored() {
+        let mut camera = Camera::new(V4::zero(), V4::zero());
+        camera.set_sensitivity(0.002);
+        camera.set_mouse_deadzone(2.0);
+
+        let jitter = [(1, 0), (0, -1), (1, 1), (-2, 0), (0, 2)];
+        let events: Vec<_> = jitter
+            .iter()
+            .map(|&(x, y)| Event::MouseMove { x, y })
+            .collect();
+        camera.input(&events).unwrap();
+        assert_eq!(camera.direction, V4::zero());
+
+        camera
+            .input(&vec![Event::MouseMove { x: 100, y: -50 }])
+            .unwrap();
+        assert_float_eq!(camera.direction.x1(), -0.2);
+        assert_float_eq!(camera.pitch(), 0.1);
+    }
+
+    // ------------------------------------------------------------------------
+    #[test]
+    fn mouse_smoothing_spreads_moves() {
+        let mut camera = Camera::new(V4::zero(), V4::zero());
+        camera.set_mouse_smoothing(0.5);
+
+        let moves = vec![Event::MouseMove { x: 100, y: 0 }];
+        camera.input(&moves).unwrap();
+        assert_float_eq!(camera.direction.x1(), -0.5);
+
+        // Converges on the raw delta when it is repeated
+        camera.input(&moves).unwrap();
+        assert_float_eq!(camera.direction.x1(), -0.5 - 0.75);
+    }
+
+    // ------------------------------------------------------------------------
+    #[test]
+    fn mouse_smoothing_keeps_the_total_rotation() {
+        let mut camera = Camera::new(V4::zero(), V4::zero());
+        camera.set_mouse_smoothing(0.5);
+
+        // A single flick keeps turning the camera until all of it is applied
+        camera
+            .input(&vec![Event::MouseMove { x: 100, y: 0 }])
+            .unwrap();
+        assert_float_eq!(camera.direction.x1(), -0.5);
+        for _ in 0..30 {
+            camera.input(&vec![]).unwrap();
+        }
+        assert_float_eq!(camera.direction.x1(), -100.0 * camera.sensitivity);
+
+        // Splitting a frame's move into more events doesn't change it
+        let mut split = Camera::new(V4::zero(), V4::zero());
+        split.set_mouse_smoothing(0.5);
+        let halves = vec![Event::MouseMove { x: 50, y: 0 }; 2];
+        split.input(&halves).unwrap();
+        assert_float_eq!(split.direction.x1(), -0.5);
+    }
+
+    // ------------------------------------------------------------------------
+    #[test]
+    fn orbit_requires_drag() {
+        let mut camera = orbit_camera();
+        camera
+            .input(&vec![Event::MouseMove { x: 50, y: 50 }])
+            .unwrap();
+        assert_eq!(camera.pitch(), 0.0);
+    }
+
+    // ------------------------------------------------------------------------
+    #[test]
+    fn wheel_zoom_is_bounded() {
+        let mut camera = orbit_camera();
+        camera.set_distance_limits(2.0, 10.0);
+        let distance = camera.distance();
+
+        camera.input(&vec![Event::Wheel { delta: 120 }]).unwrap();
+        assert!(camera.distance() < distance);
+
+        camera
+            .input(&vec![Event::Wheel { delta: 120 * 100 }])
+            .unwrap();
+        assert_eq!(camera.distance(), 2.0);
+
+        camera
+            .input(&vec![Event::Wheel { delta: -120 * 100 }])
+            .unwrap();
+        assert_eq!(camera.distance(), 10.0);
+    }
+
+    // ------------------------------------------------------------------------
+    #[test]
+    fn chase_wheel_zoom_is_monotonic() {
+        let mut camera = Camera::new(V4::zero(), V4::zero());
+        camera.set_distance_limits(2.0, 8.0);
+
+        let mut distances = vec![camera.distance()];
+        for _ in 0..20 {
+            camera.input(&vec![Event::Wheel { delta: 120 }]).unwrap();
+            distances.push(camera.distance());
+        }
+        assert!(distances.windows(2).all(|d| d[1] <= d[0]));
+        assert_eq!(camera.distance(), 2.0);
+
+        for _ in 0..20 {
+            camera.input(&vec![Event::Wheel { delta: -120 }]).unwrap();
+        }
+        assert_eq!(camera.distance(), 8.0);
+    }
+
+    // ------------------------------------------------------------------------
+    #[test]
+    fn first_person_wheel_changes_fov() {
+        let mut camera = Camera::new(V4::zero(), V4::zero());
+        camera.set_mode(CameraMode::FirstPerson);
+        let distance = camera.distance();
+
+        camera.input(&vec![Event::Wheel { delta: 120 }]).unwrap();
+        assert!(camera.fov() < 45.0);
+        assert_eq!(camera.distance(), distance);
+
+        camera.input(&vec![Event::Wheel { delta: 2400 }]).unwrap();
+        assert_eq!(camera.fov(), MIN_FOV);
+        camera.input(&vec![Event::Wheel { delta: -2400 }]).unwrap();
+        assert_eq!(camera.fov(), MAX_FOV);
+    }
+
+    #[test]
+    fn clip_planes_reach_projection() {
+        let mut camera = Camera::new(V4::zero(), V4::zero());
+        camera.set_clip_planes(0.5, 5000.0);
+        assert_eq!(camera.near(), 0.5);
+        assert_eq!(camera.far(), 5000.0);
+
+        // points on the near and far plane map to depth 0 and 1
+        let p = camera.projection(16.0 / 9.0);
+        let near = p * V4::new([0.0, 0.0, 0.5, 1.0]);
+        let far = p * V4::new([0.0, 0.0, 5000.0, 1.0]);
+        assert_float_eq!(near.x2() / near.x3(), 0.0);
+        assert_float_eq!(far.x2() / far.x3(), 1.0);
+    }
+
+    #[test]
+    fn heading_follows_reversal_gradually() {
+        use crate::core::game_input::InputContext;
+        use crate::core::terrain::Terrain;
+
+        let (state, terrain) = (InputContext::default(), Terrain::new_flat(1, 1));
+        let ctx = Context {
+            dt: std::time::Duration::from_millis(16),
+            state: &state,
+            terrain: &terrain,
+        };
+
+        let mut camera = Camera::new(V4::zero(), V4::zero());
+        camera.look_at(V4::zero(), coords::direction(FORWARD));
+        camera.snap_to_target();
+        assert_eq!(camera.smoothed_forward(), coords::direction(FORWARD));
+
+        let reversed = coords::direction(-FORWARD);
+        camera.look_at(V4::zero(), reversed);
+        camera.update(&ctx).unwrap();
+        let mut alignment = camera.smoothed_forward().dot(reversed);
+        assert!(alignment < -0.9, "camera snapped to the new heading");
+
+        for _ in 0..200 {
+            camera.update(&ctx).unwrap();
+            let next = camera.smoothed_forward().dot(reversed);
+            assert!(next >= alignment - 1e-6);
+            alignment = next;
+        }
+        assert!(alignment > 0.99);
+    }
+
+    #[test]
+    fn exponential_smoothing_ignores_step_size() {
+        let target = V4::new([10.0, 0.0, 5.0, 1.0]);
+        let follow = |steps: u32| {
+            let mut camera = Camera::new(V4::zero(), V4::zero());
+            camera.look_at(V4::new([0.0, 0.0, 0.0, 1.0]), coords::direction(FORWARD));
+            camera.snap_to_target();
+            camera.set_smoothing(TargetSmoothing::Exponential { rate: 3.0 });
+            camera.look_at(target, coords::direction(FORWARD));
+            for _ in 0..steps {
+                camera.smooth_target(1.0 / steps as f32);
+            }
+            camera.target_smoothed
+        };
+
+        let (coarse, fine) = (follow(10), follow(240));
+        assert_eq!(coarse, fine);
+        assert_eq!(
+            coarse,
+            target.lerp(V4::new([0.0, 0.0, 0.0, 1.0]), (-3.0f32).exp())
+        );
+    }
+
+    #[test]
+    fn screen_center_ray_looks_forward() {
+        let mut camera = Camera::new(V4::new([0.0, 2.0, -5.0, 1.0]), V4::zero());
+        camera.set_mode(CameraMode::Orbit);
+        camera.look_at(V4::new([1.0, 2.0, 5.0, 1.0]), coords::direction(FORWARD));
+        let forward = V3::new([1.0, 0.0, 10.0]).norm();
+
+        let (origin, dir) = camera.screen_ray(0.0, 0.0, 16.0 / 9.0);
+        assert_eq!(dir, forward);
+        assert_eq!(origin, V3::new([0.0, 2.0, -5.0]) + camera.near() * forward);
+
+        // Off center rays diverge towards the matching side
+        let (_, right) = camera.screen_ray(0.5, 0.0, 16.0 / 9.0);
+        let (_, up) = camera.screen_ray(0.0, 0.5, 16.0 / 9.0);
+        assert!(right.dot(RIGHT) > dir.dot(RIGHT));
+        assert!(up.dot(UP) > dir.dot(UP));
+    }
+
+    #[test]
+    fn far_plane_stays_beyond_near() {
+        let mut camera = Camera::new(V4::zero(), V4::zero());
+        camera.set_clip_planes(10.0, 1.0);
+        assert!(camera.far() > camera.near());
+    }
+
+    #[test]
+    fn free_camera_flies_forward() {
+        use crate::core::game_input::InputContext;
+        use crate::core::input::{Input, Key};
+        use crate::core::terrain::Terrain;
+
+        let mut input = Input::new();
+        input.key_down(Key::k_W);
+        let mut state = InputContext::default();
+        state.update_state(input.take_state());
+        let terrain = Terrain::new_flat(1, 1);
+        let ctx = Context {
+            dt: std::time::Duration::from_millis(500),
+            state: &state,
+            terrain: &terrain,
+        };
+
+        let start = V4::new([0.0, 2.0, 0.0, 1.0]);
+        let mut camera = Camera::new(start, V4::zero());
+        camera.set_mode(CameraMode::Free);
+        assert_eq!(camera.forward(), coords::direction(FORWARD));
+        camera.orbit(300.0, 0.0);
+        let forward = camera.forward();
+        assert_float_eq!(forward.length(), 1.0);
+
+        camera.update(&ctx).unwrap();
+        assert_eq!(camera.position(), start + 0.5 * FREE_SPEED * forward);
+
+        // looking ignores the target
+        camera.look_at(V4::new([50.0, 0.0, 50.0, 1.0]), coords::direction(FORWARD));
+        camera.update(&ctx).unwrap();
+        assert_eq!(camera.position(), start + FREE_SPEED * forward);
+    }
+}
@@ -1,5 +1,6 @@
-use crate::core::component::Context;
-use crate::core::game_input::GameKey;
+use crate::core::component::{Component, Context};
+use crate::core::coords::{self, FORWARD, RIGHT, UP};
+use crate::core::game_input::{AxisRamp, GameKey, InputContext};
 use crate::core::gl_pipeline_colored::arrow;
 use crate::core::gl_renderer::{
     DefaultMaterials, DefaultMeshes, RenderContext, RenderObject, Transform,
@@ -8,13 +9,21 @@ use crate::core::terrain::Terrain;
 use crate::error::{Error, Result};
 use crate::v2d::{m3x3::M3x3, q::Q, v3::V3, v4::V4};
 use crate::x2d::{
-    self, BodyId, ContactId, JointId, constraint::contact::Contact, constraint::joint::Joint,
-    constraint::softness::Softness, constraint::tire_contact::TireContext, mass::Mass,
-    physics::Physics, rigid_body::RigidBody,
+    self, BodyId, ContactId, JointId,
+    constraint::contact::Contact,
+    constraint::joint::Joint,
+    constraint::softness::Softness,
+    constraint::tire_contact::{TireContext, TireModel},
+    mass::Mass,
+    physics::Physics,
+    rigid_body::RigidBody,
 };
 use std::fmt;
 
 // ----------------------------------------------------------------------------
+// `ride_height` is the height of the chassis centre above the ground with the
+// car at rest. The chassis mass sits at its centre, so this is also the
+// height of the centre of gravity.
 #[derive(Debug, Clone, Default)]
 pub struct Geometry {
     pub length: f32,
@@ -24,9 +33,50 @@ pub struct Geometry {
     pub wheel_track: f32,
     pub wheel_radius: f32,
     pub wheel_width: f32,
+    pub ride_height: f32,
 }
 
 // ----------------------------------------------------------------------------
+// Thickness of the chassis slab, for its mass as well as its render size
+const CHASSIS_HEIGHT: f32 = 0.2;
+
+// ----------------------------------------------------------------------------
+impl Geometry {
+    pub fn chassis_size(&self) -> V3 {
+        V3::new([self.width, CHASSIS_HEIGHT, self.length])
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Spring and damper of each wheel, solved as a soft constraint: `frequency` is
+// the natural frequency in Hz, `damping` the ratio of critical damping.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Suspension {
+    pub frequency: f32,
+    pub damping: f32,
+}
+
+// ----------------------------------------------------------------------------
+impl Default for Suspension {
+    fn default() -> Self {
+        Self {
+            frequency: 3.0,
+            damping: 0.2,
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Everything that makes one car drive differently from another.
+#[derive(Debug, Clone, Default)]
+pub struct CarSetup {
+    pub geometry: Geometry,
+    pub suspension: Suspension,
+    pub tire: TireModel,
+}
+
+// ----------------------------------------------------------------------------
+// Pulls along -coords::UP
 pub const GRAVITY: V3 = V3::new([0.0, -9.81, 0.0]);
 
 // ----------------------------------------------------------------------------
@@ -40,6 +90,7 @@ pub struct WheelData {
     pub body: BodyId,
     pub joint: JointId,
     pub contact: Option<ContactId>,
+    pub tire: TireModel,
 }
 
 // ----------------------------------------------------------------------------
@@ -62,10 +113,40 @@ impl WheelData {
             body,
             joint: wheel_joint,
             contact: None,
+            tire: TireModel::default(),
         }
     }
 }
 
+// ----------------------------------------------------------------------------
+// Per wheel readout of the last physics step for tuning the suspension and
+// tires. Slip and load are zero while the wheel is in the air.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct WheelTelemetry {
+    pub compression: f32,
+    pub normal_impulse: f32,
+    pub normal_load: f32,
+    pub slip_ratio: f32,
+    pub slip_angle: f32,
+    pub angular_velocity: f32,
+}
+
+// ----------------------------------------------------------------------------
+impl fmt::Display for WheelTelemetry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "c {:.3} n {:.1} l {:.0} sr {:.2} sa {:.1} w {:.1}",
+            self.compression,
+            self.normal_impulse,
+            self.normal_load,
+            self.slip_ratio,
+            self.slip_angle.to_degrees(),
+            self.angular_velocity
+        )
+    }
+}
+
 // ----------------------------------------------------------------------------
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum DriveDirection {
@@ -120,6 +201,10 @@ const STOP_DELAY: f32 = 0.3;
 const V_BACKWARD: f32 = -0.5;
 const V_EPSILON: f32 = 0.1;
 
+// Full steering / pedal deflection is reached after 1 / rate seconds
+const STEER_RAMP_RATE: f32 = 5.0;
+const PEDAL_RAMP_RATE: f32 = 2.0;
+
 // ----------------------------------------------------------------------------
 // `drive` and `resist` are abstract — caller maps throttle/brake to them
 fn update_drive_state(state: DriveState, drive: bool, brake: bool, near_stop: bool) -> DriveState {
@@ -220,6 +305,11 @@ fn update_direction_state(
     }
 }
 
+// ----------------------------------------------------------------------------
+fn update_lights(lights_on: bool, input: &InputContext) -> bool {
+    lights_on ^ input.just_pressed(GameKey::Lights)
+}
+
 // ----------------------------------------------------------------------------
 #[derive(Debug)]
 pub struct Car {
@@ -227,11 +317,16 @@ pub struct Car {
     pub wheels: Vec<WheelData>,
     pub objects: [RenderObject; 5],
     pub debug_arrows: [RenderObject; 4],
+    pub headlights: [RenderObject; 2],
+    pub lights_on: bool,
     pub geometry: Geometry,
     pub steering_angle: f32,
+    pub steer_ramp: AxisRamp,
+    pub pedal_ramp: AxisRamp,
     pub chassis_position: V3,
     pub chassis_orientation: Q,
     pub drive_state: DriveStateContext,
+    pub telemetry: [WheelTelemetry; 4],
 }
 
 // ----------------------------------------------------------------------------
@@ -252,15 +347,131 @@ fn raycast_ground(terrain: &Terrain, origin: V3, max_dist: f32) -> Option<(V3, V
     Some((point, normal, t))
 }
 
+// ----------------------------------------------------------------------------
+// Spring compression that carries the chassis at rest. The soft constraint
+// acts like a spring of stiffness m * omega^2, with m the effective mass along
+// the suspension axis; exact for the 100 Hz step the softness is built for.
+// Each of the four springs carries a quarter of the chassis.
+fn static_sag(chassis: &RigidBody, wheel: &Mass, anchor: V3, suspension: Suspension) -> f32 {
+    if suspension.frequency <= 0.0 {
+        return 0.0;
+    }
+    let rn = anchor.cross(UP);
+    let inv_mass = wheel.inv_mass() + chassis.inv_mass() + rn * chassis.inv_inertia() * rn;
+    let omega = std::f32::consts::TAU * suspension.frequency;
+    let load = 0.25 * chassis.mass() * -GRAVITY.dot(UP);
+    load * inv_mass / (omega * omega)
+}
+
+// ----------------------------------------------------------------------------
+// Chassis and wheel bodies joined by the suspension, without render objects.
+// The car is spawned at rest on flat ground at height 0, with every spring
+// compressed by the weight it carries.
+fn create_bodies(physics: &mut Physics, setup: &CarSetup) -> Result<(BodyId, Vec<WheelData>)> {
+    let geo = &setup.geometry;
+
+    // This is temporary and gives the car 952 kg.
+    let chassis_material = x2d::WOOD;
+    let mass = Mass::from_box(chassis_material.density, geo.chassis_size())?;
+
+    let chassis_body = RigidBody::new(
+        String::from("car:chassis"),
+        mass,
+        chassis_material,
+        geo.ride_height * UP,
+        Q::identity(),
+    );
+
+    let wheel_material = x2d::RUBBER;
+    let wheel_mass = Mass::from_wheel(wheel_material.density, geo.wheel_radius)?;
+
+    let track_half = 0.5 * geo.wheel_track;
+    let base_half = 0.5 * geo.wheel_base;
+    let wheels = [
+        (true, false, "FL", base_half * FORWARD - track_half * RIGHT),
+        (true, false, "FR", base_half * FORWARD + track_half * RIGHT),
+        (false, true, "RL", -base_half * FORWARD - track_half * RIGHT),
+        (false, true, "RR", -base_half * FORWARD + track_half * RIGHT),
+    ];
+
+    let rest_length = geo.wheel_radius / 4.0;
+    let wheels = wheels
+        .iter()
+        .map(|(steering, driving, name, offset)| {
+            // Spring anchor on the chassis, placed so that the wheel touches
+            // the ground once the spring carries its share of the weight.
+            let sag = static_sag(&chassis_body, &wheel_mass, *offset, setup.suspension);
+            let local = *offset + (geo.wheel_radius - rest_length - sag - geo.ride_height) * UP;
+            let position =
+                chassis_body.to_world(*offset) + (geo.wheel_radius - geo.ride_height) * UP;
+            let wheel_body = RigidBody::new(
+                String::from(*name),
+                wheel_mass,
+                wheel_material,
+                position,
+                Q::identity(),
+            );
+
+            (*steering, *driving, local, wheel_body)
+        })
+        .collect::<Vec<_>>();
+
+    let chassis_id = physics.add_body(chassis_body);
+
+    let suspension = setup.suspension;
+    let suspension_softness = Softness::new(suspension.frequency, suspension.damping, 1.0 / 100.0);
+
+    let world_basis = coords::basis();
+
+    let wheels = wheels
+        .into_iter()
+        .map(|(steering, driving, local, wheel_body)| {
+            let wheel_id = physics.add_body(wheel_body);
+
+            let joint = Joint::new_wheel(
+                wheel_id,
+                chassis_id,
+                V3::ZERO,
+                local,
+                world_basis,
+                rest_length,
+                suspension_softness,
+            );
+
+            let joint_id = physics.add_joint(joint);
+
+            WheelData {
+                tire: setup.tire,
+                ..WheelData::new(
+                    steering,
+                    driving,
+                    local,
+                    wheel_id,
+                    joint_id,
+                    geo.wheel_radius,
+                    geo.wheel_width,
+                )
+            }
+        })
+        .collect::<Vec<_>>();
+
+    Ok((chassis_id, wheels))
+}
+
 // ----------------------------------------------------------------------------
 impl Car {
     // ------------------------------------------------------------------------
-    pub fn new(context: &mut RenderContext, physics: &mut Physics, geo: Geometry) -> Result<Self> {
+    pub fn new(
+        context: &mut RenderContext,
+        physics: &mut Physics,
+        setup: CarSetup,
+    ) -> Result<Self> {
+        let geo = setup.geometry.clone();
         let mut debug_arrows = Vec::new();
         for _ in 0..4 {
             let arrow_verts = arrow(V3::ZERO, V3::X0)?;
             let debug_arrow = RenderObject {
-                name: String::from("car:debug_arrow_left"),
+                name: "car:debug_arrow_left".into(),
                 transform: Transform::default(),
                 pipe_id: 0,
                 mesh_id: context.create_colored_mesh(&arrow_verts, &[], true)?,
@@ -275,94 +486,36 @@ impl Car {
         transform_mesh(
             &mut verts,
             V3::default(),
-            M3x3::from_cols(-V3::X1, V3::X0, V3::X2),
+            M3x3::from_cols(-UP, RIGHT, FORWARD),
         );
         let wheel_mesh_id = context.create_colored_mesh(&verts, &indices, false)?;
         let chassis_mesh_id = context.default_mesh(DefaultMeshes::Cube);
 
-        // This is temporary and gives the car 952 kg.
-        let chassis_material = x2d::WOOD;
-        let dimensions = V3::new([geo.width, 0.2, geo.length]);
-        let mass = Mass::from_box(chassis_material.density, dimensions)?;
-
-        let chassis_body = RigidBody::new(
-            String::from("car:chassis"),
-            mass,
-            chassis_material,
-            V3::new([0.0, 2.0 + geo.wheel_radius + 0.2, 0.0]),
-            Q::identity(),
-        );
-
-        let wheel_material = x2d::RUBBER;
-        let wheel_mass = Mass::from_wheel(wheel_material.density, geo.wheel_radius)?;
-
-        let track_half = 0.5 * geo.wheel_track;
-        let base_half = 0.5 * geo.wheel_base;
-        let wheels = [
-            (true, false, "FL", V3::new([-track_half, 0.0, base_half])),
-            (true, false, "FR", V3::new([track_half, 0.0, base_half])),
-            (false, true, "RL", V3::new([-track_half, 0.0, -base_half])),
-            (false, true, "RR", V3::new([track_half, 0.0, -base_half])),
+        let headlight = |name: &str| RenderObject {
+            name: name.into(),
+            transform: Transform {
+                size: V4::new([0.3, 0.1, 0.05, 1.0]),
+                ..Default::default()
+            },
+            pipe_id: 0,
+            mesh_id: chassis_mesh_id,
+            material_id: context.default_material(DefaultMaterials::Yellow),
+            ..Default::default()
+        };
+        let headlights = [
+            headlight("car:headlight_left"),
+            headlight("car:headlight_right"),
         ];
 
-        let wheels = wheels
-            .iter()
-            .map(|(steering, driving, name, local)| {
-                let offset = chassis_body.to_world(*local);
-                let wheel_body = RigidBody::new(
-                    String::from(*name),
-                    wheel_mass,
-                    wheel_material,
-                    offset,
-                    Q::identity(),
-                );
-
-                (*steering, *driving, *local, wheel_body)
-            })
-            .collect::<Vec<_>>();
-
-        let chassis_id = physics.add_body(chassis_body);
-
-        let suspension_softness = Softness::new(3.0, 0.2, 1.0 / 100.0);
-
-        let world_basis = M3x3::from_cols(V3::X0, V3::X1, V3::X2);
-
-        let wheels = wheels
-            .into_iter()
-            .map(|(steering, driving, local, wheel_body)| {
-                let wheel_id = physics.add_body(wheel_body);
-
-                let joint = Joint::new_wheel(
-                    wheel_id,
-                    chassis_id,
-                    V3::ZERO,
-                    local,
-                    world_basis,
-                    geo.wheel_radius / 4.0,
-                    suspension_softness,
-                );
-
-                let joint_id = physics.add_joint(joint);
-
-                WheelData::new(
-                    steering,
-                    driving,
-                    local,
-                    wheel_id,
-                    joint_id,
-                    geo.wheel_radius,
-                    geo.wheel_width,
-                )
-            })
-            .collect::<Vec<_>>();
+        let (chassis_id, wheels) = create_bodies(physics, &setup)?;
 
         Ok(Self {
             chassis: chassis_id,
             objects: [
                 RenderObject {
-                    name: String::from("car:chassis"),
+                    name: "car:chassis".into(),
                     transform: Transform {
-                        size: V4::new([geo.width, 0.2, geo.length, 1.0]),
+                        size: V4::from_v3(geo.chassis_size(), 1.0),
                         ..Default::default()
                     },
                     pipe_id: 0,
@@ -371,7 +524,7 @@ impl Car {
                     ..Default::default()
                 },
                 RenderObject {
-                    name: String::from("car:wheel:front_left"),
+                    name: "car:wheel:front_left".into(),
                     transform: Transform::default(),
                     pipe_id: 0,
                     mesh_id: wheel_mesh_id,
@@ -379,7 +532,7 @@ impl Car {
                     ..Default::default()
                 },
                 RenderObject {
-                    name: String::from("car:wheel:front_right"),
+                    name: "car:wheel:front_right".into(),
                     transform: Transform::default(),
                     pipe_id: 0,
                     mesh_id: wheel_mesh_id,
@@ -387,7 +540,7 @@ impl Car {
                     ..Default::default()
                 },
                 RenderObject {
-                    name: String::from("car:wheel:rear_left"),
+                    name: "car:wheel:rear_left".into(),
                     transform: Transform::default(),
                     pipe_id: 0,
                     mesh_id: wheel_mesh_id,
@@ -395,7 +548,7 @@ impl Car {
                     ..Default::default()
                 },
                 RenderObject {
-                    name: String::from("car:wheel:rear_right"),
+                    name: "car:wheel:rear_right".into(),
                     transform: Transform::default(),
                     pipe_id: 0,
                     mesh_id: wheel_mesh_id,
@@ -404,12 +557,17 @@ impl Car {
                 },
             ],
             debug_arrows: debug_arrows.try_into().unwrap(),
+            headlights,
+            lights_on: false,
             wheels,
             geometry: geo,
             steering_angle: 0.0,
+            steer_ramp: AxisRamp::new(STEER_RAMP_RATE),
+            pedal_ramp: AxisRamp::new(PEDAL_RAMP_RATE),
             chassis_position: V3::ZERO,
             chassis_orientation: Q::identity(),
             drive_state: DriveStateContext::default(),
+            telemetry: Default::default(),
         })
     }
 
@@ -451,9 +609,9 @@ impl Car {
     // ------------------------------------------------------------------------
     pub fn transform(&self, physics: &Physics) -> Result<(V4, V4)> {
         let chassis_body = physics.get_body(self.chassis).ok_or(Error::InvalidBodyId)?;
-        let forward = chassis_body.orientation().rotate(V3::X2);
+        let forward = chassis_body.to_world_dir(FORWARD);
         let position = chassis_body.position();
-        Ok((V4::from_v3(forward, 0.0), V4::from_v3(position, 1.0)))
+        Ok((coords::direction(forward), coords::point(position)))
     }
 
     // ------------------------------------------------------------------------
@@ -462,7 +620,18 @@ impl Car {
     }
 
     // ------------------------------------------------------------------------
-    pub fn update(&mut self, ctx: &Context, physics: &mut Physics) -> Result<()> {
+    // Handles one-shot actions once per frame, right after the input state
+    // changed. Updates can run several times per frame and would see the same
+    // key press again.
+    pub fn handle_actions(&mut self, input: &InputContext) {
+        if input.just_pressed(GameKey::Horn) {
+            log::info!("car: horn");
+        }
+        self.lights_on = update_lights(self.lights_on, input);
+    }
+
+    // ------------------------------------------------------------------------
+    pub fn apply_controls(&mut self, ctx: &Context, physics: &mut Physics) -> Result<()> {
         const TURN_SPEED: f32 = 1.5;
         const DRIVE_TORQUE: f32 = 4000.0;
         const BRAKE_TORQUE: f32 = 2000.0;
@@ -472,17 +641,22 @@ impl Car {
         let throttle = ctx.state.is_pressed(GameKey::Accelerate);
         let brake = ctx.state.is_pressed(GameKey::Brake);
 
-        if ctx.state.is_pressed(GameKey::SteerLeft) {
-            self.steering_angle -= TURN_SPEED * dt;
-        }
-        if ctx.state.is_pressed(GameKey::SteerRight) {
-            self.steering_angle += TURN_SPEED * dt;
+        let steer = ctx.state.axis(GameKey::SteerLeft, GameKey::SteerRight);
+        self.steering_angle += TURN_SPEED * self.steer_ramp.update(steer, dt) * dt;
+
+        let pedal = ctx.state.axis(GameKey::Brake, GameKey::Accelerate).abs();
+        let pedal = self.pedal_ramp.update(pedal, dt);
+
+        // Driver input moves the car even if the motor impulses are too small
+        // to wake resting bodies on their own
+        if throttle || brake || steer != 0.0 {
+            self.wake(physics);
         }
 
         let chassis_body = physics.get_body(self.chassis).ok_or(Error::InvalidBodyId)?;
         let chassis_orientation = chassis_body.orientation();
 
-        let forward = chassis_orientation.rotate(V3::X2);
+        let forward = chassis_body.to_world_dir(FORWARD);
         let v_long = chassis_body.linear_velocity().dot(forward);
 
         self.drive_state = update_direction_state(&self.drive_state, throttle, brake, v_long, dt);
@@ -491,8 +665,8 @@ impl Car {
         let (free_speed, free_torque, drive_speed, drive_torque) = match self.drive_state.state {
             DriveState::Coast => (0.0, 0.0, 0.0, ENGINE_BRAKE_TORQUE),
             DriveState::Drive => match self.drive_state.direction {
-                DriveDirection::Forward => (0.0, 0.0, -max_speed, DRIVE_TORQUE),
-                DriveDirection::Reverse => (0.0, 0.0, max_speed, DRIVE_TORQUE),
+                DriveDirection::Forward => (0.0, 0.0, -max_speed, DRIVE_TORQUE * pedal),
+                DriveDirection::Reverse => (0.0, 0.0, max_speed, DRIVE_TORQUE * pedal),
             },
             DriveState::DriveBraking => match self.drive_state.direction {
                 DriveDirection::Forward => (0.0, BRAKE_TORQUE, -max_speed, DRIVE_TORQUE),
@@ -542,7 +716,7 @@ impl Car {
                     normal,
                     penetration,
                     normal_force,
-                    friction: 2.8,
+                    tire: wheel_data.tire,
                 };
 
                 if let Some(contact_id) = wheel_data.contact {
@@ -565,6 +739,16 @@ impl Car {
         Ok(())
     }
 
+    // ------------------------------------------------------------------------
+    pub fn wake(&self, physics: &mut Physics) {
+        let bodies = std::iter::once(self.chassis).chain(self.wheels.iter().map(|w| w.body));
+        for id in bodies {
+            if let Some(body) = physics.get_body_mut(id) {
+                body.wake();
+            }
+        }
+    }
+
     // ------------------------------------------------------------------------
     pub fn apply_gravity(&mut self, physics: &mut Physics) -> Result<()> {
         let chassis_body = physics
@@ -584,6 +768,58 @@ impl Car {
         Ok(())
     }
 
+    // ------------------------------------------------------------------------
+    pub fn telemetry(&self) -> [WheelTelemetry; 4] {
+        self.telemetry
+    }
+
+    // ------------------------------------------------------------------------
+    // Summed normal load of the front and rear wheels; braking shifts load to
+    // the front, accelerating to the rear.
+    pub fn axle_loads(&self) -> (f32, f32) {
+        self.wheels.iter().zip(&self.telemetry).fold(
+            (0.0, 0.0),
+            |(front, rear), (wheel, telemetry)| {
+                if wheel.local_position.dot(FORWARD) > 0.0 {
+                    (front + telemetry.normal_load, rear)
+                } else {
+                    (front, rear + telemetry.normal_load)
+                }
+            },
+        )
+    }
+
+    // ------------------------------------------------------------------------
+    pub fn update_telemetry(&mut self, physics: &Physics) -> Result<()> {
+        let chassis_body = physics.get_body(self.chassis).ok_or(Error::InvalidBodyId)?;
+        let axle = chassis_body.orientation().as_mat3x3().col0();
+
+        for (wheel_data, telemetry) in self.wheels.iter().zip(self.telemetry.iter_mut()) {
+            let wheel_body = physics
+                .get_body(wheel_data.body)
+                .ok_or(Error::InvalidBodyId)?;
+            let joint = physics
+                .get_joint(wheel_data.joint)
+                .ok_or(Error::InvalidJointId)?;
+            let wheel_joint = joint.as_wheel().ok_or(Error::InvalidJointType)?;
+            let tire = wheel_data
+                .contact
+                .and_then(|id| physics.get_contact(id))
+                .and_then(|contact| contact.as_tire());
+
+            *telemetry = WheelTelemetry {
+                compression: wheel_joint.compression(),
+                normal_impulse: tire.map_or(0.0, |t| t.normal_impulse()),
+                normal_load: tire.map_or(0.0, |t| t.normal_force()),
+                slip_ratio: tire.map_or(0.0, |t| t.slip_ratio()),
+                slip_angle: tire.map_or(0.0, |t| t.slip_angle()),
+                angular_velocity: wheel_body.angular_velocity().dot(axle),
+            };
+        }
+
+        Ok(())
+    }
+
     // ------------------------------------------------------------------------
     pub fn update_render_objects(&mut self, physics: &Physics) -> Result<()> {
         let chassis_body = physics.get_body(self.chassis).ok_or(Error::InvalidBodyId)?;
@@ -591,8 +827,16 @@ impl Car {
         self.chassis_position = chassis_body.position();
         self.chassis_orientation = chassis_body.orientation();
 
-        self.objects[0].transform.rotation = self.chassis_orientation.into();
-        self.objects[0].transform.position = V4::from_v3(self.chassis_position, 1.0);
+        let chassis_size = self.geometry.chassis_size();
+        self.objects[0].transform = chassis_body.transform_with_scale(chassis_size);
+
+        let front = 0.5 * self.geometry.length;
+        let side = 0.3 * self.geometry.width;
+        for (headlight, x) in self.headlights.iter_mut().zip([-side, side]) {
+            let local = front * FORWARD + x * RIGHT;
+            headlight.transform.rotation = self.chassis_orientation.into();
+            headlight.transform.position = V4::from_v3(chassis_body.to_world(local), 1.0);
+        }
 
         for (wheel_data, render_obj) in self.wheels.iter().zip(self.objects[1..].iter_mut()) {
             let wheel_body = physics
@@ -602,13 +846,305 @@ impl Car {
             render_obj.transform = wheel_body.transform();
 
             if wheel_data.is_steering {
-                let steering = Q::from_axis_angle(V3::X1, self.steering_angle);
+                let steering = Q::from_axis_angle(UP, self.steering_angle);
                 render_obj.transform.rotation = (steering * wheel_body.orientation()).into();
             } else {
                 render_obj.transform.rotation = wheel_body.orientation().into();
             }
         }
 
+        self.update_telemetry(physics)
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Driving happens in `Car::apply_controls` once per physics sub-step and
+// one-shot actions in `Car::handle_actions` once per frame.
+impl Component for Car {
+    fn update(&mut self, _ctx: &Context) -> Result<()> {
         Ok(())
     }
+
+    // Headlights are only drawn while switched on.
+    fn render_objects(&self) -> impl Iterator<Item = &RenderObject> {
+        let headlights = if self.lights_on {
+            &self.headlights[..]
+        } else {
+            &[]
+        };
+        self.objects.iter().chain(headlights)
+    }
+
+    fn debug_objects(&self) -> impl Iterator<Item = &RenderObject> {
+        self.debug_arrows.iter()
+    }
+}
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_float_eq;
+    use crate::core::input::{Input, Key};
+
+    fn test_setup() -> CarSetup {
+        CarSetup {
+            geometry: Geometry {
+                length: 4.0,
+                width: 1.7,
+                height: 1.5,
+                wheel_base: 2.5,
+                wheel_track: 2.0,
+                wheel_radius: 0.4,
+                wheel_width: 0.3,
+                ride_height: 0.6,
+            },
+            ..Default::default()
+        }
+    }
+
+    // Runs `frames` frames of the context's frame time, updating the car
+    // before every physics sub-step like World does.
+    fn step_car(physics: &mut Physics, car: &mut Car, ctx: &Context, frames: usize) {
+        for _ in 0..frames {
+            physics
+                .step_substeps(ctx.dt_secs(), |physics, h| {
+                    let ctx = Context {
+                        dt: std::time::Duration::from_secs_f32(h),
+                        ..*ctx
+                    };
+                    car.apply_controls(&ctx, physics)?;
+                    car.apply_gravity(physics)
+                })
+                .unwrap();
+        }
+    }
+
+    // Sets the chassis and the wheels moving forward, without wheel spin.
+    fn launch(physics: &mut Physics, car: &Car, speed: f32) {
+        let bodies = std::iter::once(car.chassis).chain(car.wheels.iter().map(|w| w.body));
+        for id in bodies {
+            physics
+                .get_body_mut(id)
+                .unwrap()
+                .set_velocity(speed * FORWARD);
+        }
+    }
+
+    // Car with physics bodies only, for tests that don't render.
+    fn physics_car(physics: &mut Physics) -> Car {
+        physics_car_with(physics, test_setup())
+    }
+
+    fn physics_car_with(physics: &mut Physics, setup: CarSetup) -> Car {
+        let (chassis, wheels) = create_bodies(physics, &setup).unwrap();
+        let geo = setup.geometry;
+        Car {
+            chassis,
+            wheels,
+            objects: Default::default(),
+            debug_arrows: Default::default(),
+            headlights: Default::default(),
+            lights_on: false,
+            geometry: geo,
+            steering_angle: 0.0,
+            steer_ramp: AxisRamp::new(STEER_RAMP_RATE),
+            pedal_ramp: AxisRamp::new(PEDAL_RAMP_RATE),
+            chassis_position: V3::ZERO,
+            chassis_orientation: Q::identity(),
+            drive_state: DriveStateContext::default(),
+            telemetry: Default::default(),
+        }
+    }
+
+    #[test]
+    fn headlights_render_only_when_on() {
+        let mut physics = Physics::new();
+        let mut car = physics_car(&mut physics);
+        assert_eq!(car.render_objects().count(), 5);
+        car.lights_on = true;
+        assert_eq!(car.render_objects().count(), 7);
+        assert_eq!(car.debug_objects().count(), 4);
+    }
+
+    #[test]
+    fn telemetry_reports_settled_wheels() {
+        let mut physics = Physics::new();
+        let mut car = physics_car(&mut physics);
+
+        let (state, terrain) = (InputContext::default(), Terrain::new_flat(1, 1));
+        let ctx = Context {
+            dt: std::time::Duration::from_millis(16),
+            state: &state,
+            terrain: &terrain,
+        };
+
+        step_car(&mut physics, &mut car, &ctx, 120);
+        car.update_telemetry(&physics).unwrap();
+
+        for wheel in car.telemetry() {
+            assert!(
+                wheel.compression > 0.0,
+                "loaded wheel not compressed: {wheel}"
+            );
+            assert!(wheel.slip_ratio.is_finite() && wheel.slip_angle.is_finite());
+            assert!(wheel.angular_velocity.is_finite());
+            assert!(wheel.normal_impulse > 0.0, "wheel not loaded: {wheel}");
+        }
+    }
+
+    #[test]
+    fn chassis_rests_at_ride_height() {
+        let mut physics = Physics::new();
+        let mut car = physics_car(&mut physics);
+
+        let (state, terrain) = (InputContext::default(), Terrain::new_flat(1, 1));
+        let ctx = Context {
+            dt: std::time::Duration::from_millis(10),
+            state: &state,
+            terrain: &terrain,
+        };
+
+        let chassis = car.chassis;
+        let chassis_height =
+            |physics: &Physics| physics.get_body(chassis).unwrap().position().dot(UP);
+        assert_float_eq!(chassis_height(&physics), 0.6);
+
+        for _ in 0..300 {
+            step_car(&mut physics, &mut car, &ctx, 1);
+            let height = chassis_height(&physics);
+            assert!((height - 0.6).abs() < 0.01, "chassis at {height}");
+        }
+    }
+
+    #[test]
+    fn stiffer_springs_compress_less() {
+        let terrain = Terrain::new_flat(1, 1);
+        let state = InputContext::default();
+        let ctx = Context {
+            dt: std::time::Duration::from_millis(16),
+            state: &state,
+            terrain: &terrain,
+        };
+
+        let settled_compression = |frequency| {
+            let mut physics = Physics::new();
+            let setup = CarSetup {
+                suspension: Suspension {
+                    frequency,
+                    ..Default::default()
+                },
+                ..test_setup()
+            };
+            let mut car = physics_car_with(&mut physics, setup);
+            step_car(&mut physics, &mut car, &ctx, 120);
+            car.update_telemetry(&physics).unwrap();
+            car.telemetry().iter().map(|w| w.compression).sum::<f32>()
+        };
+
+        let soft = settled_compression(3.0);
+        let stiff = settled_compression(6.0);
+        assert!(0.0 < stiff && stiff < soft, "stiff {stiff} soft {soft}");
+    }
+
+    #[test]
+    fn gripping_wheels_roll_at_ground_speed() {
+        let mut physics = Physics::new();
+        let mut car = physics_car(&mut physics);
+
+        let (state, terrain) = (InputContext::default(), Terrain::new_flat(1, 1));
+        let ctx = Context {
+            dt: std::time::Duration::from_millis(10),
+            state: &state,
+            terrain: &terrain,
+        };
+
+        // Launched without wheel spin, the tires first slip and spin the wheels
+        // up until they roll
+        launch(&mut physics, &car, 10.0);
+
+        step_car(&mut physics, &mut car, &ctx, 300);
+        car.update_telemetry(&physics).unwrap();
+
+        let chassis = physics.get_body(car.chassis).unwrap();
+        let ground_speed = chassis.linear_velocity().dot(chassis.to_world_dir(FORWARD));
+        assert!(ground_speed > 1.0, "car stopped at {ground_speed}");
+
+        for (wheel, telemetry) in car.wheels.iter().zip(car.telemetry()) {
+            assert!(
+                telemetry.slip_ratio.abs() < 0.01,
+                "wheel slips: {telemetry}"
+            );
+            let rolling_speed = telemetry.angular_velocity * wheel.radius;
+            assert!(
+                (rolling_speed - ground_speed).abs() < 0.01 * ground_speed,
+                "wheel rolls at {rolling_speed}, car moves at {ground_speed}"
+            );
+        }
+    }
+
+    #[test]
+    fn braking_shifts_load_to_the_front() {
+        let mut physics = Physics::new();
+        let mut car = physics_car(&mut physics);
+
+        let mut input = Input::new();
+        let mut state = InputContext::default();
+        let terrain = Terrain::new_flat(1, 1);
+        let dt = std::time::Duration::from_millis(16);
+
+        let run = |car: &mut Car, physics: &mut Physics, state: &InputContext, frames| {
+            let ctx = Context {
+                dt,
+                state,
+                terrain: &terrain,
+            };
+            step_car(physics, car, &ctx, frames);
+            car.update_telemetry(physics).unwrap();
+            car.axle_loads()
+        };
+
+        let (front, rear) = run(&mut car, &mut physics, &state, 120);
+        assert!(front > 0.0 && rear > 0.0);
+
+        launch(&mut physics, &car, 10.0);
+        input.key_down(Key::k_S);
+        state.update_state(input.take_state());
+
+        let (braking_front, braking_rear) = run(&mut car, &mut physics, &state, 10);
+        assert!(
+            braking_front / braking_rear > front / rear,
+            "front {braking_front} rear {braking_rear}, at rest {front} {rear}"
+        );
+    }
+
+    #[test]
+    fn lights_toggle_once_per_press() {
+        let mut input = Input::new();
+        let mut ctx = InputContext::default();
+        let mut lights_on = false;
+
+        let mut frame = |input: &Input, lights_on: &mut bool| {
+            ctx.update_state(input.take_state());
+            *lights_on = update_lights(*lights_on, &ctx);
+        };
+
+        input.key_down(Key::k_L);
+        frame(&input, &mut lights_on);
+        assert!(lights_on);
+
+        // holding the key does not flicker
+        for _ in 0..5 {
+            frame(&input, &mut lights_on);
+            assert!(lights_on);
+        }
+
+        input.key_up(Key::k_L);
+        frame(&input, &mut lights_on);
+        assert!(lights_on);
+
+        input.key_down(Key::k_L);
+        frame(&input, &mut lights_on);
+        assert!(!lights_on);
+    }
 }
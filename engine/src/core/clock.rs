@@ -1,8 +1,55 @@
 use crate::core::IClock;
 
+// ----------------------------------------------------------------------------
+// How `Clock::sleep` waits. OS sleep only wakes up at the next scheduler tick,
+// which makes frame times jitter; spinning is exact but keeps a core busy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SleepStrategy {
+    #[default]
+    OsSleep,
+    SpinWait,
+    Hybrid, // OS sleep for all but the last SPIN_MARGIN, then spin
+}
+
+// Longer than the typical scheduler granularity
+const SPIN_MARGIN: std::time::Duration = std::time::Duration::from_millis(2);
+
+// ----------------------------------------------------------------------------
+// Waits for `dt` on the time source `now`, using `os_sleep` as far as the
+// strategy allows. Returns the time after waiting.
+fn wait(
+    strategy: SleepStrategy,
+    dt: std::time::Duration,
+    now: impl Fn() -> std::time::Duration,
+    mut os_sleep: impl FnMut(std::time::Duration),
+) -> std::time::Duration {
+    let deadline = now() + dt;
+    match strategy {
+        SleepStrategy::OsSleep => {
+            os_sleep(dt);
+            return now();
+        }
+        SleepStrategy::SpinWait => (),
+        SleepStrategy::Hybrid => {
+            let t_os = dt.saturating_sub(SPIN_MARGIN);
+            if !t_os.is_zero() {
+                os_sleep(t_os);
+            }
+        }
+    }
+
+    let mut t = now();
+    while t < deadline {
+        std::hint::spin_loop();
+        t = now();
+    }
+    t
+}
+
 // ----------------------------------------------------------------------------
 pub struct Clock {
     t0: std::time::Instant,
+    strategy: SleepStrategy,
 }
 
 // ----------------------------------------------------------------------------
@@ -13,8 +60,7 @@ impl IClock for Clock {
     }
 
     fn sleep(&self, dt: std::time::Duration) -> std::time::Duration {
-        std::thread::sleep(dt);
-        self.now()
+        wait(self.strategy, dt, || self.now(), std::thread::sleep)
     }
 }
 
@@ -28,8 +74,94 @@ impl Default for Clock {
 // ----------------------------------------------------------------------------
 impl Clock {
     pub fn new() -> Self {
+        Self::with_strategy(SleepStrategy::default())
+    }
+
+    pub fn with_strategy(strategy: SleepStrategy) -> Self {
         Clock {
             t0: std::time::Instant::now(),
+            strategy,
         }
     }
+
+    pub fn strategy(&self) -> SleepStrategy {
+        self.strategy
+    }
+}
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::time::Duration;
+
+    // Time source whose reads take 10 us and whose OS sleep oversleeps to
+    // the next 1 ms tick.
+    struct MockTime {
+        t: Cell<Duration>,
+        os_sleeps: Cell<usize>,
+    }
+
+    impl MockTime {
+        fn new() -> Self {
+            Self {
+                t: Cell::new(Duration::from_micros(300)),
+                os_sleeps: Cell::new(0),
+            }
+        }
+
+        fn now(&self) -> Duration {
+            self.t.set(self.t.get() + Duration::from_micros(10));
+            self.t.get()
+        }
+
+        fn os_sleep(&self, dt: Duration) {
+            let tick = Duration::from_millis(1).as_nanos();
+            let wake = (self.t.get() + dt).as_nanos().div_ceil(tick) * tick;
+            self.t.set(Duration::from_nanos(wake as u64));
+            self.os_sleeps.set(self.os_sleeps.get() + 1);
+        }
+
+        fn wait(&self, strategy: SleepStrategy, dt: Duration) -> Duration {
+            let t0 = self.t.get();
+            wait(strategy, dt, || self.now(), |dt| self.os_sleep(dt)) - t0
+        }
+    }
+
+    #[test]
+    fn sleeps_at_least_the_requested_time() {
+        let dt = Duration::from_micros(7500);
+        for strategy in [
+            SleepStrategy::OsSleep,
+            SleepStrategy::SpinWait,
+            SleepStrategy::Hybrid,
+        ] {
+            let time = MockTime::new();
+            let slept = time.wait(strategy, dt);
+            assert!(slept >= dt, "{strategy:?} slept {slept:?}");
+        }
+    }
+
+    #[test]
+    fn spinning_ends_on_time() {
+        let dt = Duration::from_micros(7500);
+        let precise = dt + Duration::from_micros(20);
+
+        let time = MockTime::new();
+        assert!(time.wait(SleepStrategy::OsSleep, dt) > precise);
+
+        let time = MockTime::new();
+        assert!(time.wait(SleepStrategy::SpinWait, dt) <= precise);
+        assert_eq!(time.os_sleeps.get(), 0);
+
+        let time = MockTime::new();
+        assert!(time.wait(SleepStrategy::Hybrid, dt) <= precise);
+        assert_eq!(time.os_sleeps.get(), 1);
+
+        // Too short to hand to the OS
+        let time = MockTime::new();
+        time.wait(SleepStrategy::Hybrid, SPIN_MARGIN);
+        assert_eq!(time.os_sleeps.get(), 0);
+    }
 }
@@ -1,4 +1,5 @@
 use crate::core::game_input;
+use crate::core::gl_renderer::RenderObject;
 use crate::core::terrain;
 use crate::error::Result;
 use std::time::Duration;
@@ -23,4 +24,71 @@ pub trait Component {
     fn update(&mut self, ctx: &Context) -> Result<()>;
     fn solve_constraints(&mut self) {}
     fn integrate_positions(&mut self, _dt: f32) {}
+
+    // Objects to draw this frame.
+    fn render_objects(&self) -> impl Iterator<Item = &RenderObject> {
+        std::iter::empty()
+    }
+
+    // Debug overlays, only drawn while they are switched on.
+    fn debug_objects(&self) -> impl Iterator<Item = &RenderObject> {
+        std::iter::empty()
+    }
+}
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::gl_renderer::copy_objects;
+
+    struct Stub {
+        objects: Vec<RenderObject>,
+    }
+
+    impl Stub {
+        fn new(names: &[&str]) -> Self {
+            let objects = names
+                .iter()
+                .map(|&name| RenderObject {
+                    name: name.into(),
+                    ..Default::default()
+                })
+                .collect();
+            Self { objects }
+        }
+    }
+
+    impl Component for Stub {
+        fn update(&mut self, _ctx: &Context) -> Result<()> {
+            Ok(())
+        }
+
+        fn render_objects(&self) -> impl Iterator<Item = &RenderObject> {
+            self.objects.iter()
+        }
+    }
+
+    struct Invisible;
+
+    impl Component for Invisible {
+        fn update(&mut self, _ctx: &Context) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn render_objects_concatenate() {
+        let (a, b) = (Stub::new(&["a0", "a1"]), Stub::new(&["b0"]));
+        let mut objects = Vec::new();
+        copy_objects(
+            &mut objects,
+            a.render_objects()
+                .chain(Invisible.render_objects())
+                .chain(b.render_objects()),
+        );
+
+        let names: Vec<_> = objects.iter().map(RenderObject::name).collect();
+        assert_eq!(names, ["a0", "a1", "b0"]);
+    }
 }
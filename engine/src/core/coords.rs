@@ -0,0 +1,94 @@
+use crate::v2d::{m3x3::M3x3, v2::V2, v3::V3, v4::V4};
+
+// ----------------------------------------------------------------------------
+// World space conventions shared by physics and rendering.
+//
+// The world is left-handed with +Y up: an object facing FORWARD (+Z) has
+// RIGHT (+X) on its right hand side. This matches the Direct3D style
+// `affine4x4::look_at` and `affine4x4::perspective`, whose view space also
+// looks down +Z with +X to the right and +Y up. Physics bodies, render objects
+// and the camera all live in this one space, so converting between them only
+// adds the homogeneous coordinate.
+//
+// The terrain is a height field over the ground plane spanned by RIGHT and
+// FORWARD; `ground` and `from_ground` convert between the two.
+
+// ----------------------------------------------------------------------------
+pub const RIGHT: V3 = V3::X0;
+pub const UP: V3 = V3::X1;
+pub const FORWARD: V3 = V3::X2;
+
+// ----------------------------------------------------------------------------
+// Columns are right, up, forward; the identity in world space.
+pub const fn basis() -> M3x3 {
+    M3x3::from_cols(RIGHT, UP, FORWARD)
+}
+
+// ----------------------------------------------------------------------------
+// Physics position to a homogeneous render position.
+pub const fn point(p: V3) -> V4 {
+    V4::from_v3(p, 1.0)
+}
+
+// ----------------------------------------------------------------------------
+// Physics direction to a homogeneous render direction, unaffected by
+// translation.
+pub const fn direction(d: V3) -> V4 {
+    V4::from_v3(d, 0.0)
+}
+
+// ----------------------------------------------------------------------------
+// Projects a world position onto the terrain's ground plane.
+pub const fn ground(p: V3) -> V2 {
+    V2::new([p.x0(), p.x2()])
+}
+
+// ----------------------------------------------------------------------------
+pub const fn from_ground(p: V2, height: f32) -> V3 {
+    V3::new([p.x0(), height, p.x1()])
+}
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v2d::{affine4x4, q::Q};
+
+    #[test]
+    fn basis_is_left_handed() {
+        assert_eq!(RIGHT.cross(UP), FORWARD);
+        assert_eq!(basis(), M3x3::identity());
+    }
+
+    #[test]
+    fn view_looks_down_forward() {
+        let eye = point(V3::ZERO);
+        let view = affine4x4::look_at(eye, point(FORWARD), direction(UP));
+
+        assert_eq!(view * point(5.0 * FORWARD), V4::new([0.0, 0.0, 5.0, 1.0]));
+        assert_eq!(view * direction(RIGHT), V4::new([1.0, 0.0, 0.0, 0.0]));
+        assert_eq!(view * direction(UP), V4::new([0.0, 1.0, 0.0, 0.0]));
+
+        // Points in front of the camera end up with a positive w
+        let projection = affine4x4::perspective(60.0, 1.0, 0.1, 100.0);
+        let clip = projection * view * point(5.0 * FORWARD);
+        assert!(clip.x3() > 0.0);
+    }
+
+    #[test]
+    fn positive_yaw_turns_right() {
+        let yaw = Q::from_axis_angle(UP, 0.1);
+        let heading = yaw.rotate(FORWARD);
+        assert!(heading.dot(RIGHT) > 0.0);
+        assert!(heading.dot(FORWARD) > 0.0);
+    }
+
+    #[test]
+    fn ground_plane_round_trip() {
+        let p = V3::new([1.0, 2.0, 3.0]);
+        assert_eq!(ground(p), V2::new([1.0, 3.0]));
+        assert_eq!(from_ground(ground(p), p.x1()), p);
+        assert_eq!(point(p).x3(), 1.0);
+        assert_eq!(direction(p).x3(), 0.0);
+    }
+}
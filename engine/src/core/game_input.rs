@@ -36,13 +36,17 @@ pub enum GameKey {
     Handbrake = 21,
     Horn = 22,
     Lights = 23,
+
+    // Debugging
+    ToggleDebug = 24,
 }
 
 // ----------------------------------------------------------------------------
 #[derive(Debug, Clone)]
 pub struct InputContext {
-    mapping: [Key; GameKey::Lights as usize + 1],
+    mapping: [Key; GameKey::ToggleDebug as usize + 1],
     state: State,
+    previous: State,
 }
 
 // ----------------------------------------------------------------------------
@@ -74,8 +78,10 @@ impl Default for InputContext {
                 Key::k_Space,     // Handbrake
                 Key::k_H,         // Horn
                 Key::k_L,         // Lights
+                Key::k_F3,        // ToggleDebug
             ],
             state: State::default(),
+            previous: State::default(),
         }
     }
 }
@@ -83,11 +89,129 @@ impl Default for InputContext {
 // ----------------------------------------------------------------------------
 impl InputContext {
     pub fn update_state(&mut self, state: State) {
-        self.state = state;
+        self.previous = std::mem::replace(&mut self.state, state);
+    }
+
+    // The mapping has an entry for every GameKey, so this can't go out of
+    // bounds.
+    pub fn mapped_key(&self, key: GameKey) -> Key {
+        self.mapping[key as usize]
     }
 
     pub fn is_pressed(&self, key: GameKey) -> bool {
-        let key = self.mapping.get(key as usize);
-        key.is_some_and(|&k| self.state.is_pressed(k))
+        self.state.is_pressed(self.mapped_key(key))
+    }
+
+    // True only for the first update in which the key is held.
+    pub fn just_pressed(&self, key: GameKey) -> bool {
+        let key = self.mapped_key(key);
+        self.state.is_pressed(key) && !self.previous.is_pressed(key)
+    }
+
+    // Combines two opposing keys into -1, 0 or +1; 0 if both are held.
+    pub fn axis(&self, neg: GameKey, pos: GameKey) -> f32 {
+        self.is_pressed(pos) as i32 as f32 - self.is_pressed(neg) as i32 as f32
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Moves towards a target axis value at a limited rate, so that digital keys
+// behave like an analog stick that is pushed over time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AxisRamp {
+    value: f32,
+    rate: f32,
+}
+
+// ----------------------------------------------------------------------------
+impl AxisRamp {
+    // `rate` is the change per second, i.e. a full deflection takes 1 / rate.
+    pub const fn new(rate: f32) -> Self {
+        Self { value: 0.0, rate }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    pub fn update(&mut self, target: f32, dt: f32) -> f32 {
+        let max_step = self.rate * dt;
+        self.value += (target - self.value).clamp(-max_step, max_step);
+        self.value
+    }
+}
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::input::Input;
+
+    // ------------------------------------------------------------------------
+    fn context_with(keys: &[Key]) -> InputContext {
+        let mut input = Input::new();
+        for key in keys {
+            input.key_down(*key);
+        }
+        let mut ctx = InputContext::default();
+        ctx.update_state(input.take_state());
+        ctx
+    }
+
+    #[test]
+    fn just_pressed_fires_once_per_press() {
+        let mut input = Input::new();
+        let mut ctx = InputContext::default();
+
+        input.key_down(Key::k_L);
+        ctx.update_state(input.take_state());
+        assert!(ctx.just_pressed(GameKey::Lights));
+        assert!(ctx.is_pressed(GameKey::Lights));
+
+        // still held
+        ctx.update_state(input.take_state());
+        assert!(!ctx.just_pressed(GameKey::Lights));
+        assert!(ctx.is_pressed(GameKey::Lights));
+
+        input.key_up(Key::k_L);
+        ctx.update_state(input.take_state());
+        assert!(!ctx.just_pressed(GameKey::Lights));
+
+        input.key_down(Key::k_L);
+        ctx.update_state(input.take_state());
+        assert!(ctx.just_pressed(GameKey::Lights));
+    }
+
+    #[test]
+    fn axis_from_key_pairs() {
+        let axis = |keys: &[Key]| context_with(keys).axis(GameKey::SteerLeft, GameKey::SteerRight);
+        assert_eq!(axis(&[]), 0.0);
+        assert_eq!(axis(&[Key::k_D]), 1.0);
+        assert_eq!(axis(&[Key::k_A]), -1.0);
+        assert_eq!(axis(&[Key::k_A, Key::k_D]), 0.0);
+    }
+
+    #[test]
+    fn held_key_ramps_towards_extreme() {
+        let ctx = context_with(&[Key::k_D]);
+        let mut ramp = AxisRamp::new(4.0);
+
+        let mut last = ramp.value();
+        for _ in 0..11 {
+            let value = ramp.update(ctx.axis(GameKey::SteerLeft, GameKey::SteerRight), 0.02);
+            assert!(value > last && value <= 1.0);
+            last = value;
+        }
+        assert!(last > 0.85 && last < 1.0);
+
+        for _ in 0..50 {
+            ramp.update(ctx.axis(GameKey::SteerLeft, GameKey::SteerRight), 0.02);
+        }
+        assert_eq!(ramp.value(), 1.0);
+
+        // releasing ramps back to the center
+        let released = context_with(&[]);
+        let value = ramp.update(released.axis(GameKey::SteerLeft, GameKey::SteerRight), 0.1);
+        assert!((value - 0.6).abs() < 1e-6);
     }
 }
@@ -1,20 +1,112 @@
 use crate::core::{IClock, IGame, input};
 use crate::error::Result;
 
+// ----------------------------------------------------------------------------
+// Fixed update rate of the simulation; rendering runs as often as it can.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameConfig {
+    pub update_hz: u32,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self { update_hz: 100 }
+    }
+}
+
+impl GameConfig {
+    pub fn dt_update(&self) -> std::time::Duration {
+        std::time::Duration::from_nanos(1_000_000_000 / u64::from(self.update_hz.max(1)))
+    }
+}
+
+// ----------------------------------------------------------------------------
+// On slow machines we deliberately drop updates rather than spiral to death.
+// We accept simulation slowdown over instability.
+const MAX_UPDATES_PER_FRAME: u32 = 4;
+
+// Consecutive frames at MAX_UPDATES_PER_FRAME after which the loop counts as
+// behind, so single hitches don't.
+const BEHIND_FRAMES: u32 = 3;
+
+// Dropped updates are reported at most this often.
+const WARN_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+// ----------------------------------------------------------------------------
 pub struct GameLoop {
     dt_update: std::time::Duration,
     t_lag: std::time::Duration,
     t_prev: std::time::Duration,
+    frames_at_clamp: u32,
+    dropped_updates: u64,
+    unreported_drops: u64,
+    t_last_warning: Option<std::time::Duration>,
 }
 
 impl GameLoop {
-    pub fn new(dt_update: std::time::Duration) -> Self {
+    pub fn new(config: GameConfig) -> Self {
         Self {
-            dt_update,
+            dt_update: config.dt_update(),
             t_lag: std::time::Duration::ZERO,
             t_prev: std::time::Duration::ZERO,
+            frames_at_clamp: 0,
+            dropped_updates: 0,
+            unreported_drops: 0,
+            t_last_warning: None,
+        }
+    }
+
+    // ----------------------------------------------------------------------------
+    pub fn dt_update(&self) -> std::time::Duration {
+        self.dt_update
+    }
+
+    // ----------------------------------------------------------------------------
+    // Effective rate, which differs from the configured one when the period
+    // does not divide a second evenly.
+    pub fn update_hz(&self) -> f64 {
+        1.0 / self.dt_update.as_secs_f64()
+    }
+
+    // ----------------------------------------------------------------------------
+    // Whether the last frames all needed more updates than are run per frame,
+    // i.e. the simulation runs slower than real time.
+    pub fn is_behind(&self) -> bool {
+        self.frames_at_clamp >= BEHIND_FRAMES
+    }
+
+    // ----------------------------------------------------------------------------
+    // Updates skipped so far to stay responsive.
+    pub fn dropped_updates(&self) -> u64 {
+        self.dropped_updates
+    }
+
+    // ----------------------------------------------------------------------------
+    fn track_dropped(&mut self, updates_dropped: u32, t_current: std::time::Duration) {
+        if updates_dropped == 0 {
+            self.frames_at_clamp = 0;
+            return;
+        }
+
+        self.frames_at_clamp += 1;
+        self.dropped_updates += u64::from(updates_dropped);
+        self.unreported_drops += u64::from(updates_dropped);
+
+        let due = self
+            .t_last_warning
+            .is_none_or(|t| t_current.saturating_sub(t) >= WARN_INTERVAL);
+        if due {
+            log::warn!(
+                "dropped {} update(s), behind for {} frame(s), lag={:?}",
+                self.unreported_drops,
+                self.frames_at_clamp,
+                self.t_lag
+            );
+            self.unreported_drops = 0;
+            self.t_last_warning = Some(t_current);
         }
     }
+
     // ----------------------------------------------------------------------------
     pub fn step<Game: IGame, Clock: IClock>(
         &mut self,
@@ -29,14 +121,11 @@ impl GameLoop {
         self.t_lag += t_frame_total;
         self.t_prev = t_current;
 
-        game.input(events.clone(), state.clone())?;
+        game.input(events, state)?;
 
         let updates_needed = (self.t_lag.as_nanos() / self.dt_update.as_nanos()) as u32;
         let updates_needed = updates_needed.max(1);
 
-        // On slow machines we deliberately drop updates rather than spiral to death.
-        // We accept simulation slowdown over instability.
-        const MAX_UPDATES_PER_FRAME: u32 = 4;
         let updates_to_run = updates_needed.min(MAX_UPDATES_PER_FRAME);
         let updates_dropped = updates_needed - updates_to_run;
 
@@ -50,9 +139,7 @@ impl GameLoop {
         // forgetting the debt rather than carrying it forward.
         self.t_lag = self.t_lag.saturating_sub(self.dt_update * updates_needed);
 
-        if updates_dropped > 0 {
-            log::warn!("dropped {updates_dropped} update(s), lag={:?}", self.t_lag);
-        }
+        self.track_dropped(updates_dropped, t_current);
 
         // Sleep for the remainder of the frame budget.
         let t_work = clock.t_since(t_current);
@@ -82,7 +169,7 @@ mod tests {
         let state = input::State::default();
         let clock = MockClock::default();
         let mut game = MockGame::new(&clock, t_update, t_render);
-        let mut game_loop = GameLoop::new(t_step);
+        let mut game_loop = GameLoop::new(GameConfig { update_hz: 50 });
         for _ in 0..4 {
             let _ = game_loop.step(&mut game, &clock, &events, &state);
         }
@@ -92,11 +179,12 @@ mod tests {
 
         // since processing time was 0 ms, every loop should only contain one update
         assert_eq!(game.loops(), &vec![1; 4]);
+        assert!(!game_loop.is_behind());
+        assert_eq!(game_loop.dropped_updates(), 0);
     }
 
     #[test]
     fn test_gameloop_slow() {
-        let t_step = std::time::Duration::from_millis(20);
         let t_update = std::time::Duration::from_millis(10);
         let t_render = std::time::Duration::from_millis(20);
 
@@ -104,7 +192,7 @@ mod tests {
         let state = input::State::default();
         let clock = MockClock::default();
         let mut game = MockGame::new(&clock, t_update, t_render);
-        let mut game_loop = GameLoop::new(t_step);
+        let mut game_loop = GameLoop::new(GameConfig { update_hz: 50 });
         for _ in 0..6 {
             let _ = game_loop.step(&mut game, &clock, &events, &state);
         }
@@ -116,11 +204,14 @@ mod tests {
         // with 10 ms updating time and 20 ms rendering time, we expect 2 updates per loop
         // give 2 loops to account for adoption time
         assert_eq!(game.loops()[2..6], vec![2; 4]);
+
+        // slow, but keeping up
+        assert!(!game_loop.is_behind());
+        assert_eq!(game_loop.dropped_updates(), 0);
     }
 
     #[test]
     fn test_gameloop_superslow() {
-        let t_step = std::time::Duration::from_millis(20);
         let t_update = std::time::Duration::from_millis(20);
         let t_render = std::time::Duration::from_millis(20);
 
@@ -128,7 +219,7 @@ mod tests {
         let state = input::State::default();
         let clock = MockClock::default();
         let mut game = MockGame::new(&clock, t_update, t_render);
-        let mut game_loop = GameLoop::new(t_step);
+        let mut game_loop = GameLoop::new(GameConfig { update_hz: 50 });
         for _ in 0..6 {
             let _ = game_loop.step(&mut game, &clock, &events, &state);
         }
@@ -141,4 +232,55 @@ mod tests {
         // per loop, give 3 loops to account for adoption time
         assert_eq!(game.loops()[3..6], vec![4; 3]);
     }
+
+    #[test]
+    fn test_gameloop_behind() {
+        let t_update = std::time::Duration::from_millis(30);
+        let t_render = std::time::Duration::from_millis(0);
+
+        let events = input::Events::default();
+        let state = input::State::default();
+        let clock = MockClock::default();
+        let mut game = MockGame::new(&clock, t_update, t_render);
+        let mut game_loop = GameLoop::new(GameConfig { update_hz: 50 });
+
+        // a single update is slower than the step, so the lag keeps growing
+        // until the clamp is hit
+        let mut frames = 0;
+        while !game_loop.is_behind() {
+            let _ = game_loop.step(&mut game, &clock, &events, &state);
+            frames += 1;
+            assert!(frames < 20, "never fell behind");
+        }
+
+        assert_eq!(game.loops().last(), Some(&(MAX_UPDATES_PER_FRAME as usize)));
+        assert!(game_loop.dropped_updates() >= u64::from(BEHIND_FRAMES));
+    }
+
+    #[test]
+    fn test_gameloop_forwards_input() {
+        let t_update = std::time::Duration::from_millis(0);
+        let t_render = std::time::Duration::from_millis(0);
+
+        let mut input = input::Input::new();
+        let clock = MockClock::default();
+        let mut game = MockGame::new(&clock, t_update, t_render);
+        let mut game_loop = GameLoop::new(GameConfig { update_hz: 50 });
+
+        input.key_down(input::Key::k_Space);
+        input.add_event(input::Event::MouseMove { x: 1, y: 2 });
+        let events = input.take_events();
+        let state = input.take_state();
+        assert_eq!(game_loop.step(&mut game, &clock, &events, &state), Ok(()));
+
+        // events are handed over once per frame, not per update
+        assert_eq!(game.input_events(), 2);
+    }
+
+    #[test]
+    fn test_gameloop_update_rate() {
+        let game_loop = GameLoop::new(GameConfig { update_hz: 100 });
+        assert_eq!(game_loop.dt_update(), std::time::Duration::from_millis(10));
+        assert_eq!(game_loop.update_hz(), 100.0);
+    }
 }
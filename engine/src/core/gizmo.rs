@@ -0,0 +1,105 @@
+use crate::core::coords::{FORWARD, RIGHT, UP};
+use crate::core::gl_pipeline::GlPipelineType;
+use crate::core::gl_pipeline_colored::arrow;
+use crate::core::gl_renderer::{DefaultMaterials, RenderContext, RenderObject, Transform};
+use crate::error::{Error, Result};
+use crate::v2d::v3::V3;
+
+// ----------------------------------------------------------------------------
+// Avoids losing the outermost line when extent / spacing rounds down
+const GRID_EPSILON: f32 = 1e-4;
+const GRID_COLOR: V3 = V3::new([0.6, 0.6, 0.6]);
+
+// ----------------------------------------------------------------------------
+// Spatial reference at the origin: a line grid on the ground plane and one
+// arrow per world axis, red, green and blue for right, up and forward.
+#[derive(Debug)]
+pub struct Gizmo {
+    pub objects: Vec<RenderObject>,
+}
+
+// ----------------------------------------------------------------------------
+// Lines of a square grid on the XZ plane, `spacing` apart and reaching out to
+// `extent` from the origin in each direction. Lines along X come first.
+pub fn grid_lines(extent: f32, spacing: f32) -> Result<Vec<[V3; 2]>> {
+    if spacing <= 0.0 || extent < 0.0 {
+        return Err(Error::InvalidLength);
+    }
+
+    let n = (extent / spacing + GRID_EPSILON).floor() as i32;
+    let half = n as f32 * spacing;
+    let offsets = (-n..=n).map(|i| i as f32 * spacing);
+
+    let along_x = offsets
+        .clone()
+        .map(|z| [V3::new([-half, 0.0, z]), V3::new([half, 0.0, z])]);
+    let along_z = offsets.map(|x| [V3::new([x, 0.0, -half]), V3::new([x, 0.0, half])]);
+    Ok(along_x.chain(along_z).collect())
+}
+
+// ----------------------------------------------------------------------------
+impl Gizmo {
+    pub fn new(context: &mut RenderContext, extent: f32, spacing: f32) -> Result<Self> {
+        let pipe_id = GlPipelineType::Colored.into();
+
+        let points: Vec<_> = grid_lines(extent, spacing)?.concat();
+        let mut objects = vec![RenderObject {
+            name: "gizmo:grid".into(),
+            transform: Transform::default(),
+            pipe_id,
+            mesh_id: context.create_line_mesh(&points, GRID_COLOR)?,
+            material_id: context.default_material(DefaultMaterials::White),
+            ..Default::default()
+        }];
+
+        let axes = [
+            ("gizmo:x0_arrow", RIGHT, DefaultMaterials::Red),
+            ("gizmo:x1_arrow", UP, DefaultMaterials::Green),
+            ("gizmo:x2_arrow", FORWARD, DefaultMaterials::Blue),
+        ];
+        for (name, axis, material) in axes {
+            let arrow_verts = arrow(V3::ZERO, axis)?;
+            objects.push(RenderObject {
+                name: name.into(),
+                transform: Transform::default(),
+                pipe_id,
+                mesh_id: context.create_colored_mesh(&arrow_verts, &[], true)?,
+                material_id: context.default_material(material),
+                ..Default::default()
+            });
+        }
+
+        Ok(Self { objects })
+    }
+
+    // ------------------------------------------------------------------------
+    pub fn render_objects(&self) -> impl Iterator<Item = &RenderObject> {
+        self.objects.iter()
+    }
+}
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_has_a_line_per_step() {
+        // 9 lines in each direction, from -2 to 2
+        assert_eq!(grid_lines(2.0, 0.5).unwrap().len(), 18);
+        assert_eq!(grid_lines(2.2, 0.5).unwrap().len(), 18);
+        assert_eq!(grid_lines(0.3, 0.1).unwrap().len(), 14);
+        assert_eq!(grid_lines(0.0, 1.0).unwrap().len(), 2);
+        assert!(grid_lines(1.0, 0.0).is_err());
+
+        let lines = grid_lines(1.0, 1.0).unwrap();
+        assert_eq!(
+            lines[0],
+            [V3::new([-1.0, 0.0, -1.0]), V3::new([1.0, 0.0, -1.0])]
+        );
+        assert_eq!(
+            lines[5],
+            [V3::new([1.0, 0.0, -1.0]), V3::new([1.0, 0.0, 1.0])]
+        );
+    }
+}
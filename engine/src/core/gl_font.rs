@@ -7,6 +7,7 @@
 // Example command to generate MSDF font atlas:
 // msdf-atlas-gen.exe -font Roboto.ttf -type mtsdf -fontname Roboto -format png -imageout roboto.png -json roboto.json -charset charset_all.txt -pots
 
+use crate::core::gl_graphics::TextureSampler;
 use crate::core::gl_texture;
 use crate::error::Result;
 use crate::sys::opengl::{self as gl, GLuint};
@@ -24,8 +25,8 @@ pub struct Font {
 #[derive(Debug, Clone)]
 pub struct FontMeta {
     pub line_height: f32,
-    pub _ascender: f32,
-    pub _descender: f32,
+    pub ascender: f32,
+    pub descender: f32,
     pub _underline_y: f32,
     pub _underline_thickness: f32,
 }
@@ -67,8 +68,11 @@ impl FontGlyph {
 impl Font {
     pub fn load(gl: &gl::OpenGlFunctions, path: &std::path::Path) -> Result<Self> {
         let png_path = path.with_extension("png");
-        let (width, height, texture) =
-            gl_texture::load_png(gl, gl::LINEAR, gl::CLAMP_TO_EDGE, &png_path)?;
+        let (width, height, texture) = gl_texture::load_png(
+            gl,
+            &TextureSampler::new(gl::LINEAR, gl::CLAMP_TO_EDGE),
+            &png_path,
+        )?;
 
         let size = (1.0 / width as f32, 1.0 / height as f32);
         let json_path = path.with_extension("json");
@@ -131,8 +135,8 @@ fn load_json(path: &std::path::Path, size: (f32, f32)) -> Result<(FontMeta, Font
 
     let meta = FontMeta {
         line_height: atlas.metrics.line_height,
-        _ascender: atlas.metrics.ascender,
-        _descender: atlas.metrics.descender,
+        ascender: atlas.metrics.ascender,
+        descender: atlas.metrics.descender,
         _underline_y: atlas.metrics.underline_y,
         _underline_thickness: atlas.metrics.underline_thickness,
     };
@@ -1,5 +1,6 @@
 use crate::error::{Error, Result};
 use crate::sys::opengl::{self as gl, GLint, GLuint, GLvoid};
+use crate::v2d::{m3x3::M3x3, m4x4::M4x4, v3::V3};
 use std::ffi::CString;
 
 // --------------------------------------------------------------------------------
@@ -239,24 +240,112 @@ pub fn delete_texture(gl: &gl::OpenGlFunctions, texture: GLuint) {
     }
 }
 
+// --------------------------------------------------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureFormat {
+    R8,
+    Rgb8,
+    Rgba8,
+}
+
+// --------------------------------------------------------------------------------
+impl TextureFormat {
+    // Internal format, pixel format and pixel type as passed to glTexImage2D.
+    pub fn gl_formats(self) -> (GLint, gl::GLenum, gl::GLenum) {
+        match self {
+            Self::R8 => (gl::R8, gl::RED, gl::UNSIGNED_BYTE),
+            Self::Rgb8 => (gl::RGB8, gl::RGB, gl::UNSIGNED_BYTE),
+            Self::Rgba8 => (gl::RGBA8, gl::RGBA, gl::UNSIGNED_BYTE),
+        }
+    }
+
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            Self::R8 => 1,
+            Self::Rgb8 => 3,
+            Self::Rgba8 => 4,
+        }
+    }
+}
+
+// --------------------------------------------------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextureSampler {
+    pub min_filter: GLint,
+    pub mag_filter: GLint,
+    pub wrap_s: GLint,
+    pub wrap_t: GLint,
+    pub anisotropy: f32,
+}
+
+// --------------------------------------------------------------------------------
+impl TextureSampler {
+    // Same filter and wrap mode in all directions, no mipmaps or anisotropy.
+    pub const fn new(filter: GLint, wrap: GLint) -> Self {
+        Self {
+            min_filter: filter,
+            mag_filter: filter,
+            wrap_s: wrap,
+            wrap_t: wrap,
+            anisotropy: 1.0,
+        }
+    }
+
+    // Trilinear filtering with up to `anisotropy` samples, for surfaces seen
+    // at grazing angles such as terrain.
+    pub const fn mipmapped(wrap: GLint, anisotropy: f32) -> Self {
+        Self {
+            min_filter: gl::LINEAR_MIPMAP_LINEAR as GLint,
+            mag_filter: gl::LINEAR,
+            wrap_s: wrap,
+            wrap_t: wrap,
+            anisotropy,
+        }
+    }
+
+    pub fn uses_mipmaps(&self) -> bool {
+        self.min_filter != gl::NEAREST && self.min_filter != gl::LINEAR
+    }
+}
+
+// --------------------------------------------------------------------------------
+// Applies `sampler` to the texture bound to TEXTURE_2D. Anisotropy is clamped
+// to what the driver supports and skipped if the extension is missing.
+fn apply_sampler(gl: &gl::OpenGlFunctions, sampler: &TextureSampler) {
+    unsafe {
+        gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, sampler.min_filter);
+        gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, sampler.mag_filter);
+        gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, sampler.wrap_s);
+        gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, sampler.wrap_t);
+
+        if sampler.uses_mipmaps() {
+            gl.GenerateMipmap(gl::TEXTURE_2D);
+        }
+
+        if sampler.anisotropy > 1.0 {
+            let mut max_anisotropy = 0.0;
+            gl.GetFloatv(gl::MAX_TEXTURE_MAX_ANISOTROPY, &mut max_anisotropy);
+            if check_gl_error(gl).is_ok() && max_anisotropy > 1.0 {
+                let anisotropy = sampler.anisotropy.min(max_anisotropy);
+                gl.TexParameterf(gl::TEXTURE_2D, gl::TEXTURE_MAX_ANISOTROPY, anisotropy);
+            }
+        }
+    }
+}
+
 // --------------------------------------------------------------------------------
 pub fn create_texture(
     gl: &gl::OpenGlFunctions,
     width: usize,
     height: usize,
-    format: usize,
+    format: TextureFormat,
     data: &[u8],
-    filter: GLint,
-    wrap: GLint,
-) -> Result<GLuint> {
-    const INTERNAL_FMT: [(gl::GLint, gl::GLenum); 3] = [
-        (gl::RGBA8, gl::RGBA),
-        (gl::RGB8, gl::RGB),
-        (gl::R8, gl::RED),
-    ];
-    let Some((internal, format)) = INTERNAL_FMT.get(format) else {
-        return Err(Error::InvalidTextureFormat);
-    };
+    sampler: &TextureSampler,
+) -> Result<(GLuint, TextureFormat)> {
+    if data.len() < width * height * format.bytes_per_pixel() {
+        return Err(Error::InvalidTextureSize);
+    }
+    let (internal, pixel_format, pixel_type) = format.gl_formats();
 
     let mut texture = 0;
     unsafe {
@@ -265,12 +354,12 @@ pub fn create_texture(
         gl.TexImage2D(
             gl::TEXTURE_2D,
             0,
-            *internal,
+            internal,
             width as i32,
             height as i32,
             0,
-            *format,
-            gl::UNSIGNED_BYTE,
+            pixel_format,
+            pixel_type,
             data.as_ptr() as *const _,
         );
 
@@ -278,14 +367,11 @@ pub fn create_texture(
             gl.DeleteTextures(1, &texture);
             return Err(e);
         }
-
-        gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, filter);
-        gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, filter);
-        gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, wrap);
-        gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, wrap);
     }
 
-    Ok(texture)
+    apply_sampler(gl, sampler);
+
+    Ok((texture, format))
 }
 
 // --------------------------------------------------------------------------------
@@ -378,6 +464,56 @@ pub fn get_uniform_location(
         .ok_or(Error::InvalidLocation)
 }
 
+// --------------------------------------------------------------------------------
+// Location of the uniform `name`, or -1 with a warning if the program doesn't
+// use it. Resolved once when a pipeline is created; the `set_uniform_*`
+// helpers below skip -1 locations.
+pub fn uniform_location_or_warn(
+    gl: &gl::OpenGlFunctions,
+    program: gl::GLuint,
+    name: &str,
+) -> gl::GLint {
+    get_uniform_location(gl, program, name).unwrap_or_else(|_| {
+        log::warn!("uniform '{name}' not found in program {program}");
+        -1
+    })
+}
+
+// --------------------------------------------------------------------------------
+pub fn set_uniform_1i(gl: &gl::OpenGlFunctions, location: GLint, value: GLint) {
+    if location != -1 {
+        unsafe { gl.Uniform1i(location, value) };
+    }
+}
+
+// --------------------------------------------------------------------------------
+pub fn set_uniform_1f(gl: &gl::OpenGlFunctions, location: GLint, value: f32) {
+    if location != -1 {
+        unsafe { gl.Uniform1f(location, value) };
+    }
+}
+
+// --------------------------------------------------------------------------------
+pub fn set_uniform_v3(gl: &gl::OpenGlFunctions, location: GLint, value: &V3) {
+    if location != -1 {
+        unsafe { gl.Uniform3fv(location, 1, value.as_ptr()) };
+    }
+}
+
+// --------------------------------------------------------------------------------
+pub fn set_uniform_m3(gl: &gl::OpenGlFunctions, location: GLint, value: &M3x3) {
+    if location != -1 {
+        unsafe { gl.UniformMatrix3fv(location, 1, gl::FALSE, value.as_ptr()) };
+    }
+}
+
+// --------------------------------------------------------------------------------
+pub fn set_uniform_m4(gl: &gl::OpenGlFunctions, location: GLint, value: &M4x4) {
+    if location != -1 {
+        unsafe { gl.UniformMatrix4fv(location, 1, gl::FALSE, value.as_ptr()) };
+    }
+}
+
 // --------------------------------------------------------------------------------
 pub fn check_texture_size(size: usize, max_size: i32) -> Result<i32> {
     let size = size.try_into().map_err(|_| Error::InvalidTextureSize)?;
@@ -387,3 +523,113 @@ pub fn check_texture_size(size: usize, max_size: i32) -> Result<i32> {
         Ok(size)
     }
 }
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sys::opengl::tests::{
+        generated_mipmaps, mock_gl, tex_image_formats, tex_parameters_f, tex_parameters_i,
+        uniform_locations,
+    };
+
+    // ------------------------------------------------------------------------
+    fn upload(format: TextureFormat) -> (GLint, gl::GLenum, gl::GLenum) {
+        let gl = mock_gl();
+        let data = vec![0u8; 4 * 4 * format.bytes_per_pixel()];
+        let sampler = TextureSampler::new(gl::LINEAR, gl::CLAMP_TO_EDGE);
+        let (texture, created) = create_texture(&gl, 4, 4, format, &data, &sampler).unwrap();
+        assert_ne!(texture, 0);
+        assert_eq!(created, format);
+        tex_image_formats().unwrap()
+    }
+
+    // ------------------------------------------------------------------------
+    #[test]
+    fn texture_formats_map_to_gl_enums() {
+        assert_eq!(
+            upload(TextureFormat::R8),
+            (gl::R8, gl::RED, gl::UNSIGNED_BYTE)
+        );
+        assert_eq!(
+            upload(TextureFormat::Rgb8),
+            (gl::RGB8, gl::RGB, gl::UNSIGNED_BYTE)
+        );
+        assert_eq!(
+            upload(TextureFormat::Rgba8),
+            (gl::RGBA8, gl::RGBA, gl::UNSIGNED_BYTE)
+        );
+    }
+
+    // ------------------------------------------------------------------------
+    #[test]
+    fn short_texture_data_is_rejected() {
+        let gl = mock_gl();
+        let data = vec![0u8; 4 * 4 * 3];
+        let sampler = TextureSampler::new(gl::LINEAR, gl::CLAMP_TO_EDGE);
+        let result = create_texture(&gl, 4, 4, TextureFormat::Rgba8, &data, &sampler);
+        assert!(matches!(result, Err(Error::InvalidTextureSize)));
+        assert_eq!(tex_image_formats(), None);
+    }
+
+    // ------------------------------------------------------------------------
+    #[test]
+    fn sampler_parameters_are_applied() {
+        let gl = mock_gl();
+        let data = vec![0u8; 4 * 4 * 4];
+        let sampler = TextureSampler {
+            min_filter: gl::LINEAR_MIPMAP_LINEAR as GLint,
+            mag_filter: gl::NEAREST,
+            wrap_s: gl::REPEAT,
+            wrap_t: gl::CLAMP_TO_EDGE,
+            anisotropy: 32.0,
+        };
+        create_texture(&gl, 4, 4, TextureFormat::Rgba8, &data, &sampler).unwrap();
+
+        assert_eq!(
+            tex_parameters_i(),
+            [
+                (gl::TEXTURE_MIN_FILTER, gl::LINEAR_MIPMAP_LINEAR as GLint),
+                (gl::TEXTURE_MAG_FILTER, gl::NEAREST),
+                (gl::TEXTURE_WRAP_S, gl::REPEAT),
+                (gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE),
+            ]
+        );
+        assert_eq!(generated_mipmaps(), 1);
+
+        // the mock driver supports up to 16x
+        assert_eq!(tex_parameters_f(), [(gl::TEXTURE_MAX_ANISOTROPY, 16.0)]);
+    }
+
+    // ------------------------------------------------------------------------
+    #[test]
+    fn plain_sampler_skips_mipmaps_and_anisotropy() {
+        let gl = mock_gl();
+        let data = vec![0u8; 4 * 4];
+        let sampler = TextureSampler::new(gl::NEAREST, gl::REPEAT);
+        create_texture(&gl, 4, 4, TextureFormat::R8, &data, &sampler).unwrap();
+
+        assert_eq!(tex_parameters_i().len(), 4);
+        assert_eq!(generated_mipmaps(), 0);
+        assert!(tex_parameters_f().is_empty());
+    }
+
+    // ------------------------------------------------------------------------
+    #[test]
+    fn missing_uniform_is_never_uploaded() {
+        let gl = mock_gl();
+        let found = uniform_location_or_warn(&gl, 1, "model");
+        let missing = uniform_location_or_warn(&gl, 1, "missingColor");
+        assert_eq!((found, missing), (0, -1));
+
+        set_uniform_1i(&gl, missing, 1);
+        set_uniform_1f(&gl, missing, 1.0);
+        set_uniform_v3(&gl, missing, &V3::ONE);
+        set_uniform_m3(&gl, missing, &M3x3::identity());
+        set_uniform_m4(&gl, missing, &M4x4::identity());
+        assert!(uniform_locations().is_empty());
+
+        set_uniform_m4(&gl, found, &M4x4::identity());
+        assert_eq!(uniform_locations(), [found]);
+    }
+}
@@ -1,7 +1,10 @@
+use crate::core::bounds::Aabb;
+use crate::core::gl_graphics;
+use crate::core::light::Light;
 use crate::error::Result;
 use crate::sys::opengl as gl;
 use crate::util::obj_pool::{ObjId, ObjPool};
-use crate::v2d::{m4x4::M4x4, v3::V3};
+use crate::v2d::{affine4x4, m3x3::M3x3, m4x4::M4x4, v3::V3};
 
 // ----------------------------------------------------------------------------
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -30,9 +33,22 @@ pub struct GlMesh {
     pub vbo_indices: gl::GLuint,
     pub num_indices: gl::GLsizei,
     pub num_vertices: gl::GLsizei,
+    pub stride: usize,
     pub primitive_type: gl::GLenum,
     pub has_indices: bool,
+    pub has_colors: bool,
+    pub has_ao: bool,
     pub is_debug: bool,
+    pub extents: Aabb,
+}
+
+// ----------------------------------------------------------------------------
+impl GlMesh {
+    // Local bounds of the vertices, kept up to date by the pipelines'
+    // `create_mesh` and `update_mesh`.
+    pub fn aabb(&self) -> Aabb {
+        self.extents
+    }
 }
 
 // ----------------------------------------------------------------------------
@@ -46,6 +62,13 @@ pub fn delete_mesh(gl: &gl::OpenGlFunctions, mesh: &GlMesh) {
     }
 }
 
+// ----------------------------------------------------------------------------
+pub fn delete_meshes(gl: &gl::OpenGlFunctions, meshes: &GlMeshes) {
+    for mesh in meshes.iter() {
+        delete_mesh(gl, mesh);
+    }
+}
+
 // ----------------------------------------------------------------------------
 #[derive(Debug, Clone)]
 pub enum GlMaterial {
@@ -53,23 +76,134 @@ pub enum GlMaterial {
     Color { color: V3 },
 }
 
+// ----------------------------------------------------------------------------
+pub fn delete_materials(gl: &gl::OpenGlFunctions, materials: &GlMaterials) {
+    for material in materials.iter() {
+        if let GlMaterial::Texture { texture } = material {
+            gl_graphics::delete_texture(gl, *texture);
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+// How lighting normals vary across a triangle: interpolated from the vertex
+// normals, or the triangle's face normal for a faceted look.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ShadingMode {
+    #[default]
+    Smooth,
+    Flat,
+}
+
 // ----------------------------------------------------------------------------
 #[derive(Debug, Clone)]
 pub struct GlUniforms {
     pub model: M4x4,
+    pub normal: M3x3,
     pub view: M4x4,
     pub projection: M4x4,
     pub camera: M4x4,
     pub mat_id: gl::GLint,
     pub light_pos: V3,
+    pub light_dir: V3,
+    pub light_directional: bool,
     pub view_pos: V3,
     pub light_color: V3,
-    pub object_color: V3,
+    pub log_depth_coef: f32,
+    pub shading: ShadingMode,
+}
+
+// ----------------------------------------------------------------------------
+// Identity transforms lit by the default light, with the logarithmic depth
+// coefficient of the camera's default far plane.
+impl Default for GlUniforms {
+    fn default() -> Self {
+        Self {
+            model: M4x4::identity(),
+            normal: M3x3::identity(),
+            view: M4x4::identity(),
+            projection: M4x4::identity(),
+            camera: M4x4::identity(),
+            mat_id: 0,
+            light_pos: V3::ZERO,
+            light_dir: V3::ZERO,
+            light_directional: false,
+            view_pos: V3::ZERO,
+            light_color: V3::ZERO,
+            log_depth_coef: 0.0,
+            shading: ShadingMode::Smooth,
+        }
+        .with_light(&Light::default())
+        .with_far(100.0)
+    }
+}
+
+// ----------------------------------------------------------------------------
+impl GlUniforms {
+    // Also updates the normal matrix.
+    pub fn with_model(mut self, model: M4x4) -> Self {
+        self.model = model;
+        self.normal = affine4x4::normal_matrix(&model);
+        self
+    }
+
+    // Also updates the combined camera matrix.
+    pub fn with_view_projection(mut self, view: M4x4, projection: M4x4) -> Self {
+        self.view = view;
+        self.projection = projection;
+        self.camera = projection * view;
+        self
+    }
+
+    pub fn with_view_pos(mut self, view_pos: V3) -> Self {
+        self.view_pos = view_pos;
+        self
+    }
+
+    pub fn with_light(mut self, light: &Light) -> Self {
+        self.light_pos = light.position;
+        self.light_dir = light.light_dir(V3::ZERO);
+        self.light_directional = light.is_directional();
+        self.light_color = light.radiance();
+        self
+    }
+
+    pub fn with_shading(mut self, shading: ShadingMode) -> Self {
+        self.shading = shading;
+        self
+    }
+
+    pub fn with_mat_id(mut self, mat_id: gl::GLint) -> Self {
+        self.mat_id = mat_id;
+        self
+    }
+
+    // Logarithmic depth coefficient for a far clip plane at `far`.
+    pub fn with_far(mut self, far: f32) -> Self {
+        self.log_depth_coef = 2.0 / (far + 1.0).log2();
+        self
+    }
 }
 
 // --------------------------------------------------------------------------------
+// Draws are split into `bind`, `bind_material` and `draw` so the renderer can
+// sort objects and only switch program and material state when it changes.
 pub trait GlPipeline {
-    fn render(&self, mesh: &GlMesh, material: &GlMaterial, uniforms: &GlUniforms) -> Result<()>;
+    // Makes the program current and uploads the uniforms shared by all
+    // objects of the frame.
+    fn bind(&self, uniforms: &GlUniforms) -> Result<()>;
+
+    fn bind_material(&self, material: &GlMaterial) -> Result<()>;
+
+    // Uploads the per-object uniforms and draws `mesh`; the pipeline must be
+    // bound.
+    fn draw(&self, mesh: &GlMesh, uniforms: &GlUniforms) -> Result<()>;
+
+    fn render(&self, mesh: &GlMesh, material: &GlMaterial, uniforms: &GlUniforms) -> Result<()> {
+        self.bind(uniforms)?;
+        self.bind_material(material)?;
+        self.draw(mesh, uniforms)
+    }
 }
 
 // ----------------------------------------------------------------------------
@@ -77,3 +211,92 @@ pub type GlMeshes = ObjPool<GlMesh>;
 pub type GlMeshId = ObjId<GlMesh>;
 pub type GlMaterials = ObjPool<GlMaterial>;
 pub type GlMaterialId = ObjId<GlMaterial>;
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sys::opengl::tests::{
+        deleted_buffers, deleted_textures, deleted_vertex_arrays, mock_gl,
+    };
+
+    // ------------------------------------------------------------------------
+    fn mesh(vbo_indices: gl::GLuint) -> GlMesh {
+        GlMesh {
+            vao_vertices: 1,
+            vbo_vertices: 2,
+            vbo_indices,
+            num_indices: 0,
+            num_vertices: 0,
+            stride: 0,
+            primitive_type: gl::TRIANGLES,
+            has_indices: vbo_indices != 0,
+            has_colors: false,
+            has_ao: false,
+            is_debug: false,
+            extents: Aabb::EMPTY,
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    #[test]
+    fn uniforms_builder() {
+        let light = Light::directional(-V3::X1, V3::ONE, 2.0);
+        let view = M4x4::identity();
+        let projection = 2.0 * M4x4::identity();
+        let uniforms = GlUniforms::default()
+            .with_view_projection(view, projection)
+            .with_view_pos(V3::X2)
+            .with_light(&light)
+            .with_mat_id(3)
+            .with_far(255.0);
+
+        assert_eq!(uniforms.model, M4x4::identity());
+        assert_eq!(uniforms.normal, M3x3::identity());
+        assert_eq!(uniforms.camera, projection);
+        assert_eq!(uniforms.view_pos, V3::X2);
+        assert_eq!(uniforms.light_dir, V3::X1);
+        assert!(uniforms.light_directional);
+        assert_eq!(uniforms.light_color, V3::uniform(2.0));
+        assert_eq!(uniforms.mat_id, 3);
+        assert_eq!(uniforms.log_depth_coef, 0.25);
+
+        let defaults = GlUniforms::default();
+        assert_eq!(defaults.light_pos, Light::default().position);
+        assert!(!defaults.light_directional);
+    }
+
+    // ------------------------------------------------------------------------
+    #[test]
+    fn delete_all_meshes() {
+        let gl = mock_gl();
+
+        let mut meshes = GlMeshes::new();
+        for i in 0..5 {
+            meshes.insert(mesh(i % 2));
+        }
+        let removed = meshes.insert(mesh(3));
+        meshes.remove(removed);
+
+        delete_meshes(&gl, &meshes);
+
+        // 5 vertex buffers + 2 index buffers, one VAO per live mesh
+        assert_eq!(deleted_buffers(), 7);
+        assert_eq!(deleted_vertex_arrays(), 5);
+    }
+
+    // ------------------------------------------------------------------------
+    #[test]
+    fn delete_texture_materials() {
+        let gl = mock_gl();
+
+        let mut materials = GlMaterials::new();
+        materials.insert(GlMaterial::Texture { texture: 1 });
+        materials.insert(GlMaterial::Color { color: V3::one() });
+        materials.insert(GlMaterial::Texture { texture: 2 });
+
+        delete_materials(&gl, &materials);
+
+        assert_eq!(deleted_textures(), 2);
+    }
+}
@@ -1,5 +1,6 @@
+use crate::core::bounds::Aabb;
 use crate::core::gl_graphics;
-use crate::core::gl_pipeline::{GlMaterial, GlMesh, GlPipeline, GlUniforms};
+use crate::core::gl_pipeline::{GlMaterial, GlMesh, GlPipeline, GlUniforms, ShadingMode};
 use crate::error::{Error, Result};
 use crate::sys::opengl as gl;
 use crate::v2d::affine3x3;
@@ -14,6 +15,137 @@ pub struct Vertex {
     pub n: V3,
 }
 
+// ----------------------------------------------------------------------------
+// Vertex with its own color, used instead of the material color.
+#[derive(Debug, Clone, Copy)]
+pub struct ColoredVertex {
+    pub pos: V3,
+    pub n: V3,
+    pub color: V3,
+}
+
+// ----------------------------------------------------------------------------
+// Vertex with its own color and a baked ambient occlusion factor in [0, 1]
+// that scales the ambient light, used for the terrain.
+#[derive(Debug, Clone, Copy)]
+pub struct OccludedVertex {
+    pub pos: V3,
+    pub n: V3,
+    pub color: V3,
+    pub ao: f32,
+}
+
+// ----------------------------------------------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VertexAttrib {
+    pub location: gl::GLuint,
+    pub components: gl::GLint,
+    pub offset: usize,
+}
+
+// ----------------------------------------------------------------------------
+// Float attributes of a vertex type as bound to the colored pipeline shader:
+// location 0 is the position, 1 the normal, 2 the optional color and 3 the
+// optional ambient occlusion.
+pub trait VertexFormat: Copy {
+    const ATTRIBS: &'static [VertexAttrib];
+    const STRIDE: usize = std::mem::size_of::<Self>();
+
+    fn has_colors() -> bool {
+        Self::ATTRIBS.iter().any(|a| a.location == COLOR_LOCATION)
+    }
+
+    fn has_ao() -> bool {
+        Self::ATTRIBS.iter().any(|a| a.location == AO_LOCATION)
+    }
+
+    fn position(&self) -> V3;
+}
+
+const COLOR_LOCATION: gl::GLuint = 2;
+const AO_LOCATION: gl::GLuint = 3;
+
+// ----------------------------------------------------------------------------
+// Local bounds of the vertex positions, cached on the mesh.
+pub fn mesh_aabb<V: VertexFormat>(vertices: &[V]) -> Aabb {
+    Aabb::from_points(vertices.iter().map(V::position))
+}
+
+// ----------------------------------------------------------------------------
+impl VertexFormat for Vertex {
+    const ATTRIBS: &'static [VertexAttrib] = &[
+        VertexAttrib {
+            location: 0,
+            components: 3,
+            offset: std::mem::offset_of!(Vertex, pos),
+        },
+        VertexAttrib {
+            location: 1,
+            components: 3,
+            offset: std::mem::offset_of!(Vertex, n),
+        },
+    ];
+
+    fn position(&self) -> V3 {
+        self.pos
+    }
+}
+
+// ----------------------------------------------------------------------------
+impl VertexFormat for ColoredVertex {
+    const ATTRIBS: &'static [VertexAttrib] = &[
+        VertexAttrib {
+            location: 0,
+            components: 3,
+            offset: std::mem::offset_of!(ColoredVertex, pos),
+        },
+        VertexAttrib {
+            location: 1,
+            components: 3,
+            offset: std::mem::offset_of!(ColoredVertex, n),
+        },
+        VertexAttrib {
+            location: COLOR_LOCATION,
+            components: 3,
+            offset: std::mem::offset_of!(ColoredVertex, color),
+        },
+    ];
+
+    fn position(&self) -> V3 {
+        self.pos
+    }
+}
+
+// ----------------------------------------------------------------------------
+impl VertexFormat for OccludedVertex {
+    const ATTRIBS: &'static [VertexAttrib] = &[
+        VertexAttrib {
+            location: 0,
+            components: 3,
+            offset: std::mem::offset_of!(OccludedVertex, pos),
+        },
+        VertexAttrib {
+            location: 1,
+            components: 3,
+            offset: std::mem::offset_of!(OccludedVertex, n),
+        },
+        VertexAttrib {
+            location: COLOR_LOCATION,
+            components: 3,
+            offset: std::mem::offset_of!(OccludedVertex, color),
+        },
+        VertexAttrib {
+            location: AO_LOCATION,
+            components: 1,
+            offset: std::mem::offset_of!(OccludedVertex, ao),
+        },
+    ];
+
+    fn position(&self) -> V3 {
+        self.pos
+    }
+}
+
 // --------------------------------------------------------------------------------
 fn add_unit_cube_quad(verts: &mut Vec<Vertex>, indices: &mut Vec<u32>, u: V3, v: V3) {
     let i = verts.len() as u32;
@@ -316,64 +448,133 @@ fn face_normal(v0: V3, v1: V3, v2: V3) -> V3 {
     u.cross(v).norm()
 }
 
+// ----------------------------------------------------------------------------
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DepthMode {
+    #[default]
+    Linear,
+    Logarithmic,
+}
+
+// ----------------------------------------------------------------------------
+// Vertex and fragment shader sources for the given depth mode.
+pub fn shader_sources(depth_mode: DepthMode) -> (String, String) {
+    let with_defines = |src: &str| match depth_mode {
+        DepthMode::Linear => src.to_string(),
+        DepthMode::Logarithmic => src.replacen(
+            "#version 330 core\n",
+            "#version 330 core\n#define LOG_DEPTH\n",
+            1,
+        ),
+    };
+    (with_defines(VS_COLOR), with_defines(FS_COLOR))
+}
+
 // ----------------------------------------------------------------------------
 #[derive(Debug)]
 pub struct GlColoredPipeline {
     pub gl: Rc<gl::OpenGlFunctions>,
     pub shader: gl::GLuint,
     pub uid_model: gl::GLint,
+    pub uid_normal: gl::GLint,
     pub uid_view: gl::GLint,
     pub uid_projection: gl::GLint,
     pub uid_camera: gl::GLint,
     pub uid_mat_id: gl::GLint,
     pub uid_light_pos: gl::GLint,
+    pub uid_light_dir: gl::GLint,
+    pub uid_light_directional: gl::GLint,
     pub uid_view_pos: gl::GLint,
     pub uid_light_color: gl::GLint,
     pub uid_object_color: gl::GLint,
+    pub uid_use_vertex_color: gl::GLint,
+    pub uid_use_vertex_ao: gl::GLint,
+    pub uid_log_depth_coef: gl::GLint,
+    pub uid_flat_shading: gl::GLint,
 }
 
 // ----------------------------------------------------------------------------
 impl GlColoredPipeline {
-    pub fn new(gl: Rc<gl::OpenGlFunctions>) -> Result<Self> {
-        let shader = gl_graphics::create_program(&gl, "gl_pos_col", VS_COLOR, FS_COLOR);
+    pub fn new(gl: Rc<gl::OpenGlFunctions>, depth_mode: DepthMode) -> Result<Self> {
+        let (vs, fs) = shader_sources(depth_mode);
+        let shader = gl_graphics::create_program(&gl, "gl_pos_col", &vs, &fs);
         if let Err(e) = shader {
             println!("Error creating shader: {e:?}");
             return Err(e);
         };
         let shader = shader.unwrap();
-        let uid_model = gl_graphics::get_uniform_location(&gl, shader, "model").unwrap_or(-1);
-        let uid_view = gl_graphics::get_uniform_location(&gl, shader, "view").unwrap_or(-1);
-        let uid_projection =
-            gl_graphics::get_uniform_location(&gl, shader, "projection").unwrap_or(-1);
-        let uid_camera = gl_graphics::get_uniform_location(&gl, shader, "camera").unwrap_or(-1);
-        let uid_mat_id = gl_graphics::get_uniform_location(&gl, shader, "mat_id").unwrap_or(-1);
-        let uid_light_pos =
-            gl_graphics::get_uniform_location(&gl, shader, "lightPos").unwrap_or(-1);
-        let uid_view_pos = gl_graphics::get_uniform_location(&gl, shader, "viewPos").unwrap_or(-1);
-        let uid_light_color =
-            gl_graphics::get_uniform_location(&gl, shader, "lightColor").unwrap_or(-1);
-        let uid_object_color =
-            gl_graphics::get_uniform_location(&gl, shader, "objectColor").unwrap_or(-1);
+        let uid_model = gl_graphics::uniform_location_or_warn(&gl, shader, "model");
+        let uid_normal = gl_graphics::uniform_location_or_warn(&gl, shader, "normalMatrix");
+        let uid_view = gl_graphics::uniform_location_or_warn(&gl, shader, "view");
+        let uid_projection = gl_graphics::uniform_location_or_warn(&gl, shader, "projection");
+        let uid_camera = gl_graphics::uniform_location_or_warn(&gl, shader, "camera");
+        let uid_mat_id = gl_graphics::uniform_location_or_warn(&gl, shader, "mat_id");
+        let uid_light_pos = gl_graphics::uniform_location_or_warn(&gl, shader, "lightPos");
+        let uid_light_dir = gl_graphics::uniform_location_or_warn(&gl, shader, "lightDirection");
+        let uid_light_directional =
+            gl_graphics::uniform_location_or_warn(&gl, shader, "lightDirectional");
+        let uid_view_pos = gl_graphics::uniform_location_or_warn(&gl, shader, "viewPos");
+        let uid_light_color = gl_graphics::uniform_location_or_warn(&gl, shader, "lightColor");
+        let uid_object_color = gl_graphics::uniform_location_or_warn(&gl, shader, "objectColor");
+        let uid_use_vertex_color =
+            gl_graphics::uniform_location_or_warn(&gl, shader, "useVertexColor");
+        let uid_use_vertex_ao = gl_graphics::uniform_location_or_warn(&gl, shader, "useVertexAo");
+        let uid_log_depth_coef = gl_graphics::uniform_location_or_warn(&gl, shader, "logDepthCoef");
+        let uid_flat_shading = gl_graphics::uniform_location_or_warn(&gl, shader, "flatShading");
         Ok(GlColoredPipeline {
             gl,
             shader,
             uid_model,
+            uid_normal,
             uid_view,
             uid_projection,
             uid_camera,
             uid_mat_id,
             uid_light_pos,
+            uid_light_dir,
+            uid_light_directional,
             uid_view_pos,
             uid_light_color,
             uid_object_color,
+            uid_use_vertex_color,
+            uid_use_vertex_ao,
+            uid_log_depth_coef,
+            uid_flat_shading,
         })
     }
 
-    pub fn create_mesh(
+    pub fn create_mesh<V: VertexFormat>(
         &self,
-        vertices: &[Vertex],
+        vertices: &[V],
         indices: &[u32],
         is_debug: bool,
+    ) -> Result<GlMesh> {
+        self.create_primitives(vertices, indices, gl::TRIANGLES, is_debug)
+    }
+
+    // Line segments between consecutive pairs of `points`, all in `color`.
+    // Lines are lit as if facing up.
+    pub fn create_line_mesh(&self, points: &[V3], color: V3) -> Result<GlMesh> {
+        if !points.len().is_multiple_of(2) {
+            return Err(Error::InvalidLength);
+        }
+        let vertices: Vec<_> = points
+            .iter()
+            .map(|&pos| ColoredVertex {
+                pos,
+                n: V3::X1,
+                color,
+            })
+            .collect();
+        self.create_primitives(&vertices, &[], gl::LINES, false)
+    }
+
+    fn create_primitives<V: VertexFormat>(
+        &self,
+        vertices: &[V],
+        indices: &[u32],
+        primitive_type: gl::GLenum,
+        is_debug: bool,
     ) -> Result<GlMesh> {
         let gl = &self.gl;
         let vao_vertices = gl_graphics::create_vertex_array(gl);
@@ -386,15 +587,19 @@ impl GlColoredPipeline {
             )
         };
 
-        let stride = std::mem::size_of::<Vertex>() as gl::GLint;
-        let pos_ofs = std::mem::offset_of!(Vertex, pos) as gl::GLint;
-        let norm_ofs = std::mem::offset_of!(Vertex, n) as gl::GLint;
-
-        unsafe {
-            gl.EnableVertexAttribArray(0); // position
-            gl.EnableVertexAttribArray(1); // normal
-            gl.VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, stride, pos_ofs as *const _);
-            gl.VertexAttribPointer(1, 3, gl::FLOAT, gl::FALSE, stride, norm_ofs as *const _);
+        let stride = V::STRIDE as gl::GLint;
+        for attrib in V::ATTRIBS {
+            unsafe {
+                gl.EnableVertexAttribArray(attrib.location);
+                gl.VertexAttribPointer(
+                    attrib.location,
+                    attrib.components,
+                    gl::FLOAT,
+                    gl::FALSE,
+                    stride,
+                    attrib.offset as *const _,
+                );
+            }
         }
 
         let (num_indices, vbo_indices) = if !indices.is_empty() {
@@ -417,13 +622,32 @@ impl GlColoredPipeline {
             vbo_indices,
             num_indices,
             num_vertices: vertices.len() as gl::GLsizei,
-            primitive_type: gl::TRIANGLES,
+            stride: V::STRIDE,
+            primitive_type,
             has_indices: !indices.is_empty(),
+            has_colors: V::has_colors(),
+            has_ao: V::has_ao(),
             is_debug,
+            extents: mesh_aabb(vertices),
         })
     }
 
-    pub fn update_mesh(&self, mesh: &GlMesh, vertices: &[Vertex], indices: &[u32]) {
+    // The vertices must have the format the mesh was created with, as the
+    // attribute layout of its vertex array is not set up again.
+    pub fn update_mesh<V: VertexFormat>(
+        &self,
+        mesh: &mut GlMesh,
+        vertices: &[V],
+        indices: &[u32],
+    ) -> Result<()> {
+        if V::STRIDE != mesh.stride
+            || V::has_colors() != mesh.has_colors
+            || V::has_ao() != mesh.has_ao
+        {
+            return Err(Error::InvalidVertexFormat);
+        }
+
+        mesh.extents = mesh_aabb(vertices);
         let gl = &self.gl;
         unsafe {
             gl_graphics::update_buffer(
@@ -441,6 +665,7 @@ impl GlColoredPipeline {
                 );
             }
         }
+        Ok(())
     }
 
     pub fn create_cube(&self) -> Result<GlMesh> {
@@ -456,34 +681,52 @@ impl GlColoredPipeline {
 
 // ----------------------------------------------------------------------------
 impl GlPipeline for GlColoredPipeline {
-    fn render(
-        &self,
-        bindings: &GlMesh,
-        material: &GlMaterial,
-        uniforms: &GlUniforms,
-    ) -> Result<()> {
+    fn bind(&self, uniforms: &GlUniforms) -> Result<()> {
         let gl = &self.gl;
+        unsafe { gl.UseProgram(self.shader) };
+        gl_graphics::set_uniform_m4(gl, self.uid_camera, &uniforms.camera);
+        gl_graphics::set_uniform_m4(gl, self.uid_view, &uniforms.view);
+        gl_graphics::set_uniform_m4(gl, self.uid_projection, &uniforms.projection);
+        gl_graphics::set_uniform_v3(gl, self.uid_light_pos, &uniforms.light_pos);
+        gl_graphics::set_uniform_v3(gl, self.uid_light_dir, &uniforms.light_dir);
+        gl_graphics::set_uniform_1i(
+            gl,
+            self.uid_light_directional,
+            uniforms.light_directional as gl::GLint,
+        );
+        gl_graphics::set_uniform_v3(gl, self.uid_view_pos, &uniforms.view_pos);
+        gl_graphics::set_uniform_v3(gl, self.uid_light_color, &uniforms.light_color);
+        gl_graphics::set_uniform_1f(gl, self.uid_log_depth_coef, uniforms.log_depth_coef);
+        Ok(())
+    }
+
+    fn bind_material(&self, material: &GlMaterial) -> Result<()> {
         let color = match material {
             GlMaterial::Color { color } => *color,
             _ => V3::new([1.0, 1.0, 1.0]),
         };
+        gl_graphics::set_uniform_v3(&self.gl, self.uid_object_color, &color);
+        Ok(())
+    }
+
+    fn draw(&self, bindings: &GlMesh, uniforms: &GlUniforms) -> Result<()> {
+        let gl = &self.gl;
+        gl_graphics::set_uniform_m4(gl, self.uid_model, &uniforms.model);
+        gl_graphics::set_uniform_m3(gl, self.uid_normal, &uniforms.normal);
+        gl_graphics::set_uniform_1i(gl, self.uid_mat_id, uniforms.mat_id);
+        gl_graphics::set_uniform_1i(
+            gl,
+            self.uid_use_vertex_color,
+            bindings.has_colors as gl::GLint,
+        );
+        gl_graphics::set_uniform_1i(gl, self.uid_use_vertex_ao, bindings.has_ao as gl::GLint);
+        gl_graphics::set_uniform_1i(
+            gl,
+            self.uid_flat_shading,
+            (uniforms.shading == ShadingMode::Flat) as gl::GLint,
+        );
         unsafe {
-            gl.UseProgram(self.shader);
             gl.BindVertexArray(bindings.vao_vertices);
-            gl.UniformMatrix4fv(self.uid_model, 1, gl::FALSE, uniforms.model.as_ptr());
-            gl.UniformMatrix4fv(self.uid_camera, 1, gl::FALSE, uniforms.camera.as_ptr());
-            gl.UniformMatrix4fv(self.uid_view, 1, gl::FALSE, uniforms.view.as_ptr());
-            gl.UniformMatrix4fv(
-                self.uid_projection,
-                1,
-                gl::FALSE,
-                uniforms.projection.as_ptr(),
-            );
-            gl.Uniform1i(self.uid_mat_id, uniforms.mat_id);
-            gl.Uniform3fv(self.uid_light_pos, 1, uniforms.light_pos.as_ptr());
-            gl.Uniform3fv(self.uid_view_pos, 1, uniforms.view_pos.as_ptr());
-            gl.Uniform3fv(self.uid_light_color, 1, uniforms.light_color.as_ptr());
-            gl.Uniform3fv(self.uid_object_color, 1, color.as_ptr());
 
             if bindings.has_indices {
                 if !bindings.is_debug {
@@ -531,19 +774,32 @@ const VS_COLOR: &str = r#"
 #version 330 core
 layout (location = 0) in vec3 a_pos;
 layout (location = 1) in vec3 a_norm;
+layout (location = 2) in vec3 a_color;
+layout (location = 3) in float a_ao;
 
 uniform mat4 model;
+uniform mat3 normalMatrix;
 uniform mat4 view;
 uniform mat4 projection;
 uniform mat4 camera;
 
 out vec3 v_norm;
 out vec3 v_pos;
+out vec3 v_color;
+out float v_ao;
+#ifdef LOG_DEPTH
+out float v_flogz;
+#endif
 
 void main() {
     gl_Position = camera * model * vec4(a_pos, 1.0);
-    v_norm = (model * vec4(a_norm, 0.0)).xyz;
+    v_norm = normalMatrix * a_norm;
     v_pos = (model * vec4(a_pos, 1.0)).xyz;
+    v_color = a_color;
+    v_ao = a_ao;
+#ifdef LOG_DEPTH
+    v_flogz = 1.0 + gl_Position.w;
+#endif
 }"#;
 
 // ----------------------------------------------------------------------------
@@ -551,21 +807,40 @@ const FS_COLOR: &str = r#"
 #version 330 core
 in vec3 v_norm;
 in vec3 v_pos;
+in vec3 v_color;
+in float v_ao;
+#ifdef LOG_DEPTH
+in float v_flogz;
+uniform float logDepthCoef;
+#endif
 
 uniform vec3 lightPos; 
+uniform vec3 lightDirection;
+uniform bool lightDirectional;
 uniform vec3 viewPos; 
 uniform vec3 lightColor;
 uniform vec3 objectColor;
+uniform bool useVertexColor;
+uniform bool useVertexAo;
+uniform bool flatShading;
 
 out vec4 FragColor;
 void main() {
     // ambient
     float ambientStrength = 0.1;
-    vec3 ambient = ambientStrength * lightColor;
+    float ao = useVertexAo ? v_ao : 1.0;
+    vec3 ambient = ambientStrength * ao * lightColor;
 
     // diffuse
     vec3 norm = normalize(v_norm);
-    vec3 lightDir = normalize(lightPos - v_pos);
+    if (flatShading) {
+        // the position derivatives span the triangle's plane; keep the face
+        // normal on the side of the vertex normals
+        vec3 face = normalize(cross(dFdx(v_pos), dFdy(v_pos)));
+        norm = dot(face, norm) < 0.0 ? -face : face;
+    }
+    // a directional light comes from the same direction for all fragments
+    vec3 lightDir = lightDirectional ? lightDirection : normalize(lightPos - v_pos);
     float diff = max(dot(norm, lightDir), 0.0);
     vec3 diffuse = diff * lightColor;
     
@@ -576,6 +851,145 @@ void main() {
     float spec = pow(max(dot(viewDir, reflectDir), 0.0), 32);
     vec3 specular = specularStrength * spec * lightColor;
         
-    vec3 result = (ambient + diffuse + specular) * objectColor;
+    vec3 color = useVertexColor ? v_color : objectColor;
+    vec3 result = (ambient + diffuse + specular) * color;
     FragColor = vec4(result, 1.0);
+#ifdef LOG_DEPTH
+    gl_FragDepth = log2(v_flogz) * 0.5 * logDepthCoef;
+#endif
 }"#;
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_depth_selects_depth_writing_shader() {
+        let (vs, fs) = shader_sources(DepthMode::Linear);
+        assert!(!vs.contains("#define LOG_DEPTH"));
+        assert!(!fs.contains("#define LOG_DEPTH"));
+
+        let (vs, fs) = shader_sources(DepthMode::Logarithmic);
+        assert!(vs.starts_with("\n#version 330 core\n#define LOG_DEPTH\n"));
+        assert!(fs.starts_with("\n#version 330 core\n#define LOG_DEPTH\n"));
+        assert!(fs.contains("gl_FragDepth"));
+    }
+
+    // ------------------------------------------------------------------------
+    // CPU version of the normal computed by FS_COLOR.
+    fn fragment_normal(shading: ShadingMode, v_norm: V3, dpdx: V3, dpdy: V3) -> V3 {
+        let norm = v_norm.norm();
+        match shading {
+            ShadingMode::Smooth => norm,
+            ShadingMode::Flat => {
+                let face = dpdx.cross(dpdy).norm();
+                if face.dot(norm) < 0.0 { -face } else { face }
+            }
+        }
+    }
+
+    #[test]
+    fn flat_shading_uses_face_normal() {
+        let p = [
+            V3::new([0.0, 0.0, 0.0]),
+            V3::new([1.0, 0.2, 0.0]),
+            V3::new([0.0, 0.4, 1.0]),
+        ];
+        let n = [V3::X1, V3::new([0.5, 1.0, 0.0]), V3::new([0.0, 1.0, -0.5])];
+
+        // Fragments at barycentric (u, v), with screen axes along the edges
+        let (dpdx, dpdy) = (p[1] - p[0], p[2] - p[0]);
+        let fragments = [(0.1, 0.1), (0.8, 0.1), (0.1, 0.8), (0.3, 0.3)];
+        let normals = |shading| -> Vec<V3> {
+            fragments
+                .iter()
+                .map(|&(u, v)| {
+                    let v_norm = (1.0 - u - v) * n[0] + u * n[1] + v * n[2];
+                    fragment_normal(shading, v_norm, dpdx, dpdy)
+                })
+                .collect()
+        };
+
+        let smooth = normals(ShadingMode::Smooth);
+        assert!(smooth.windows(2).any(|w| w[0] != w[1]));
+
+        let face = -face_normal(p[0], p[1], p[2]);
+        assert!(face.x1() > 0.0);
+        assert!(normals(ShadingMode::Flat).iter().all(|&n| n == face));
+
+        // Swapped screen axes flip the cross product, not the result
+        let flipped = fragment_normal(ShadingMode::Flat, n[0], dpdy, dpdx);
+        assert_eq!(flipped, face);
+    }
+
+    #[test]
+    fn unit_cube_aabb() {
+        let (verts, _) = create_unit_cube_mesh();
+        let aabb = mesh_aabb(&verts);
+        assert_eq!(aabb, Aabb::new(V3::uniform(-0.5), V3::uniform(0.5)));
+        assert!(mesh_aabb::<Vertex>(&[]).is_empty());
+    }
+
+    #[test]
+    fn occluded_vertex_layout() {
+        assert_eq!(OccludedVertex::STRIDE, 40);
+        assert!(OccludedVertex::has_ao());
+        assert!(OccludedVertex::has_colors());
+        assert!(!Vertex::has_ao() && !ColoredVertex::has_ao());
+
+        let ao = OccludedVertex::ATTRIBS[3];
+        assert_eq!((ao.location, ao.components, ao.offset), (3, 1, 36));
+    }
+
+    #[test]
+    fn vertex_layout() {
+        assert_eq!(Vertex::STRIDE, 24);
+        assert!(!Vertex::has_colors());
+        let offsets: Vec<_> = Vertex::ATTRIBS.iter().map(|a| a.offset).collect();
+        assert_eq!(offsets, [0, 12]);
+    }
+
+    #[test]
+    fn colored_triangle_layout() {
+        let n = V3::new([0.0, 0.0, 1.0]);
+        #[rustfmt::skip]
+        let tri = [
+            ColoredVertex { pos: V3::new([0.0, 0.0, 0.0]), n, color: V3::new([1.0, 0.0, 0.0]) },
+            ColoredVertex { pos: V3::new([1.0, 0.0, 0.0]), n, color: V3::new([0.0, 1.0, 0.0]) },
+            ColoredVertex { pos: V3::new([0.0, 1.0, 0.0]), n, color: V3::new([0.0, 0.0, 1.0]) },
+        ];
+
+        assert_eq!(ColoredVertex::STRIDE, 36);
+        assert_eq!(std::mem::size_of_val(&tri), 3 * ColoredVertex::STRIDE);
+        assert!(ColoredVertex::has_colors());
+        assert_eq!(
+            ColoredVertex::ATTRIBS,
+            [
+                VertexAttrib {
+                    location: 0,
+                    components: 3,
+                    offset: 0
+                },
+                VertexAttrib {
+                    location: 1,
+                    components: 3,
+                    offset: 12
+                },
+                VertexAttrib {
+                    location: 2,
+                    components: 3,
+                    offset: 24
+                },
+            ]
+        );
+
+        // the color of the second vertex sits one stride plus the color offset
+        // into the buffer
+        let floats: &[f32] = unsafe {
+            std::slice::from_raw_parts(tri.as_ptr() as *const f32, 3 * ColoredVertex::STRIDE / 4)
+        };
+        let ofs = (ColoredVertex::STRIDE + ColoredVertex::ATTRIBS[2].offset) / 4;
+        assert_eq!(floats[ofs..ofs + 3], [0.0, 1.0, 0.0]);
+    }
+}
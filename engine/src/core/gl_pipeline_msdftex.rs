@@ -1,8 +1,9 @@
+use crate::core::bounds::Aabb;
 use crate::core::gl_graphics;
 use crate::core::gl_pipeline::{GlMaterial, GlMesh, GlPipeline, GlUniforms};
 use crate::error::Result;
 use crate::sys::opengl as gl;
-use crate::v2d::v2::V2;
+use crate::v2d::{v2::V2, v3::V3};
 use std::rc::Rc;
 
 // ----------------------------------------------------------------------------
@@ -12,6 +13,12 @@ pub struct Vertex {
     pub tex: V2,
 }
 
+// ----------------------------------------------------------------------------
+// Glyph quads lie in the z = 0 plane of the object.
+fn extents(vertices: &[Vertex]) -> Aabb {
+    Aabb::from_points(vertices.iter().map(|v| V3::from_v2(&v.pos, 0.0)))
+}
+
 // ----------------------------------------------------------------------------
 #[derive(Debug)]
 pub struct GlMSDFTexPipeline {
@@ -30,8 +37,8 @@ impl GlMSDFTexPipeline {
             return Err(e);
         };
         let shader = shader.unwrap();
-        let uid_model = gl_graphics::get_uniform_location(&gl, shader, "model").unwrap_or(-1);
-        let uid_view = gl_graphics::get_uniform_location(&gl, shader, "camera").unwrap_or(-1);
+        let uid_model = gl_graphics::uniform_location_or_warn(&gl, shader, "model");
+        let uid_view = gl_graphics::uniform_location_or_warn(&gl, shader, "camera");
         Ok(GlMSDFTexPipeline {
             gl,
             shader,
@@ -70,13 +77,18 @@ impl GlMSDFTexPipeline {
             vbo_indices: 0,
             num_indices: 0,
             num_vertices: vertices.len() as gl::GLsizei,
+            stride: std::mem::size_of::<Vertex>(),
             primitive_type: gl::TRIANGLES,
             has_indices: false,
+            has_colors: false,
+            has_ao: false,
             is_debug: false,
+            extents: extents(vertices),
         })
     }
 
-    pub fn update_mesh(&self, mesh: &GlMesh, vertices: &[Vertex]) {
+    pub fn update_mesh(&self, mesh: &mut GlMesh, vertices: &[Vertex]) {
+        mesh.extents = extents(vertices);
         let gl = &self.gl;
         unsafe {
             gl_graphics::update_buffer(
@@ -91,18 +103,30 @@ impl GlMSDFTexPipeline {
 
 // ----------------------------------------------------------------------------
 impl GlPipeline for GlMSDFTexPipeline {
-    fn render(&self, mesh: &GlMesh, material: &GlMaterial, uniforms: &GlUniforms) -> Result<()> {
+    fn bind(&self, uniforms: &GlUniforms) -> Result<()> {
+        let gl = &self.gl;
+        unsafe { gl.UseProgram(self.shader) };
+        gl_graphics::set_uniform_m4(gl, self.uid_view, &uniforms.camera);
+        Ok(())
+    }
+
+    fn bind_material(&self, material: &GlMaterial) -> Result<()> {
         let gl = &self.gl;
         let texture = match material {
             GlMaterial::Texture { texture } => *texture,
             _ => 0,
         };
         unsafe {
-            gl.UseProgram(self.shader);
             gl.ActiveTexture(gl::TEXTURE0);
             gl.BindTexture(gl::TEXTURE_2D, texture);
-            gl.UniformMatrix4fv(self.uid_model, 1, gl::FALSE, uniforms.model.as_ptr());
-            gl.UniformMatrix4fv(self.uid_view, 1, gl::FALSE, uniforms.camera.as_ptr());
+        }
+        Ok(())
+    }
+
+    fn draw(&self, mesh: &GlMesh, uniforms: &GlUniforms) -> Result<()> {
+        let gl = &self.gl;
+        gl_graphics::set_uniform_m4(gl, self.uid_model, &uniforms.model);
+        unsafe {
             gl.BindVertexArray(mesh.vao_vertices);
             gl.DrawArrays(mesh.primitive_type, 0, mesh.num_vertices);
         }
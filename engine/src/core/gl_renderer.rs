@@ -1,27 +1,105 @@
 use crate::core::IRenderer;
+use crate::core::bounds::{Aabb, BoundingSphere};
 use crate::core::camera::Camera;
 use crate::core::gl_graphics::{
-    create_framebuffer, create_program, create_texture_vao, print_opengl_info,
+    create_framebuffer, create_program, create_texture_vao, print_opengl_info, set_uniform_1f,
+    uniform_location_or_warn,
 };
-use crate::core::gl_pipeline::{self, GlMaterial, GlMaterialId, GlMeshId};
-use crate::core::gl_pipeline_colored::{self, GlColoredPipeline};
+use crate::core::gl_pipeline::{
+    self, GlMaterial, GlMaterialId, GlMaterials, GlMesh, GlMeshId, GlMeshes, GlPipeline,
+    GlUniforms, ShadingMode,
+};
+use crate::core::gl_pipeline_colored::{self, DepthMode, GlColoredPipeline, VertexFormat};
 use crate::core::gl_pipeline_msdftex::{self, GlMSDFTexPipeline};
+use crate::core::light::Light;
 use crate::error::{Error, Result};
 use crate::sys::opengl as gl;
 use crate::v2d::{affine4x4, m4x4::M4x4, q::Q, v3::V3, v4::V4};
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
+// ----------------------------------------------------------------------------
+// Uniforms shared by all objects of a frame; `model` is set per object.
+pub fn scene_uniforms(camera: &Camera, aspect: f32, light: &Light) -> gl_pipeline::GlUniforms {
+    gl_pipeline::GlUniforms::default()
+        .with_view_projection(camera.transform(), camera.projection(aspect))
+        .with_view_pos(camera.position().into())
+        .with_light(light)
+        .with_far(camera.far())
+}
+
+// ----------------------------------------------------------------------------
+// Draws `objects` grouped by pipeline, then by material, so the program and
+// material state only change between groups instead of for every object.
+// `order` is scratch space for the sorted indices, kept to avoid allocating
+// every frame.
+fn draw_sorted(
+    objects: &[RenderObject],
+    order: &mut Vec<usize>,
+    meshes: &GlMeshes,
+    materials: &GlMaterials,
+    pipes: &[Rc<dyn GlPipeline>],
+    mut uniforms: GlUniforms,
+) -> Result<()> {
+    order.clear();
+    order.extend(0..objects.len());
+    order.sort_by_key(|&i| (objects[i].pipe_id, objects[i].material_id.index()));
+
+    let mut bound_pipe = None;
+    let mut bound_material = None;
+    for object in order.iter().map(|&i| &objects[i]) {
+        let mesh = meshes.get(object.mesh_id);
+        let pipe = pipes.get(object.pipe_id);
+        let material = materials.get(object.material_id);
+        if let (Some(mesh), Some(material), Some(pipe)) = (mesh, material, pipe) {
+            if bound_pipe != Some(object.pipe_id) {
+                pipe.bind(&uniforms)?;
+                bound_pipe = Some(object.pipe_id);
+                bound_material = None;
+            }
+            if bound_material != Some(object.material_id.index()) {
+                pipe.bind_material(material)?;
+                bound_material = Some(object.material_id.index());
+            }
+            uniforms = uniforms
+                .with_model(object.transform.into())
+                .with_shading(object.shading);
+            pipe.draw(mesh, &uniforms)?;
+        }
+    }
+    Ok(())
+}
+
+// ----------------------------------------------------------------------------
+// Effect applied when the offscreen frame is copied to the screen. `None`
+// uses a plain copy shader; the others share the post-process shader.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PostProcess {
+    #[default]
+    None,
+    Grain,
+}
+
+// ----------------------------------------------------------------------------
+const DEFAULT_GRAIN_INTENSITY: f32 = 0.05;
+
 // ----------------------------------------------------------------------------
 pub struct Renderer {
     gl: Rc<gl::OpenGlFunctions>,
     texture_vao: gl::GLuint,
     texture_program: gl::GLuint,
+    post_program: gl::GLuint,
+    uid_intensity: gl::GLint,
+    clear_color: V4,
+    post_process: PostProcess,
+    intensity: f32,
     fbo: gl::GLuint,
     color_tex: gl::GLuint,
     depth_tex: gl::GLuint,
     fbo_width: usize,
     fbo_height: usize,
-    projection: M4x4,
+    viewport: Cell<(i32, i32)>,
+    draw_order: RefCell<Vec<usize>>,
 }
 
 // ----------------------------------------------------------------------------
@@ -33,74 +111,96 @@ impl Renderer {
         let fbo_height = 720;
 
         let texture_vao = create_texture_vao(&gl);
-        let texture_program = create_program(&gl, "texture", VS_TEXTURE, FS_TEXTURE).unwrap();
+        let texture_program = create_program(&gl, "texture", VS_TEXTURE, FS_TEXTURE)?;
+        let post_program = create_program(&gl, "post", VS_TEXTURE, FS_GRAIN)?;
+        let uid_intensity = uniform_location_or_warn(&gl, post_program, "intensity");
         let (fbo, color_tex, depth_tex) = create_framebuffer(&gl, fbo_width, fbo_height)?;
 
-        let aspect = fbo_width as f32 / fbo_height as f32;
-        let projection = affine4x4::perspective(45.0, aspect, 0.1, 100.0);
-
         Ok(Self {
             gl,
             texture_vao,
             texture_program,
+            post_program,
+            uid_intensity,
+            clear_color: V4::new([0.3, 0.2, 0.1, 1.0]),
+            post_process: PostProcess::default(),
+            intensity: DEFAULT_GRAIN_INTENSITY,
             fbo,
             color_tex,
             depth_tex,
             fbo_width,
             fbo_height,
-            projection,
+            viewport: Cell::new((fbo_width as i32, fbo_height as i32)),
+            draw_order: RefCell::new(Vec::new()),
         })
     }
 
-    fn render_1st_pass(
-        &self,
-        camera: &Camera,
-        objects: Vec<RenderObject>,
-        context: &RenderContext,
-    ) -> Result<()> {
-        let gl = &self.gl;
+    // Window size the final image is drawn at.
+    pub fn viewport(&self) -> (i32, i32) {
+        self.viewport.get()
+    }
+
+    pub fn clear_color(&self) -> V4 {
+        self.clear_color
+    }
+
+    pub fn set_clear_color(&mut self, color: V4) {
+        self.clear_color = color;
+    }
 
-        let view = camera.transform();
-        let cam_pos = camera.position();
-        let projection = self.projection;
-        let camera = projection * view;
+    pub fn post_process(&self) -> PostProcess {
+        self.post_process
+    }
+
+    pub fn set_post_process(&mut self, post_process: PostProcess) {
+        self.post_process = post_process;
+    }
 
+    pub fn post_intensity(&self) -> f32 {
+        self.intensity
+    }
+
+    // Strength of the post-process effect, e.g. the texture coordinate offset
+    // of the grain.
+    pub fn set_post_intensity(&mut self, intensity: f32) {
+        self.intensity = intensity.max(0.0);
+    }
+
+    // Binds the offscreen framebuffer and clears it to the background color.
+    fn begin_frame(&self) {
+        let gl = &self.gl;
+        let c = self.clear_color;
         unsafe {
             gl.BindFramebuffer(gl::FRAMEBUFFER, self.fbo);
             gl.Enable(gl::DEPTH_TEST);
             gl.Enable(gl::CULL_FACE);
-            gl.ClearColor(0.3, 0.2, 0.1, 1.0);
+            gl.ClearColor(c.x0(), c.x1(), c.x2(), c.x3());
             gl.Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
         }
+    }
 
-        let mut uniforms = gl_pipeline::GlUniforms {
-            model: M4x4::identity(),
-            view,
-            projection,
-            camera,
-            mat_id: 0,
-            light_pos: V3::new([2.0, 5.0, 2.0]),
-            view_pos: cam_pos.into(),
-            light_color: V3::new([1.0, 0.5, 1.0]),
-            object_color: V3::new([0.5, 1.0, 1.0]),
-        };
+    fn render_1st_pass(
+        &self,
+        camera: &Camera,
+        objects: &[RenderObject],
+        context: &RenderContext,
+    ) -> Result<()> {
+        // The offscreen image is stretched to the window, so the projection
+        // has to match the window's aspect rather than the framebuffer's.
+        let (cx, cy) = self.viewport.get();
+        let aspect = cx as f32 / cy as f32;
+        let uniforms = scene_uniforms(camera, aspect, context.light());
 
-        let meshes = context.meshes();
-        let materials = context.materials();
-        let pipes = context.pipes();
-
-        for object in objects {
-            let mesh = meshes.get(object.mesh_id);
-            let pipe = pipes.get(object.pipe_id);
-            let material = materials.get(object.material_id);
-            if let (Some(mesh), Some(material), Some(pipe)) = (mesh, material, pipe) {
-                uniforms.model = object.transform.into();
-                uniforms.mat_id = 0;
-                pipe.render(mesh, material, &uniforms)?;
-            }
-        }
+        self.begin_frame();
 
-        Ok(())
+        draw_sorted(
+            objects,
+            &mut self.draw_order.borrow_mut(),
+            context.meshes(),
+            context.materials(),
+            context.pipes(),
+            uniforms,
+        )
     }
 
     fn render_2nd_pass(&self) -> Result<()> {
@@ -109,7 +209,13 @@ impl Renderer {
             gl.BindFramebuffer(gl::FRAMEBUFFER, 0);
             gl.Disable(gl::DEPTH_TEST);
 
-            gl.UseProgram(self.texture_program);
+            match self.post_process {
+                PostProcess::None => gl.UseProgram(self.texture_program),
+                PostProcess::Grain => {
+                    gl.UseProgram(self.post_program);
+                    set_uniform_1f(gl, self.uid_intensity, self.intensity);
+                }
+            }
             gl.BindVertexArray(self.texture_vao);
             gl.ActiveTexture(gl::TEXTURE0);
             gl.BindTexture(gl::TEXTURE_2D, self.color_tex);
@@ -126,7 +232,7 @@ impl IRenderer for Renderer {
     fn render(
         &self,
         camera: &Camera,
-        objects: Vec<RenderObject>,
+        objects: &[RenderObject],
         context: &RenderContext,
     ) -> Result<()> {
         self.render_1st_pass(camera, objects, context)?;
@@ -134,8 +240,13 @@ impl IRenderer for Renderer {
         Ok(())
     }
 
+    // Minimized windows report a zero size, which would break the aspect.
     fn resize(&self, cx: i32, cy: i32) {
-        println!("Resize to {cx} x {cy}");
+        if cx <= 0 || cy <= 0 {
+            return;
+        }
+        log::info!("Resize to {cx} x {cy}");
+        self.viewport.set((cx, cy));
         unsafe { self.gl.Viewport(0, 0, cx, cy) };
     }
 }
@@ -168,12 +279,19 @@ pub struct RenderContext {
     pipes: Vec<Rc<dyn gl_pipeline::GlPipeline>>,
     default_mesh_ids: Vec<GlMeshId>,
     default_material_ids: Vec<GlMaterialId>,
+    light: Light,
 }
 
 // ----------------------------------------------------------------------------
 impl RenderContext {
     pub fn new(gl: Rc<gl::OpenGlFunctions>) -> Result<Self> {
-        let colored_pipe = Rc::new(GlColoredPipeline::new(Rc::clone(&gl))?);
+        Self::with_depth_mode(gl, DepthMode::default())
+    }
+
+    // Logarithmic depth trades a little precision up close for far fewer
+    // z-fighting artifacts on distant terrain.
+    pub fn with_depth_mode(gl: Rc<gl::OpenGlFunctions>, depth_mode: DepthMode) -> Result<Self> {
+        let colored_pipe = Rc::new(GlColoredPipeline::new(Rc::clone(&gl), depth_mode)?);
         let msdftex_pipe = Rc::new(GlMSDFTexPipeline::new(Rc::clone(&gl))?);
 
         let cube = colored_pipe.create_cube()?;
@@ -219,16 +337,25 @@ impl RenderContext {
             pipes: vec![colored_pipe, msdftex_pipe],
             default_mesh_ids,
             default_material_ids,
+            light: Light::default(),
         })
     }
 
+    pub fn light(&self) -> &Light {
+        &self.light
+    }
+
+    pub fn set_light(&mut self, light: Light) {
+        self.light = light;
+    }
+
     pub fn insert_material(&mut self, material: GlMaterial) -> GlMaterialId {
         self.materials.insert(material)
     }
 
-    pub fn create_colored_mesh(
+    pub fn create_colored_mesh<V: VertexFormat>(
         &mut self,
-        vertices: &[gl_pipeline_colored::Vertex],
+        vertices: &[V],
         indices: &[u32],
         is_debug: bool,
     ) -> Result<GlMeshId> {
@@ -236,15 +363,20 @@ impl RenderContext {
         Ok(self.meshes.insert(mesh))
     }
 
-    pub fn update_colored_mesh(
+    // Line segments between consecutive pairs of `points`.
+    pub fn create_line_mesh(&mut self, points: &[V3], color: V3) -> Result<GlMeshId> {
+        let mesh = self.colored_pipe.create_line_mesh(points, color)?;
+        Ok(self.meshes.insert(mesh))
+    }
+
+    pub fn update_colored_mesh<V: VertexFormat>(
         &mut self,
         mesh_id: GlMeshId,
-        vertices: &[gl_pipeline_colored::Vertex],
+        vertices: &[V],
         indices: &[u32],
     ) -> Result<()> {
-        let mesh = self.meshes.get(mesh_id).ok_or(Error::InvalidMeshId)?;
-        self.colored_pipe.update_mesh(mesh, vertices, indices);
-        Ok(())
+        let mesh = self.meshes.get_mut(mesh_id).ok_or(Error::InvalidMeshId)?;
+        self.colored_pipe.update_mesh(mesh, vertices, indices)
     }
 
     pub fn create_msdftex_mesh(
@@ -260,7 +392,7 @@ impl RenderContext {
         mesh_id: GlMeshId,
         vertices: &[gl_pipeline_msdftex::Vertex],
     ) -> Result<()> {
-        let mesh = self.meshes.get(mesh_id).ok_or(Error::InvalidMeshId)?;
+        let mesh = self.meshes.get_mut(mesh_id).ok_or(Error::InvalidMeshId)?;
         self.msdftex_pipe.update_mesh(mesh, vertices);
         Ok(())
     }
@@ -304,6 +436,14 @@ impl RenderContext {
     }
 }
 
+// ----------------------------------------------------------------------------
+impl Drop for RenderContext {
+    fn drop(&mut self) {
+        gl_pipeline::delete_meshes(&self.gl, &self.meshes);
+        gl_pipeline::delete_materials(&self.gl, &self.materials);
+    }
+}
+
 // ----------------------------------------------------------------------------
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum Rotation {
@@ -359,6 +499,19 @@ impl Default for Transform {
     }
 }
 
+// ----------------------------------------------------------------------------
+impl Transform {
+    // Turns the object's forward axis towards `target`, keeping its up axis
+    // close to `up`. Leaves the rotation unchanged if `target` is at the
+    // object's position or straight above or below it.
+    pub fn look_at(&mut self, target: V3, up: V3) {
+        let position: V3 = self.position.into();
+        if let Some(q) = Q::look_rotation(target - position, up) {
+            self.rotation = Rotation::Quat(q);
+        }
+    }
+}
+
 // ----------------------------------------------------------------------------
 impl From<Transform> for M4x4 {
     fn from(tx: Transform) -> Self {
@@ -374,14 +527,95 @@ impl From<Transform> for M4x4 {
 }
 
 // ----------------------------------------------------------------------------
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default)]
 pub struct RenderObject {
-    pub name: String,
+    pub name: Rc<str>,
     pub children: Vec<RenderObject>,
     pub transform: Transform,
     pub pipe_id: usize,
     pub mesh_id: GlMeshId,
     pub material_id: GlMaterialId,
+    pub shading: ShadingMode,
+}
+
+// ----------------------------------------------------------------------------
+// Names are shared, and `clone_from` reuses the children buffer of the
+// target, so lists of objects can be refreshed every frame without allocating.
+impl Clone for RenderObject {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            children: self.children.clone(),
+            transform: self.transform,
+            pipe_id: self.pipe_id,
+            mesh_id: self.mesh_id,
+            material_id: self.material_id,
+            shading: self.shading,
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.name.clone_from(&source.name);
+        self.children.clone_from(&source.children);
+        self.transform = source.transform;
+        self.pipe_id = source.pipe_id;
+        self.mesh_id = source.mesh_id;
+        self.material_id = source.material_id;
+        self.shading = source.shading;
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Overwrites `objects` with copies of `source`, reusing existing elements.
+pub fn copy_objects<'a>(
+    objects: &mut Vec<RenderObject>,
+    source: impl IntoIterator<Item = &'a RenderObject>,
+) {
+    let mut len = 0;
+    for (i, object) in source.into_iter().enumerate() {
+        match objects.get_mut(i) {
+            Some(target) => target.clone_from(object),
+            None => objects.push(object.clone()),
+        }
+        len = i + 1;
+    }
+    objects.truncate(len);
+}
+
+// ----------------------------------------------------------------------------
+impl RenderObject {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // World space bounds of `mesh` placed by this object's transform.
+    pub fn aabb(&self, mesh: &GlMesh) -> Aabb {
+        mesh.aabb().transformed(&self.transform.into())
+    }
+
+    pub fn bounding_sphere(&self, mesh: &GlMesh) -> BoundingSphere {
+        self.aabb(mesh).bounding_sphere()
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Nearest object whose bounds are hit by the ray. Objects without a live mesh
+// and their children are not considered.
+pub fn pick<'a>(
+    objects: impl IntoIterator<Item = &'a RenderObject>,
+    meshes: &GlMeshes,
+    origin: V3,
+    dir: V3,
+) -> Option<&'a RenderObject> {
+    objects
+        .into_iter()
+        .filter_map(|object| {
+            let mesh = meshes.get(object.mesh_id)?;
+            let t = object.aabb(mesh).ray_hit(origin, dir)?;
+            Some((t, object))
+        })
+        .min_by(|(t0, _), (t1, _)| t0.total_cmp(t1))
+        .map(|(_, object)| object)
 }
 
 // ----------------------------------------------------------------------------
@@ -401,13 +635,424 @@ const FS_TEXTURE: &str = r#"
 in vec2 TexCoord;
 out vec4 FragColor;
 uniform sampler2D texture1;
+void main() {
+    FragColor = texture(texture1, TexCoord.st);
+}"#;
+
+// ----------------------------------------------------------------------------
+const FS_GRAIN: &str = r#"
+#version 330 core
+in vec2 TexCoord;
+out vec4 FragColor;
+uniform sampler2D texture1;
+uniform float intensity;
 float rand(vec2 n) {
     return fract(sin(dot(n, vec2(12.9898, 4.1414))) * 43758.5453);
 }
 void main() {
     float n0 = rand( TexCoord.st) - 0.5;
     float n1 = rand(-TexCoord.ts) - 0.5;
-    //vec2 noise = 0.05 * vec2(n0*n0, n1*n1);
-    vec2 noise = vec2(0.0);
+    vec2 noise = intensity * vec2(n0*n0, n1*n1);
     FragColor = texture(texture1, TexCoord.st + noise);
 }"#;
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sys::opengl::tests::{
+        clear_color, draw_calls, draw_modes, mock_gl, program_binds, uniform_locations,
+        uniforms_1f, uniforms_1i, uniforms_3f, used_program, viewport,
+    };
+
+    // ------------------------------------------------------------------------
+    fn unit_cube() -> GlMesh {
+        GlMesh {
+            vao_vertices: 1,
+            vbo_vertices: 2,
+            vbo_indices: 0,
+            num_indices: 0,
+            num_vertices: 0,
+            stride: 0,
+            primitive_type: gl::TRIANGLES,
+            has_indices: false,
+            has_colors: false,
+            has_ao: false,
+            is_debug: false,
+            extents: Aabb::new(V3::uniform(-0.5), V3::uniform(0.5)),
+        }
+    }
+
+    fn cube_at(mesh_id: GlMeshId, name: &str, position: V4) -> RenderObject {
+        RenderObject {
+            name: name.into(),
+            transform: Transform {
+                position,
+                ..Default::default()
+            },
+            mesh_id,
+            ..Default::default()
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    fn mock_colored_pipe() -> GlColoredPipeline {
+        GlColoredPipeline {
+            gl: Rc::new(mock_gl()),
+            shader: 1,
+            uid_model: 0,
+            uid_normal: 14,
+            uid_view: 1,
+            uid_projection: 2,
+            uid_camera: 3,
+            uid_mat_id: 4,
+            uid_light_pos: 5,
+            uid_light_dir: 11,
+            uid_light_directional: 12,
+            uid_view_pos: 6,
+            uid_light_color: 7,
+            uid_object_color: 8,
+            uid_use_vertex_color: 9,
+            uid_use_vertex_ao: 13,
+            uid_log_depth_coef: 10,
+            uid_flat_shading: 15,
+        }
+    }
+
+    fn mock_renderer() -> Renderer {
+        Renderer {
+            gl: Rc::new(mock_gl()),
+            texture_vao: 1,
+            texture_program: 2,
+            post_program: 6,
+            uid_intensity: 0,
+            clear_color: V4::new([0.3, 0.2, 0.1, 1.0]),
+            post_process: PostProcess::default(),
+            intensity: DEFAULT_GRAIN_INTENSITY,
+            fbo: 3,
+            color_tex: 4,
+            depth_tex: 5,
+            fbo_width: 64,
+            fbo_height: 64,
+            viewport: Cell::new((64, 64)),
+            draw_order: RefCell::new(Vec::new()),
+        }
+    }
+
+    #[test]
+    fn frame_is_cleared_to_configured_color() {
+        let mut renderer = mock_renderer();
+        let sky = V4::new([0.5, 0.7, 1.0, 1.0]);
+        renderer.set_clear_color(sky);
+        assert_eq!(renderer.clear_color(), sky);
+
+        renderer.begin_frame();
+        assert_eq!(clear_color(), Some([0.5, 0.7, 1.0, 1.0]));
+        assert_eq!(renderer.post_process(), PostProcess::None);
+    }
+
+    #[test]
+    fn resize_event_updates_viewport() {
+        use crate::core::input::Event;
+
+        let renderer = mock_renderer();
+        let events = vec![
+            Event::Resize { cx: 640, cy: 480 },
+            Event::Wheel { delta: 120 },
+            Event::Resize { cx: 800, cy: 600 },
+        ];
+        renderer.input(&events);
+        assert_eq!(renderer.viewport(), (800, 600));
+        assert_eq!(viewport(), Some([0, 0, 800, 600]));
+
+        // minimized
+        renderer.input(&vec![Event::Resize { cx: 0, cy: 0 }]);
+        assert_eq!(renderer.viewport(), (800, 600));
+    }
+
+    #[test]
+    fn post_process_selects_shader() {
+        let mut renderer = mock_renderer();
+        renderer.render_2nd_pass().unwrap();
+        assert_eq!(used_program(), renderer.texture_program);
+        assert!(uniforms_1f().is_empty());
+
+        renderer.set_post_process(PostProcess::Grain);
+        renderer.set_post_intensity(0.2);
+        renderer.render_2nd_pass().unwrap();
+        assert_eq!(used_program(), renderer.post_program);
+        assert_eq!(uniforms_1f(), vec![(renderer.uid_intensity, 0.2)]);
+        assert_eq!(draw_calls(), 2);
+    }
+
+    #[test]
+    fn light_color_reaches_colored_pipeline() {
+        let pipe = mock_colored_pipe();
+        let material = GlMaterial::Color { color: V3::ONE };
+        let camera = Camera::new(V4::zero(), V4::zero());
+
+        let mut light = Light::default();
+        for color in [V3::new([1.0, 0.0, 0.0]), V3::new([0.2, 0.4, 0.6])] {
+            light.color = color;
+            light.intensity = 0.5;
+            let uniforms = scene_uniforms(&camera, 1.0, &light);
+            pipe.render(&unit_cube(), &material, &uniforms).unwrap();
+
+            let uploaded = uniforms_3f();
+            let last = uploaded.iter().rev().find(|(location, _)| *location == 7);
+            assert_eq!(last.map(|(_, v)| V3::new(*v)), Some(0.5 * color));
+        }
+        assert_eq!(draw_calls(), 2);
+    }
+
+    #[test]
+    fn directional_light_uploads_direction() {
+        let camera = Camera::new(V4::zero(), V4::zero());
+        let sun = Light::directional(V3::new([0.0, -1.0, 1.0]), V3::ONE, 1.0);
+        let uniforms = scene_uniforms(&camera, 1.0, &sun);
+        assert!(uniforms.light_directional);
+        assert_eq!(uniforms.light_dir, V3::new([0.0, 1.0, -1.0]).norm());
+
+        let material = GlMaterial::Color { color: V3::ONE };
+        mock_colored_pipe()
+            .render(&unit_cube(), &material, &uniforms)
+            .unwrap();
+        let uploaded = uniforms_3f();
+        let dir = uploaded.iter().find(|(location, _)| *location == 11);
+        assert_eq!(dir.map(|(_, v)| V3::new(*v)), Some(uniforms.light_dir));
+
+        let lamp = Light::default();
+        assert!(!scene_uniforms(&camera, 1.0, &lamp).light_directional);
+    }
+
+    #[test]
+    fn shared_pipeline_is_bound_once() {
+        let pipes: Vec<Rc<dyn GlPipeline>> = vec![Rc::new(mock_colored_pipe())];
+        let mut meshes = GlMeshes::new();
+        let cube = meshes.insert(unit_cube());
+        let mut materials = GlMaterials::new();
+        let red = materials.insert(GlMaterial::Color { color: V3::X0 });
+        let green = materials.insert(GlMaterial::Color { color: V3::X1 });
+
+        let objects: Vec<_> = (0..6)
+            .map(|i| RenderObject {
+                material_id: if i % 2 == 0 { red } else { green },
+                ..cube_at(cube, "cube", V4::new([i as f32, 0.0, 0.0, 1.0]))
+            })
+            .collect();
+        let uniforms = GlUniforms::default();
+        let mut order = Vec::new();
+        draw_sorted(&objects, &mut order, &meshes, &materials, &pipes, uniforms).unwrap();
+
+        assert_eq!(program_binds(), 1);
+        assert_eq!(draw_calls(), 6);
+        let colors = uniforms_3f();
+        let colors = colors.iter().filter(|(location, _)| *location == 8);
+        assert_eq!(colors.count(), 2);
+    }
+
+    #[test]
+    fn shading_mode_is_set_per_object() {
+        let pipes: Vec<Rc<dyn GlPipeline>> = vec![Rc::new(mock_colored_pipe())];
+        let mut meshes = GlMeshes::new();
+        let cube = meshes.insert(unit_cube());
+        let mut materials = GlMaterials::new();
+        let white = materials.insert(GlMaterial::Color { color: V3::ONE });
+
+        let objects: Vec<_> = [ShadingMode::Flat, ShadingMode::Smooth]
+            .into_iter()
+            .map(|shading| RenderObject {
+                material_id: white,
+                shading,
+                ..cube_at(cube, "cube", V4::zero())
+            })
+            .collect();
+        let uniforms = GlUniforms::default();
+        let mut order = Vec::new();
+        draw_sorted(&objects, &mut order, &meshes, &materials, &pipes, uniforms).unwrap();
+
+        let flat: Vec<_> = uniforms_1i()
+            .into_iter()
+            .filter(|(location, _)| *location == 15)
+            .map(|(_, v)| v)
+            .collect();
+        assert_eq!(flat, [1, 0]);
+    }
+
+    #[test]
+    fn line_meshes_draw_lines() {
+        let pipe = mock_colored_pipe();
+        let points = [V3::ZERO, V3::X0, V3::ZERO, V3::X2];
+        let mesh = pipe.create_line_mesh(&points, V3::ONE).unwrap();
+        assert_eq!(mesh.primitive_type, gl::LINES);
+        assert_eq!(mesh.num_vertices, 4);
+        assert!(mesh.has_colors && !mesh.has_indices && !mesh.is_debug);
+        assert_eq!(mesh.aabb(), Aabb::new(V3::ZERO, V3::new([1.0, 0.0, 1.0])));
+
+        let material = GlMaterial::Color { color: V3::ONE };
+        pipe.render(&mesh, &material, &GlUniforms::default())
+            .unwrap();
+        assert_eq!(draw_modes(), [gl::LINES]);
+
+        assert_eq!(
+            pipe.create_line_mesh(&points[..3], V3::ONE).err(),
+            Some(Error::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn mesh_updates_keep_the_vertex_format() {
+        use crate::core::gl_pipeline_colored::{ColoredVertex, OccludedVertex, Vertex};
+
+        let pipe = mock_colored_pipe();
+        let points = [V3::ZERO, V3::X0];
+        let mut mesh = pipe.create_line_mesh(&points, V3::ONE).unwrap();
+        assert_eq!(mesh.stride, std::mem::size_of::<ColoredVertex>());
+
+        let vertex = |pos| ColoredVertex {
+            pos,
+            n: V3::X1,
+            color: V3::ONE,
+        };
+        let moved = [vertex(V3::ZERO), vertex(V3::X2)];
+        pipe.update_mesh(&mut mesh, &moved, &[]).unwrap();
+        assert_eq!(mesh.aabb(), Aabb::new(V3::ZERO, V3::X2));
+
+        let plain = [Vertex {
+            pos: V3::X0,
+            n: V3::X1,
+        }];
+        let occluded = [OccludedVertex {
+            pos: V3::X0,
+            n: V3::X1,
+            color: V3::ONE,
+            ao: 1.0,
+        }];
+        let err = Some(Error::InvalidVertexFormat);
+        assert_eq!(pipe.update_mesh(&mut mesh, &plain, &[]).err(), err);
+        assert_eq!(pipe.update_mesh(&mut mesh, &occluded, &[]).err(), err);
+        assert_eq!(mesh.aabb(), Aabb::new(V3::ZERO, V3::X2));
+    }
+
+    #[test]
+    fn pipeline_skips_missing_uniforms() {
+        let mut pipe = mock_colored_pipe();
+        pipe.uid_normal = -1;
+        pipe.uid_use_vertex_ao = -1;
+        let material = GlMaterial::Color { color: V3::ONE };
+        pipe.render(&unit_cube(), &material, &GlUniforms::default())
+            .unwrap();
+
+        let locations = uniform_locations();
+        assert!(!locations.is_empty());
+        assert!(!locations.contains(&-1));
+        assert_eq!(draw_calls(), 1);
+    }
+
+    #[test]
+    fn copied_objects_reuse_buffers() {
+        let mut meshes = GlMeshes::new();
+        let cube = meshes.insert(unit_cube());
+        let mut source: Vec<_> = (0..4)
+            .map(|i| cube_at(cube, &format!("cube_{i}"), V4::zero()))
+            .collect();
+
+        let mut objects = Vec::new();
+        copy_objects(&mut objects, &source);
+        let buffer = objects.as_ptr();
+
+        for step in 0..10 {
+            source[0].transform.position = V4::new([step as f32, 0.0, 0.0, 1.0]);
+            copy_objects(&mut objects, &source);
+            assert_eq!(objects.as_ptr(), buffer);
+            assert!(Rc::ptr_eq(&objects[0].name, &source[0].name));
+            assert_eq!(objects[0].transform.position.x0(), step as f32);
+        }
+
+        copy_objects(&mut objects, &source[..2]);
+        assert_eq!(objects.len(), 2);
+        assert_eq!(objects.as_ptr(), buffer);
+    }
+
+    #[test]
+    fn clones_share_the_name() {
+        let object = RenderObject {
+            name: "car:chassis".into(),
+            ..Default::default()
+        };
+        let copy = object.clone();
+        assert!(Rc::ptr_eq(&object.name, &copy.name));
+        assert_eq!(Rc::strong_count(&object.name), 2);
+        assert_eq!(copy.name(), "car:chassis");
+    }
+
+    #[test]
+    fn look_at_turns_forward_axis() {
+        let mut transform = Transform::default();
+        transform.look_at(V3::new([5.0, 0.0, 0.0]), V3::X1);
+        let model: M4x4 = transform.into();
+        assert_eq!(model * V4::new([0.0, 0.0, 1.0, 0.0]), V4::X0);
+        assert_eq!(model * V4::new([0.0, 1.0, 0.0, 0.0]), V4::X1);
+
+        let before = transform.rotation;
+        transform.look_at(V3::new([0.0, 3.0, 0.0]), V3::X1);
+        assert_eq!(transform.rotation, before);
+    }
+
+    #[test]
+    fn scaled_object_bounds() {
+        let object = RenderObject {
+            transform: Transform {
+                position: V4::new([1.0, 2.0, 3.0, 1.0]),
+                size: V4::new([2.0, 4.0, 2.0, 1.0]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let aabb = object.aabb(&unit_cube());
+        assert_eq!(aabb.min, V3::new([0.0, 0.0, 2.0]));
+        assert_eq!(aabb.max, V3::new([2.0, 4.0, 4.0]));
+        assert_eq!(
+            object.bounding_sphere(&unit_cube()).center,
+            V3::new([1.0, 2.0, 3.0])
+        );
+    }
+
+    #[test]
+    fn pick_nearest_object() {
+        let mut meshes = GlMeshes::new();
+        let cube = meshes.insert(unit_cube());
+
+        let objects = [
+            cube_at(cube, "far", V4::new([0.0, 0.0, 10.0, 1.0])),
+            cube_at(cube, "near", V4::new([0.0, 0.0, 5.0, 1.0])),
+            cube_at(cube, "aside", V4::new([3.0, 0.0, 2.0, 1.0])),
+        ];
+
+        let hit = pick(&objects, &meshes, V3::ZERO, V3::X2);
+        assert_eq!(hit.map(RenderObject::name), Some("near"));
+
+        let hit = pick(&objects, &meshes, V3::ZERO, V3::X1);
+        assert!(hit.is_none());
+    }
+
+    #[test]
+    fn dropping_the_context_deletes_its_meshes() {
+        use crate::sys::opengl::tests::{deleted_buffers, deleted_vertex_arrays};
+
+        let mut context = RenderContext::new(Rc::new(mock_gl())).unwrap();
+        context
+            .create_line_mesh(&[V3::ZERO, V3::X0], V3::ONE)
+            .unwrap();
+
+        let meshes = context.meshes.iter().count();
+        let buffers = meshes + context.meshes.iter().filter(|m| m.vbo_indices != 0).count();
+        assert_eq!(meshes, 3);
+
+        // The counters are per thread and never reset, so compare deltas
+        let (buffers_before, arrays_before) = (deleted_buffers(), deleted_vertex_arrays());
+        drop(context);
+        assert_eq!(deleted_buffers() - buffers_before, buffers);
+        assert_eq!(deleted_vertex_arrays() - arrays_before, meshes);
+    }
+}
@@ -5,9 +5,52 @@ use crate::util::utf8::next_code_point;
 use crate::v2d::v2::V2;
 
 // ----------------------------------------------------------------------------
-pub fn create_text_mesh(font: &Font, text: &str) -> Result<Vec<Vertex>> {
+// Point of the text that is placed at the layout origin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Anchor {
+    #[default]
+    Baseline,
+    TopLeft,
+    Center,
+}
+
+// ----------------------------------------------------------------------------
+// Placement of a text mesh in font units, i.e. em, before `scale`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextLayout {
+    pub anchor: Anchor,
+    pub origin: V2,
+    pub scale: f32,
+}
+
+// ----------------------------------------------------------------------------
+impl Default for TextLayout {
+    fn default() -> Self {
+        Self {
+            anchor: Anchor::default(),
+            origin: V2::zero(),
+            scale: 1.0,
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Advance width of `text`; glyphs missing from the font are skipped.
+pub fn measure_text(font: &Font, text: &str) -> f32 {
+    let mut iter = text.as_bytes().iter();
+    let mut width = 0.0;
+    while let Some(ch) = next_code_point(&mut iter) {
+        if let Some(glyph) = font.glyphs.get(&ch) {
+            width += glyph.advance;
+        }
+    }
+    width
+}
+
+// ----------------------------------------------------------------------------
+pub fn create_text_mesh(font: &Font, text: &str, layout: &TextLayout) -> Result<Vec<Vertex>> {
     let mut iter = text.as_bytes().iter();
-    let mut pos = V2::new([0.0, 0.0]);
+    let mut pos = anchor_offset(font, text, layout.anchor);
     let mut verts = Vec::new();
     while let Some(ch) = next_code_point(&mut iter) {
         if let Some(glyph) = font.glyphs.get(&ch) {
@@ -16,9 +59,27 @@ pub fn create_text_mesh(font: &Font, text: &str) -> Result<Vec<Vertex>> {
         }
     }
 
+    for vert in &mut verts {
+        vert.pos = layout.origin + layout.scale * vert.pos;
+    }
+
     Ok(verts)
 }
 
+// ------------------------------------------------------------------------
+// Start of the baseline relative to the anchor point.
+fn anchor_offset(font: &Font, text: &str, anchor: Anchor) -> V2 {
+    let meta = &font.meta;
+    match anchor {
+        Anchor::Baseline => V2::zero(),
+        Anchor::TopLeft => V2::new([0.0, -meta.ascender]),
+        Anchor::Center => V2::new([
+            -0.5 * measure_text(font, text),
+            -0.5 * (meta.ascender + meta.descender),
+        ]),
+    }
+}
+
 // ------------------------------------------------------------------------
 fn add_glyph(glyph: &FontGlyph, pos: &V2, verts: &mut Vec<Vertex>) {
     let uv_u = glyph.uv[0];
@@ -45,3 +106,64 @@ fn add_glyph(glyph: &FontGlyph, pos: &V2, verts: &mut Vec<Vertex>) {
         xy_size.x1(),
     );
 }
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_float_eq;
+    use crate::core::gl_font::FontMeta;
+
+    fn test_font() -> Font {
+        let glyph = FontGlyph {
+            uv: [0.0, 0.0, 0.5, 0.5],
+            xy: [0.1, -0.2, 0.5, 0.7],
+            advance: 0.6,
+        };
+        Font {
+            width: 64,
+            height: 64,
+            texture: 0,
+            meta: FontMeta {
+                line_height: 1.2,
+                ascender: 0.8,
+                descender: -0.2,
+                _underline_y: -0.1,
+                _underline_thickness: 0.05,
+            },
+            glyphs: [('a' as u32, glyph.clone()), ('b' as u32, glyph)].into(),
+        }
+    }
+
+    #[test]
+    fn center_anchor_shifts_by_half_the_width() {
+        let font = test_font();
+        assert_float_eq!(measure_text(&font, "abba"), 2.4);
+
+        let baseline = create_text_mesh(&font, "abba", &TextLayout::default()).unwrap();
+        let layout = TextLayout {
+            anchor: Anchor::Center,
+            ..Default::default()
+        };
+        let centered = create_text_mesh(&font, "abba", &layout).unwrap();
+
+        assert_eq!(baseline.len(), 24);
+        assert_eq!(centered.len(), baseline.len());
+        for (c, b) in centered.iter().zip(&baseline) {
+            assert_eq!(c.pos, b.pos + V2::new([-1.2, -0.3]));
+        }
+    }
+
+    #[test]
+    fn layout_scales_around_origin() {
+        let font = test_font();
+        let layout = TextLayout {
+            anchor: Anchor::TopLeft,
+            origin: V2::new([10.0, 5.0]),
+            scale: 2.0,
+        };
+        let verts = create_text_mesh(&font, "a", &layout).unwrap();
+        // bottom left corner of the glyph quad
+        assert_eq!(verts[0].pos, V2::new([10.2, 5.0 + 2.0 * (-0.2 - 0.8)]));
+    }
+}
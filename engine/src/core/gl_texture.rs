@@ -1,15 +1,19 @@
-use crate::core::gl_graphics;
+use crate::core::gl_graphics::{self, TextureFormat, TextureSampler};
 use crate::error::{Error, Result};
-use crate::gfx::color_conversion::{ImageGeometry, ycbcr420_to_rgb24};
+use crate::gfx::color_conversion::{
+    ChromaUpsampling, Image, ImageGeometry, YuvMatrix, YuvRange, ycbcr420_to_rgb24,
+};
 use crate::gfx::color_format::ColorFormat;
-use crate::sys::opengl::{self as gl, GLint, GLuint};
+use crate::sys::opengl::{self as gl, GLuint};
 use std::path::Path;
 
+// GL_UNPACK_ALIGNMENT, GL expects texture rows to start at multiples of it
+const UNPACK_ALIGNMENT: usize = 4;
+
 // ------------------------------------------------------------------------
 pub fn load_webp(
     gl: &gl::OpenGlFunctions,
-    filter: GLint,
-    wrap: GLint,
+    sampler: &TextureSampler,
     path: &Path,
 ) -> Result<(usize, usize, GLuint)> {
     let contents = std::fs::read(path)?;
@@ -17,24 +21,52 @@ pub fn load_webp(
 
     let tx_width = frame.mb_width * 16;
     let tx_height = frame.mb_height * 16;
-    let geo = ImageGeometry {
-        cx: tx_width,
-        cy: tx_height,
-        cf: ColorFormat::YCbCr420,
-    };
-    let rgb = ycbcr420_to_rgb24(&frame.ybuf, &frame.ubuf, &frame.vbuf, &geo);
+    let rgb = webp_rgb24(tx_width, tx_height, &frame.ybuf, &frame.ubuf, &frame.vbuf);
 
-    let texture = gl_graphics::create_texture(gl, tx_width, tx_height, 1, &rgb.data, filter, wrap)?;
+    let (texture, _) = gl_graphics::create_texture(
+        gl,
+        tx_width,
+        tx_height,
+        TextureFormat::Rgb8,
+        &rgb.data,
+        sampler,
+    )?;
 
     log::info!("Loaded {path:?} as texture {texture} ({tx_width}x{tx_height})");
     Ok((tx_width, tx_height, texture))
 }
 
+// ------------------------------------------------------------------------
+// Decoded VP8 planes of a cx x cy frame as RGB24 rows padded for upload.
+fn webp_rgb24(cx: usize, cy: usize, ybuf: &[u8], ubuf: &[u8], vbuf: &[u8]) -> Image {
+    let geo = ImageGeometry {
+        cx,
+        cy,
+        cf: ColorFormat::YCbCr420,
+    };
+    // VP8 stores BT.601 in limited range
+    let rgb = ycbcr420_to_rgb24(
+        ybuf,
+        ubuf,
+        vbuf,
+        &geo,
+        YuvMatrix::Bt601,
+        YuvRange::Limited,
+        ChromaUpsampling::Bilinear,
+    );
+
+    // The converted rows are 24 bit, not the 12 bit of the planes
+    let rgb_geo = ImageGeometry {
+        cf: ColorFormat::RGB0888,
+        ..geo
+    };
+    rgb.to_aligned(&rgb_geo, UNPACK_ALIGNMENT)
+}
+
 // ------------------------------------------------------------------------
 pub fn load_png(
     gl: &gl::OpenGlFunctions,
-    filter: GLint,
-    wrap: GLint,
+    sampler: &TextureSampler,
     path: &Path,
 ) -> Result<(usize, usize, GLuint)> {
     let contents = std::fs::read(path)?;
@@ -44,19 +76,72 @@ pub fn load_png(
         return Err(Error::InvalidColorFormat);
     }
 
-    let tx_width = (png.width + 3) & !3;
+    let tx_width = png.width;
     let tx_height = png.height;
+    let geo = ImageGeometry {
+        cx: tx_width,
+        cy: tx_height,
+        cf: ColorFormat::RGB8888,
+    };
 
-    let mut aligned = vec![0u8; tx_width * tx_height * 4];
-    for y in 0..png.height {
-        let src_offset = y * (png.width * 4 + 1) + 1;
-        let dst_offset = y * tx_width * 4;
-        aligned[dst_offset..(dst_offset + png.width * 4)]
-            .copy_from_slice(&data[src_offset..(src_offset + png.width * 4)]);
+    // Each decoded row starts with its filter type byte; dropping the first
+    // one leaves the others as row padding.
+    let stride = tx_width * 4 + 1;
+    if data.is_empty() || data.len() < stride * tx_height {
+        return Err(Error::InvalidTextureSize);
     }
+    let mut data = data;
+    data.remove(0);
+    let image = Image {
+        data,
+        stride,
+        palette: Vec::new(),
+    };
+    let aligned = image.to_aligned(&geo, UNPACK_ALIGNMENT);
 
-    let texture = gl_graphics::create_texture(gl, tx_width, tx_height, 0, &aligned, filter, wrap)?;
+    let (texture, _) = gl_graphics::create_texture(
+        gl,
+        tx_width,
+        tx_height,
+        TextureFormat::Rgba8,
+        &aligned.data,
+        sampler,
+    )?;
 
     log::info!("Loaded {path:?} as texture {texture} ({tx_width}x{tx_height})");
     Ok((tx_width, tx_height, texture))
 }
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sys::opengl::tests::mock_gl;
+
+    #[test]
+    fn webp_rows_are_padded_rgb24() {
+        // 6 pixels are 18 bytes of RGB, padded to 20
+        let (cx, cy) = (6, 4);
+        let ybuf = vec![128; cx * cy];
+        let uvbuf = vec![128; cx / 2 * cy / 2];
+
+        let rgb = webp_rgb24(cx, cy, &ybuf, &uvbuf, &uvbuf);
+        assert_eq!(rgb.stride, 20);
+        assert_eq!(rgb.data.len(), 20 * cy);
+        for row in rgb.data.chunks_exact(rgb.stride) {
+            assert!(row[..18].iter().all(|&c| c == 130), "{row:?}");
+            assert_eq!(row[18..], [0, 0]);
+        }
+
+        let sampler = TextureSampler::new(gl::LINEAR, gl::CLAMP_TO_EDGE);
+        let upload = gl_graphics::create_texture(
+            &mock_gl(),
+            cx,
+            cy,
+            TextureFormat::Rgb8,
+            &rgb.data,
+            &sampler,
+        );
+        assert!(upload.is_ok());
+    }
+}
@@ -16,6 +16,28 @@ pub enum Key {
     k_U, k_V, k_W, k_X, k_Y, k_Z,
 }
 
+// ----------------------------------------------------------------------------
+impl Key {
+    // Every key in declaration order, i.e. `ALL[key as usize] == key`.
+    #[rustfmt::skip]
+    pub const ALL: [Key; 71] = {
+        use Key::*;
+        [
+            k_Escape,
+            k_F1, k_F2, k_F3, k_F4, k_F5, k_F6, k_F7, k_F8, k_F9, k_F10, k_F11, k_F12,
+            k_Return, k_Space, k_Backspace, k_Tab,
+            k_Insert, k_Delete, k_Home, k_End, k_PageUp, k_PageDown,
+            k_Up, k_Down, k_Left, k_Right,
+            k_LeftShift, k_LeftCtrl, k_LeftAlt, k_LeftSuper,
+            k_RightShift, k_RightCtrl, k_RightAlt, k_RightSuper,
+            k_0, k_1, k_2, k_3, k_4, k_5, k_6, k_7, k_8, k_9,
+            k_A, k_B, k_C, k_D, k_E, k_F, k_G, k_H, k_I, k_J,
+            k_K, k_L, k_M, k_N, k_O, k_P, k_Q, k_R, k_S, k_T,
+            k_U, k_V, k_W, k_X, k_Y, k_Z,
+        ]
+    };
+}
+
 // ----------------------------------------------------------------------------
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Event {
@@ -25,11 +47,74 @@ pub enum Event {
     Wheel { delta: i32 },
     KeyDown { key: Key },
     KeyUp { key: Key },
+    // New client area size of the window in pixels.
+    Resize { cx: i32, cy: i32 },
 }
 
 // ----------------------------------------------------------------------------
 pub type Events = Vec<Event>;
 
+// ----------------------------------------------------------------------------
+// Payloads of one kind of event, in the order they arrived. Consumers that
+// depend on the order between kinds, like a drag between button down and up,
+// still have to match on the events themselves.
+pub trait EventsExt {
+    fn mouse_moves(&self) -> impl Iterator<Item = (i32, i32)>;
+    fn key_downs(&self) -> impl Iterator<Item = Key>;
+    fn key_ups(&self) -> impl Iterator<Item = Key>;
+    // Mouse button and whether it was pressed or released.
+    fn buttons(&self) -> impl Iterator<Item = (u32, bool)>;
+    fn wheel_deltas(&self) -> impl Iterator<Item = i32>;
+    // Only the final size matters when the window was resized several times.
+    fn last_resize(&self) -> Option<(i32, i32)>;
+}
+
+// ----------------------------------------------------------------------------
+impl EventsExt for [Event] {
+    fn mouse_moves(&self) -> impl Iterator<Item = (i32, i32)> {
+        self.iter().filter_map(|event| match event {
+            Event::MouseMove { x, y } => Some((*x, *y)),
+            _ => None,
+        })
+    }
+
+    fn key_downs(&self) -> impl Iterator<Item = Key> {
+        self.iter().filter_map(|event| match event {
+            Event::KeyDown { key } => Some(*key),
+            _ => None,
+        })
+    }
+
+    fn key_ups(&self) -> impl Iterator<Item = Key> {
+        self.iter().filter_map(|event| match event {
+            Event::KeyUp { key } => Some(*key),
+            _ => None,
+        })
+    }
+
+    fn buttons(&self) -> impl Iterator<Item = (u32, bool)> {
+        self.iter().filter_map(|event| match event {
+            Event::ButtonDown { button } => Some((*button, true)),
+            Event::ButtonUp { button } => Some((*button, false)),
+            _ => None,
+        })
+    }
+
+    fn wheel_deltas(&self) -> impl Iterator<Item = i32> {
+        self.iter().filter_map(|event| match event {
+            Event::Wheel { delta } => Some(*delta),
+            _ => None,
+        })
+    }
+
+    fn last_resize(&self) -> Option<(i32, i32)> {
+        self.iter().rev().find_map(|event| match event {
+            Event::Resize { cx, cy } => Some((*cx, *cy)),
+            _ => None,
+        })
+    }
+}
+
 // ----------------------------------------------------------------------------
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct State {
@@ -42,6 +127,19 @@ impl State {
         let key = key as usize;
         self.keys.get(key).is_some_and(|&s| s != 0)
     }
+
+    // Key events that lead from `prev` to this state: releases first, then
+    // presses, each in key order.
+    pub fn diff(&self, prev: &State) -> Events {
+        let changed = |pressed: bool| {
+            Key::ALL.into_iter().filter(move |&key| {
+                self.is_pressed(key) == pressed && prev.is_pressed(key) != pressed
+            })
+        };
+        let ups = changed(false).map(|key| Event::KeyUp { key });
+        let downs = changed(true).map(|key| Event::KeyDown { key });
+        ups.chain(downs).collect()
+    }
 }
 
 // ----------------------------------------------------------------------------
@@ -81,7 +179,10 @@ impl Input {
         std::mem::take(&mut self.events)
     }
 
-    pub fn reset_state(&mut self) {
+    // Releases all held keys, e.g. when the window loses focus and the key
+    // up notifications go elsewhere. Pending events are kept, so presses and
+    // releases that arrived before still reach the game in order.
+    pub fn clear_state(&mut self) {
         self.state.keys.fill(0);
     }
 
@@ -95,4 +196,118 @@ impl Input {
     pub fn take_state(&self) -> State {
         self.state.clone()
     }
+
+    // Records a key press as event and as held state, for backends that only
+    // get one notification per transition.
+    pub fn key_down(&mut self, key: Key) {
+        self.add_event(Event::KeyDown { key });
+        self.set_state(key, 0x80);
+    }
+
+    pub fn key_up(&mut self, key: Key) {
+        self.add_event(Event::KeyUp { key });
+        self.set_state(key, 0x00);
+    }
+}
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_keys_are_listed_in_order() {
+        for (i, key) in Key::ALL.into_iter().enumerate() {
+            assert_eq!(key as usize, i, "{key:?}");
+        }
+        assert_eq!(Key::ALL.last(), Some(&Key::k_Z));
+    }
+
+    #[test]
+    fn state_diff_synthesizes_key_events() {
+        let mut input = Input::new();
+        input.key_down(Key::k_W);
+        input.key_down(Key::k_A);
+        let prev = input.take_state();
+
+        input.key_up(Key::k_W);
+        input.key_down(Key::k_Space);
+        input.key_down(Key::k_D);
+        let state = input.take_state();
+
+        assert_eq!(
+            state.diff(&prev),
+            vec![
+                Event::KeyUp { key: Key::k_W },
+                Event::KeyDown { key: Key::k_Space },
+                Event::KeyDown { key: Key::k_D },
+            ]
+        );
+        assert_eq!(prev.diff(&state).len(), 3);
+        assert!(state.diff(&state).is_empty());
+    }
+
+    #[test]
+    fn helpers_pick_matching_events() {
+        let events = vec![
+            Event::MouseMove { x: 1, y: 2 },
+            Event::KeyDown { key: Key::k_W },
+            Event::ButtonDown { button: 1 },
+            Event::Resize { cx: 640, cy: 480 },
+            Event::Wheel { delta: 120 },
+            Event::MouseMove { x: -3, y: 4 },
+            Event::KeyUp { key: Key::k_W },
+            Event::ButtonUp { button: 1 },
+            Event::KeyDown { key: Key::k_S },
+            Event::Resize { cx: 800, cy: 600 },
+        ];
+
+        let moves: Vec<_> = events.mouse_moves().collect();
+        assert_eq!(moves, [(1, 2), (-3, 4)]);
+        let downs: Vec<_> = events.key_downs().collect();
+        assert_eq!(downs, [Key::k_W, Key::k_S]);
+        let ups: Vec<_> = events.key_ups().collect();
+        assert_eq!(ups, [Key::k_W]);
+        let buttons: Vec<_> = events.buttons().collect();
+        assert_eq!(buttons, [(1, true), (1, false)]);
+        assert_eq!(events.wheel_deltas().sum::<i32>(), 120);
+        assert_eq!(events.last_resize(), Some((800, 600)));
+
+        let none: Events = Vec::new();
+        assert_eq!(none.mouse_moves().count(), 0);
+        assert_eq!(none.last_resize(), None);
+    }
+
+    #[test]
+    fn press_release_toggles_state() {
+        let mut input = Input::new();
+        assert!(!input.take_state().is_pressed(Key::k_W));
+
+        input.key_down(Key::k_W);
+        assert!(input.take_state().is_pressed(Key::k_W));
+        assert!(!input.take_state().is_pressed(Key::k_S));
+
+        // state is held across frames until the key is released
+        assert_eq!(input.take_events(), vec![Event::KeyDown { key: Key::k_W }]);
+        assert!(input.take_state().is_pressed(Key::k_W));
+
+        input.key_up(Key::k_W);
+        assert!(!input.take_state().is_pressed(Key::k_W));
+        assert_eq!(input.take_events(), vec![Event::KeyUp { key: Key::k_W }]);
+    }
+
+    #[test]
+    fn clear_state_releases_keys_but_keeps_events() {
+        let mut input = Input::new();
+        input.key_down(Key::k_W);
+        input.key_down(Key::k_A);
+        input.add_event(Event::Wheel { delta: 120 });
+
+        input.clear_state();
+        let state = input.take_state();
+        assert!(!state.is_pressed(Key::k_W));
+        assert!(!state.is_pressed(Key::k_A));
+        assert_eq!(state, State::default());
+        assert_eq!(input.take_events().len(), 3);
+    }
 }
@@ -0,0 +1,103 @@
+use crate::v2d::v3::V3;
+
+// ----------------------------------------------------------------------------
+// A point light shines from `position` in all directions, a directional light
+// such as the sun shines along `direction` everywhere in the scene.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LightKind {
+    #[default]
+    Point,
+    Directional,
+}
+
+// ----------------------------------------------------------------------------
+// Light of the scene, used by the colored pipeline for diffuse and specular
+// shading. Only the field matching `kind` of position and direction is used.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Light {
+    pub kind: LightKind,
+    pub position: V3,
+    pub direction: V3,
+    pub color: V3,
+    pub intensity: f32,
+}
+
+// ----------------------------------------------------------------------------
+impl Default for Light {
+    fn default() -> Self {
+        Self {
+            kind: LightKind::Point,
+            position: V3::new([2.0, 5.0, 2.0]),
+            direction: -V3::X1,
+            color: V3::new([1.0, 0.5, 1.0]),
+            intensity: 1.0,
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+impl Light {
+    pub fn point(position: V3, color: V3, intensity: f32) -> Self {
+        Self {
+            kind: LightKind::Point,
+            position,
+            color,
+            intensity,
+            ..Default::default()
+        }
+    }
+
+    pub fn directional(direction: V3, color: V3, intensity: f32) -> Self {
+        Self {
+            kind: LightKind::Directional,
+            direction: direction.norm(),
+            color,
+            intensity,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_directional(&self) -> bool {
+        self.kind == LightKind::Directional
+    }
+
+    // Unit vector from a surface point towards the light, as computed by the
+    // colored fragment shader.
+    pub fn light_dir(&self, surface: V3) -> V3 {
+        match self.kind {
+            LightKind::Point => (self.position - surface).norm(),
+            LightKind::Directional => -self.direction.norm(),
+        }
+    }
+
+    // Color scaled by the intensity, as uploaded to the shaders.
+    pub fn radiance(&self) -> V3 {
+        self.intensity * self.color
+    }
+}
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn directional_light_dir_is_constant() {
+        let sun = Light::directional(V3::new([1.0, -2.0, 0.5]), V3::ONE, 1.0);
+        let expected = -V3::new([1.0, -2.0, 0.5]).norm();
+        for p in [
+            V3::ZERO,
+            V3::new([100.0, 0.0, -50.0]),
+            V3::new([0.0, 30.0, 0.0]),
+        ] {
+            assert_eq!(sun.light_dir(p), expected);
+        }
+    }
+
+    #[test]
+    fn point_light_dir_depends_on_position() {
+        let lamp = Light::point(V3::new([0.0, 5.0, 0.0]), V3::ONE, 1.0);
+        assert_eq!(lamp.light_dir(V3::ZERO), V3::X1);
+        assert_eq!(lamp.light_dir(V3::new([0.0, 5.0, 2.0])), -V3::X2);
+    }
+}
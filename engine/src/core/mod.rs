@@ -1,11 +1,14 @@
 use crate::error::Result;
 
+pub mod bounds;
 pub mod camera;
 pub mod car;
 pub mod clock;
 pub mod component;
+pub mod coords;
 pub mod game_input;
 pub mod game_loop;
+pub mod gizmo;
 pub mod gl_font;
 pub mod gl_graphics;
 pub mod gl_pipeline;
@@ -15,7 +18,9 @@ pub mod gl_renderer;
 pub mod gl_text;
 pub mod gl_texture;
 pub mod input;
+pub mod light;
 pub mod player;
+pub mod slider;
 pub mod sphere;
 pub mod terrain;
 pub mod world;
@@ -31,7 +36,7 @@ pub trait IClock {
 
 // ----------------------------------------------------------------------------
 pub trait IGame {
-    fn input(&mut self, events: input::Events, state: input::State) -> Result<()>;
+    fn input(&mut self, events: &input::Events, state: &input::State) -> Result<()>;
     fn update(&mut self, dt: &std::time::Duration) -> Result<()>;
     fn render(&mut self) -> Result<()>;
 }
@@ -41,10 +46,18 @@ pub trait IRenderer {
     fn render(
         &self,
         camera: &camera::Camera,
-        objects: Vec<gl_renderer::RenderObject>,
+        objects: &[gl_renderer::RenderObject],
         context: &gl_renderer::RenderContext,
     ) -> Result<()>;
     fn resize(&self, cx: i32, cy: i32);
+
+    // Applies the latest window size from the frame's events.
+    fn input(&self, events: &input::Events) {
+        use input::EventsExt;
+        if let Some((cx, cy)) = events.last_resize() {
+            self.resize(cx, cy);
+        }
+    }
 }
 
 // ----------------------------------------------------------------------------
@@ -96,11 +109,13 @@ pub mod tests {
         t_update: std::time::Duration,
         t_render: std::time::Duration,
         update_count: usize,
+        input_events: usize,
         loops: Vec<usize>,
     }
 
     impl IGame for MockGame<'_> {
-        fn input(&mut self, _events: input::Events, _state: input::State) -> Result<()> {
+        fn input(&mut self, events: &input::Events, _state: &input::State) -> Result<()> {
+            self.input_events += events.len();
             Ok(())
         }
 
@@ -129,6 +144,7 @@ pub mod tests {
                 t_update,
                 t_render,
                 update_count: 0,
+                input_events: 0,
                 loops: Vec::new(),
             }
         }
@@ -136,6 +152,10 @@ pub mod tests {
         pub fn loops(&self) -> &Vec<usize> {
             &self.loops
         }
+
+        pub fn input_events(&self) -> usize {
+            self.input_events
+        }
     }
 
     #[test]
@@ -147,7 +167,10 @@ pub mod tests {
             std::time::Duration::from_millis(10),
             std::time::Duration::from_millis(20),
         );
-        assert_eq!(game.input(input.take_events(), input.take_state()), Ok(()));
+        assert_eq!(
+            game.input(&input.take_events(), &input.take_state()),
+            Ok(())
+        );
         assert_eq!(game.update(&clock.now()), Ok(()));
         assert_eq!(game.render(), Ok(()));
         assert_eq!(game.loops().len(), 1);
@@ -1,11 +1,16 @@
 use crate::core::component::{Component, Context};
+use crate::core::coords::{self, FORWARD};
 use crate::core::game_input::GameKey;
+use crate::core::gl_pipeline::GlMeshId;
+use crate::core::gl_pipeline_colored::{Vertex, arrow};
 use crate::core::gl_renderer::{
     DefaultMaterials, DefaultMeshes, RenderContext, RenderObject, Rotation, Transform,
 };
 use crate::error::Result;
+use crate::util::curves::{arch, bezier_quad, smoothstep};
 use crate::v2d::q::Q;
 use crate::v2d::{affine4x4, r2::R2, v2::V2, v3::V3, v4::V4};
+use serde::Serialize;
 
 // ----------------------------------------------------------------------------
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
@@ -29,7 +34,7 @@ pub enum AnimationState {
 }
 
 // ----------------------------------------------------------------------------
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct Skeleton {
     pub body_height: f32,
     pub head_height: f32,
@@ -37,10 +42,11 @@ pub struct Skeleton {
     pub feet_distance: f32,
     pub step_length: f32,
     pub step_height: f32,
+    pub crouch_depth: f32,
 }
 
 // ----------------------------------------------------------------------------
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct Pose {
     pub body: V3,
     pub head: V3,
@@ -149,31 +155,11 @@ pub struct Player {
     pub step_speed: f32,
     pub phase_progress: f32,
     pub skeleton: Skeleton,
+    pub crouching: bool,
 }
 
 // ----------------------------------------------------------------------------
-fn bezier_quad(p0: V3, p1: V3, p2: V3, t: f32) -> V3 {
-    let u = 1.0 - t;
-    u * u * p0 + 2.0 * u * t * p1 + t * t * p2
-}
-
-// ----------------------------------------------------------------------------
-pub fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
-    if edge0 == edge1 {
-        return 0.0; // Avoid division by zero
-    }
-    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
-    t * t * (3.0 - 2.0 * t)
-}
-
-// ----------------------------------------------------------------------------
-fn body_bob(t: f32) -> f32 {
-    // Smooth compression then rise, peaks at mid-step
-    let x = 2.0 * t - 1.0;
-    1.0 - x * x
-}
-
-// ----------------------------------------------------------------------------
+// Foot pitch over a step, from 0 to 1 and back.
 fn toe_roll(t: f32) -> f32 {
     if t < 0.5 {
         // heel down quickly
@@ -184,25 +170,26 @@ fn toe_roll(t: f32) -> f32 {
     }
 }
 
+// ----------------------------------------------------------------------------
+// Meshes of the left and right debug arrow; `create` uploads one mesh.
+fn create_arrow_meshes(
+    mut create: impl FnMut(&[Vertex]) -> Result<GlMeshId>,
+) -> Result<[GlMeshId; 2]> {
+    let pos = V3::new([1.0, 0.0, 0.0]);
+    let arrow_verts = arrow(pos, pos + 1.5 * FORWARD)?;
+    Ok([create(&arrow_verts)?, create(&arrow_verts)?])
+}
+
 // ----------------------------------------------------------------------------
 impl Player {
     pub fn new(context: &mut RenderContext) -> Result<Self> {
-        use crate::core::gl_pipeline_colored::arrow;
-        let pos = V3::new([1.0, 0.0, 0.0]);
-        let forward_3d = V3::new([0.0, 0.0, 1.0]);
-        let arrow_verts = arrow(pos, pos + 1.5 * forward_3d)?;
-
-        let left_arrow_mesh_id = context
-            .create_colored_mesh(&arrow_verts, &[], true)
-            .unwrap();
-        let right_arrow_mesh_id = context
-            .create_colored_mesh(&arrow_verts, &[], true)
-            .unwrap();
+        let [left_arrow_mesh_id, right_arrow_mesh_id] =
+            create_arrow_meshes(|verts| context.create_colored_mesh(verts, &[], true))?;
         Ok(Self {
             mode: PlayerMode::InCar,
             objects: [
                 RenderObject {
-                    name: String::from("player:body"),
+                    name: "player:body".into(),
                     transform: Transform {
                         size: V4::new([0.8, 0.8, 0.5, 1.0]),
                         ..Default::default()
@@ -213,7 +200,7 @@ impl Player {
                     ..Default::default()
                 },
                 RenderObject {
-                    name: String::from("player:head"),
+                    name: "player:head".into(),
                     transform: Transform {
                         size: V4::new([0.6, 0.6, 0.6, 1.0]),
                         ..Default::default()
@@ -224,7 +211,7 @@ impl Player {
                     ..Default::default()
                 },
                 RenderObject {
-                    name: String::from("player:foot_left"),
+                    name: "player:foot_left".into(),
                     transform: Transform {
                         size: V4::new([0.3, 0.2, 0.4, 1.0]),
                         ..Default::default()
@@ -235,7 +222,7 @@ impl Player {
                     ..Default::default()
                 },
                 RenderObject {
-                    name: String::from("player:foot_right"),
+                    name: "player:foot_right".into(),
                     transform: Transform {
                         size: V4::new([0.3, 0.2, 0.4, 1.0]),
                         ..Default::default()
@@ -248,7 +235,7 @@ impl Player {
             ],
             debug_arrows: [
                 RenderObject {
-                    name: String::from("player:debug_arrow_left"),
+                    name: "player:debug_arrow_left".into(),
                     transform: Transform {
                         position: V4::new([0.0, 0.0, 0.0, 1.0]),
                         size: V4::new([1.0, 1.0, 1.0, 1.0]),
@@ -260,7 +247,7 @@ impl Player {
                     ..Default::default()
                 },
                 RenderObject {
-                    name: String::from("player:debug_arrow_right"),
+                    name: "player:debug_arrow_right".into(),
                     transform: Transform {
                         position: V4::new([0.0, 0.0, 0.0, 1.0]),
                         size: V4::new([1.0, 1.0, 1.0, 1.0]),
@@ -288,7 +275,9 @@ impl Player {
                 feet_distance: 0.4,
                 step_length: 0.8,
                 step_height: 0.3,
+                crouch_depth: 0.3,
             },
+            crouching: false,
         })
     }
 
@@ -306,6 +295,7 @@ impl Player {
             feet_distance,
             step_length,
             step_height,
+            ..
         } = self.skeleton;
 
         self.phase_progress = 0.0;
@@ -390,9 +380,13 @@ impl Player {
         }
     }
 
+    // Pose of the last update, before crouching is applied.
+    pub fn current_pose(&self) -> &Pose {
+        &self.current_pose
+    }
+
     pub fn position(&self) -> V4 {
-        let pos = self.current_pose.body;
-        V4::new([pos.x0(), pos.x1(), pos.x2(), 1.0])
+        coords::point(self.current_pose.body)
     }
 
     pub fn transform(&self) -> (V4, V4) {
@@ -431,6 +425,7 @@ impl Component for Player {
         let mut move_forward = false;
         if self.mode == PlayerMode::OnFoot {
             move_forward = ctx.state.is_pressed(GameKey::MoveForward);
+            self.crouching = ctx.state.is_pressed(GameKey::Crouch);
             if ctx.state.is_pressed(GameKey::StrafeLeft) {
                 self.rotation -= TURN_SPEED * dt;
             }
@@ -481,11 +476,12 @@ impl Component for Player {
                 if let Some(step) = &self.active_step {
                     let idx = step.foot.index_self();
                     pose.feet[idx] =
-                        bezier_quad(step.foot_start, step.foot_control, step.foot_target, t);
+                        bezier_quad(&step.foot_start, &step.foot_control, &step.foot_target, t);
 
                     feet_rot[idx] = step.toe_roll_max * toe_roll(t);
 
-                    let bob = step.body_bob_height * body_bob(t);
+                    // Smooth compression then rise, peaks at mid-step
+                    let bob = step.body_bob_height * arch(t);
                     pose.body += V3::new([0.0, bob, 0.0]);
                     pose.head += V3::new([0.0, bob * 0.8, 0.0]); // slight damping looks natural                
                 }
@@ -495,17 +491,23 @@ impl Component for Player {
         }
 
         let pos = 0.5 * (self.current_pose.feet[0] + self.current_pose.feet[1]);
-        self.position = V2::new([pos.x0(), pos.x2()]);
+        self.position = coords::ground(pos);
 
+        // Crouching only lowers the rendered body, the step poses stay upright
+        let crouch = if self.crouching {
+            self.skeleton.crouch_depth
+        } else {
+            0.0
+        };
         self.objects[0].transform.position = V4::new([
             self.current_pose.body.x0(),
-            self.current_pose.body.x1(),
+            self.current_pose.body.x1() - crouch,
             self.current_pose.body.x2(),
             1.0,
         ]);
         self.objects[1].transform.position = V4::new([
             self.current_pose.head.x0(),
-            self.current_pose.head.x1(),
+            self.current_pose.head.x1() - crouch,
             self.current_pose.head.x2(),
             1.0,
         ]);
@@ -535,4 +537,100 @@ impl Component for Player {
 
         Ok(())
     }
+
+    fn render_objects(&self) -> impl Iterator<Item = &RenderObject> {
+        self.objects.iter()
+    }
+
+    fn debug_objects(&self) -> impl Iterator<Item = &RenderObject> {
+        self.debug_arrows.iter()
+    }
+}
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::game_input::InputContext;
+    use crate::core::input::{Input, Key};
+    use crate::core::terrain::Terrain;
+
+    // Player on foot without render meshes.
+    fn walker() -> Player {
+        Player {
+            mode: PlayerMode::OnFoot,
+            objects: Default::default(),
+            debug_arrows: Default::default(),
+            rotation: R2::new(0.0),
+            position: V2::default(),
+            state: AnimationState::Idle,
+            active_step: None,
+            current_pose: Pose::default(),
+            start_pose: Pose::default(),
+            target_pose: Pose::default(),
+            step_speed: 4.0,
+            phase_progress: 0.0,
+            skeleton: Skeleton {
+                body_height: 0.8,
+                head_height: 1.8,
+                feet_height: 0.1,
+                feet_distance: 0.4,
+                step_length: 0.8,
+                step_height: 0.3,
+                crouch_depth: 0.3,
+            },
+            crouching: false,
+        }
+    }
+
+    #[test]
+    fn swing_foot_follows_bezier_arc() {
+        let mut input = Input::new();
+        let mut state = InputContext::default();
+        let terrain = Terrain::new_flat(1, 1);
+        let mut player = walker();
+
+        input.key_down(Key::k_W);
+        let mut heights = Vec::new();
+        for _ in 0..15 {
+            state.update_state(input.take_state());
+            let ctx = Context {
+                dt: std::time::Duration::from_millis(16),
+                state: &state,
+                terrain: &terrain,
+            };
+            player.update(&ctx).unwrap();
+
+            let step = player.active_step.clone().unwrap();
+            assert_eq!(step.foot, Foot::Left);
+            let t = player.phase_progress * player.step_speed;
+            let expected = bezier_quad(&step.foot_start, &step.foot_control, &step.foot_target, t);
+            assert_eq!(player.current_pose().feet[0], expected);
+            heights.push(player.current_pose().feet[0].x1());
+        }
+
+        // The foot lifts off, passes the apex and comes down again
+        let apex = heights.iter().cloned().fold(f32::MIN, f32::max);
+        assert!(apex > 0.15, "foot barely lifted: {apex}");
+        assert!(heights[0] < apex && *heights.last().unwrap() < apex);
+
+        let json = serde_json::to_string(player.current_pose()).unwrap();
+        assert!(json.starts_with("{\"body\":["));
+    }
+
+    #[test]
+    fn arrow_mesh_errors_are_returned() {
+        use crate::error::Error;
+
+        let mut created = 0;
+        let meshes = create_arrow_meshes(|_| {
+            created += 1;
+            Ok(GlMeshId::default())
+        });
+        assert!(meshes.is_ok());
+        assert_eq!(created, 2);
+
+        let meshes = create_arrow_meshes(|_| Err(Error::GpuOutOfMemory));
+        assert!(matches!(meshes, Err(Error::GpuOutOfMemory)));
+    }
 }
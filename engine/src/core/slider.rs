@@ -0,0 +1,205 @@
+use crate::core::car::GRAVITY;
+use crate::core::component::{Component, Context};
+use crate::core::coords::RIGHT;
+use crate::core::gl_pipeline_colored::arrow;
+use crate::core::gl_renderer::{
+    DefaultMaterials, DefaultMeshes, RenderContext, RenderObject, Transform,
+};
+use crate::error::{Error, Result};
+use crate::v2d::{q::Q, v3::V3};
+use crate::x2d::constraint::joint::Joint;
+use crate::x2d::{BodyId, JointId, Material, mass::Mass, physics::Physics, rigid_body::RigidBody};
+
+// ----------------------------------------------------------------------------
+const RAIL_SIZE: V3 = V3::new([4.0, 0.1, 0.1]);
+const CARRIAGE_SIZE: V3 = V3::new([0.4, 0.4, 0.4]);
+
+// Pulls the carriage back to the middle of the rail
+const SPRING_STIFFNESS: f32 = 20.0;
+
+// ----------------------------------------------------------------------------
+// A carriage sliding along a rail, the two held together by a slider joint.
+// Gravity pulls the carriage off the rail and a spring pulls it back to the
+// middle, so the joint has to keep it on the rail while it oscillates.
+#[derive(Debug)]
+pub struct SliderDemo {
+    pub objects: [RenderObject; 2],
+    pub debug_arrow: RenderObject,
+    rail: BodyId,
+    carriage: BodyId,
+    joint: JointId,
+}
+
+// ----------------------------------------------------------------------------
+// Rail at `origin`, carriage displaced along the rail by `offset`.
+fn create_bodies(physics: &mut Physics, origin: V3, offset: f32) -> Result<[BodyId; 2]> {
+    let body = |name: &str, density, size, position| -> Result<RigidBody> {
+        let mass = Mass::from_box(density, size)?;
+        Ok(RigidBody::new(
+            name.into(),
+            mass,
+            Material::default(),
+            position,
+            Q::identity(),
+        ))
+    };
+
+    let rail = physics.add_body(body("slider:rail", 50_000.0, RAIL_SIZE, origin)?);
+    let carriage = physics.add_body(body(
+        "slider:carriage",
+        100.0,
+        CARRIAGE_SIZE,
+        origin + offset * RIGHT,
+    )?);
+    Ok([rail, carriage])
+}
+
+// ----------------------------------------------------------------------------
+impl SliderDemo {
+    pub fn new(context: &mut RenderContext, physics: &mut Physics, origin: V3) -> Result<Self> {
+        let [rail, carriage] = create_bodies(physics, origin, 1.0)?;
+        let joint = physics.add_joint(Joint::new_slider(carriage, rail, V3::ZERO, V3::ZERO, RIGHT));
+
+        let cube = context.default_mesh(DefaultMeshes::Cube);
+        let object = |name: &str, material| RenderObject {
+            name: name.into(),
+            transform: Transform::default(),
+            pipe_id: 0,
+            mesh_id: cube,
+            material_id: context.default_material(material),
+            ..Default::default()
+        };
+        let objects = [
+            object("slider:rail", DefaultMaterials::White),
+            object("slider:carriage", DefaultMaterials::Cyan),
+        ];
+
+        let arrow_verts = arrow(origin, origin + RIGHT)?;
+        let debug_arrow = RenderObject {
+            name: "slider:debug_arrow".into(),
+            transform: Transform::default(),
+            pipe_id: 0,
+            mesh_id: context.create_colored_mesh(&arrow_verts, &[], true)?,
+            material_id: context.default_material(DefaultMaterials::Magenta),
+            ..Default::default()
+        };
+
+        Ok(Self {
+            objects,
+            debug_arrow,
+            rail,
+            carriage,
+            joint,
+        })
+    }
+
+    // ------------------------------------------------------------------------
+    // Applied before each physics sub-step. The rail is held by its mount,
+    // which carries the carriage's weight and the spring.
+    pub fn apply_forces(&self, physics: &mut Physics) -> Result<()> {
+        let rail = physics.get_body(self.rail).ok_or(Error::InvalidBodyId)?;
+        let carriage = physics
+            .get_body(self.carriage)
+            .ok_or(Error::InvalidBodyId)?;
+
+        let spring = SPRING_STIFFNESS * carriage.mass() * (rail.position() - carriage.position());
+        let force = GRAVITY * carriage.mass() + spring;
+
+        physics
+            .get_body_mut(self.carriage)
+            .ok_or(Error::InvalidBodyId)?
+            .apply_force(force);
+        physics
+            .get_body_mut(self.rail)
+            .ok_or(Error::InvalidBodyId)?
+            .apply_force(-force);
+        Ok(())
+    }
+
+    // ------------------------------------------------------------------------
+    pub fn update_render_objects(&mut self, physics: &Physics) -> Result<()> {
+        let bodies = [self.rail, self.carriage];
+        let sizes = [RAIL_SIZE, CARRIAGE_SIZE];
+        for ((object, id), size) in self.objects.iter_mut().zip(bodies).zip(sizes) {
+            let body = physics.get_body(id).ok_or(Error::InvalidBodyId)?;
+            object.transform = body.transform_with_scale(size);
+        }
+        Ok(())
+    }
+
+    // ------------------------------------------------------------------------
+    // Points from the rail anchor to the carriage anchor, i.e. along the rail
+    // while the joint holds.
+    pub fn update_debug_arrow(&self, context: &mut RenderContext, physics: &Physics) -> Result<()> {
+        let joint = physics.get_joint(self.joint).ok_or(Error::InvalidJointId)?;
+        let slider = joint.as_slider().ok_or(Error::InvalidJointType)?;
+
+        if let Ok(arrow_verts) = arrow(slider.world_anchor_b, slider.world_anchor_a) {
+            context.update_colored_mesh(self.debug_arrow.mesh_id, &arrow_verts, &[])?;
+        }
+        Ok(())
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Everything happens per physics sub-step, see `apply_forces`.
+impl Component for SliderDemo {
+    fn update(&mut self, _ctx: &Context) -> Result<()> {
+        Ok(())
+    }
+
+    fn render_objects(&self) -> impl Iterator<Item = &RenderObject> {
+        self.objects.iter()
+    }
+
+    fn debug_objects(&self) -> impl Iterator<Item = &RenderObject> {
+        std::iter::once(&self.debug_arrow)
+    }
+}
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::coords::{FORWARD, UP};
+    use crate::sys::opengl::tests::mock_gl;
+    use std::rc::Rc;
+
+    #[test]
+    fn carriage_stays_on_the_rail() {
+        let mut context = RenderContext::new(Rc::new(mock_gl())).unwrap();
+        let mut physics = Physics::new();
+        let origin = V3::new([0.0, 2.0, 0.0]);
+        let mut demo = SliderDemo::new(&mut context, &mut physics, origin).unwrap();
+        let (rail, carriage) = (demo.rail, demo.carriage);
+
+        let mut min_offset = f32::INFINITY;
+        for _ in 0..200 {
+            physics
+                .step_substeps(0.01, |physics, _h| demo.apply_forces(physics))
+                .unwrap();
+
+            let rail = physics.get_body(rail).unwrap();
+            let d = physics.get_body(carriage).unwrap().position() - rail.position();
+            let off_axis = d.dot(UP).hypot(d.dot(FORWARD));
+            assert!(off_axis < 0.01, "carriage left the rail by {off_axis}");
+            min_offset = min_offset.min(d.dot(RIGHT));
+        }
+
+        // The spring swings the carriage through the middle of the rail
+        assert!(min_offset < 0.0, "carriage stuck at {min_offset}");
+
+        demo.update_render_objects(&physics).unwrap();
+        let names: Vec<_> = demo
+            .render_objects()
+            .map(|o| o.name().to_string())
+            .collect();
+        assert_eq!(names, ["slider:rail", "slider:carriage"]);
+        assert_eq!(
+            demo.objects[1].transform.position,
+            physics.get_body(carriage).unwrap().transform().position
+        );
+        assert_eq!(demo.debug_objects().count(), 1);
+        demo.update_debug_arrow(&mut context, &physics).unwrap();
+    }
+}
@@ -1,9 +1,109 @@
+use crate::core::car::GRAVITY;
+use crate::core::gl_pipeline::{self, GlMeshId};
+use crate::core::gl_pipeline_colored::{self, Vertex, arrow};
 use crate::core::gl_renderer::{DefaultMaterials, RenderContext, RenderObject, Transform};
-use crate::core::{gl_pipeline, gl_pipeline_colored};
-use crate::error::Result;
+use crate::core::terrain::Terrain;
+use crate::error::{Error, Result};
 use crate::v2d::{q::Q, v3::V3, v4::V4};
 use crate::x2d::Material;
-use crate::x2d::{BodyId, mass::Mass, rigid_body::RigidBody};
+use crate::x2d::{BodyId, mass::Mass, physics::Physics, rigid_body::RigidBody};
+
+// ----------------------------------------------------------------------------
+/// Sweeps a sphere from `p0` to `p1` against the plane through `point` with
+/// unit `normal`. Returns the time of impact in [0, 1], or `None` if the
+/// sphere stays in front of the plane.
+pub fn sweep_sphere_plane(p0: V3, p1: V3, radius: f32, point: V3, normal: V3) -> Option<f32> {
+    let d0 = normal.dot(p0 - point) - radius;
+    let d1 = normal.dot(p1 - point) - radius;
+
+    if d1 >= 0.0 {
+        return None;
+    }
+    if d0 <= 0.0 {
+        return Some(0.0); // already touching at the start of the step
+    }
+    Some(d0 / (d0 - d1))
+}
+
+// ----------------------------------------------------------------------------
+/// Continuous collision of a sphere body against the terrain. The segment
+/// travelled since `prev_position` is tested against the surface below the
+/// current position, so fast bodies are caught at the time of impact instead
+/// of ending up below the ground. A sphere that already touches the surface
+/// at the start keeps its motion along the surface and is only pushed out.
+pub fn collide_sphere_terrain(
+    body: &mut RigidBody,
+    radius: f32,
+    terrain: &Terrain,
+    prev_position: V3,
+) -> bool {
+    let p1 = body.position();
+    let normal = terrain.normal_at(p1.x0(), p1.x2());
+    let point = V3::new([p1.x0(), terrain.height_at(p1.x0(), p1.x2()), p1.x2()]);
+
+    let Some(toi) = sweep_sphere_plane(prev_position, p1, radius, point, normal) else {
+        return false;
+    };
+
+    // Resolve at the time of impact and push out any remaining penetration.
+    let p = if toi > 0.0 {
+        prev_position.lerp(p1, toi)
+    } else {
+        p1
+    };
+    let penetration = radius - normal.dot(p - point);
+    body.set_position(p + normal * penetration.max(0.0));
+
+    let vn = body.linear_velocity().dot(normal);
+    if vn < 0.0 {
+        let impulse = -(1.0 + body.restitution()) * vn * body.mass() * normal;
+        body.apply_impulse(impulse, "sphere_terrain");
+    }
+
+    true
+}
+
+// ----------------------------------------------------------------------------
+const ROLLING_RESISTANCE: f32 = 0.02;
+const ANGULAR_DAMPING: f32 = 0.5; // per second
+const CONTACT_SLOP: f32 = 0.01;
+
+// ----------------------------------------------------------------------------
+/// Slows down a sphere of `radius` touching a surface with unit `normal`.
+/// Rolling resistance opposes the velocity of the contact point, v + ω×r,
+/// with an impulse of at most `ROLLING_RESISTANCE` times the normal load per
+/// second. All spin, including the spin about the normal, decays
+/// exponentially first, so a rolling sphere starts to slip and slows down.
+pub fn apply_rolling_resistance(body: &mut RigidBody, normal: V3, radius: f32, dt: f32) {
+    let damping = (-ANGULAR_DAMPING * dt).exp();
+    body.set_angular_velocity(body.angular_velocity() * damping);
+
+    let r = -normal * radius;
+    let contact = body.position() + r;
+    let v = body.velocity_at(contact);
+    let v_tangent = v - normal * v.dot(normal);
+    let speed = v_tangent.length();
+    if speed > 0.0 {
+        let t = v_tangent / speed;
+        // contact point velocity change per unit impulse along t
+        let k = body.inv_mass() + (body.inv_inertia() * r.cross(t)).cross(r).dot(t);
+        let load = -GRAVITY.dot(normal).min(0.0) * body.mass();
+        let impulse = (ROLLING_RESISTANCE * load * dt).min(speed / k);
+        body.apply_impulse_at(-t * impulse, contact, "rolling_resistance");
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Meshes of the unit sphere and its debug arrow; `create` uploads one mesh.
+fn create_sphere_meshes(
+    mut create: impl FnMut(&[Vertex], &[u32]) -> Result<GlMeshId>,
+) -> Result<[GlMeshId; 2]> {
+    let (verts, indices) = gl_pipeline_colored::icosphere(1.0, 2);
+    let pos = V3::new([1.0, 0.0, 0.0]);
+    let forward_3d = V3::new([0.0, 0.0, 1.0]);
+    let arrow_verts = arrow(pos, pos + 1.5 * forward_3d)?;
+    Ok([create(&verts, &indices)?, create(&arrow_verts, &[])?])
+}
 
 // ----------------------------------------------------------------------------
 /// A physically simulated sphere that bounces and rolls
@@ -30,19 +130,12 @@ impl PhysicsSphere {
     }
 
     pub fn new_sphere(context: &mut RenderContext, body_id: BodyId, radius: f32) -> Result<Self> {
-        let (verts, indices) = gl_pipeline_colored::icosphere(1.0, 2);
-        let mesh_id = context.create_colored_mesh(&verts, &indices, true)?;
-
-        use crate::core::gl_pipeline_colored::arrow;
-        let pos = V3::new([1.0, 0.0, 0.0]);
-        let forward_3d = V3::new([0.0, 0.0, 1.0]);
-        let arrow_verts = arrow(pos, pos + 1.5 * forward_3d)?;
-        let debug_arrow_mesh_id = context
-            .create_colored_mesh(&arrow_verts, &[], true)
-            .unwrap();
+        let [mesh_id, debug_arrow_mesh_id] = create_sphere_meshes(|verts, indices| {
+            context.create_colored_mesh(verts, indices, true)
+        })?;
 
         let object = RenderObject {
-            name: String::from("physics_sphere"),
+            name: "physics_sphere".into(),
             transform: Transform::default(),
             pipe_id: gl_pipeline::GlPipelineType::Colored.into(),
             mesh_id,
@@ -51,7 +144,7 @@ impl PhysicsSphere {
         };
 
         let debug_arrow = RenderObject {
-            name: String::from("debug_arrow"),
+            name: "debug_arrow".into(),
             transform: Transform::default(),
             pipe_id: gl_pipeline::GlPipelineType::Colored.into(),
             mesh_id: debug_arrow_mesh_id,
@@ -76,13 +169,68 @@ impl PhysicsSphere {
         self.object.transform.position
     }
 
+    /// Launches the sphere with the given linear and angular velocity. The
+    /// velocities are set directly and do not depend on the next step size.
+    pub fn apply_initial_impulse(
+        &self,
+        physics: &mut Physics,
+        velocity: V3,
+        angular_velocity: V3,
+    ) -> Result<()> {
+        let body = physics
+            .get_body_mut(self.body_id)
+            .ok_or(Error::InvalidBodyId)?;
+        body.set_velocity(velocity);
+        body.set_angular_velocity(angular_velocity);
+        Ok(())
+    }
+
+    /// Per sub-step update before the physics step.
+    pub fn update(&self, physics: &mut Physics, terrain: &Terrain, dt: f32) -> Result<()> {
+        self.roll(physics, terrain, dt)?;
+
+        let body = physics
+            .get_body_mut(self.body_id)
+            .ok_or(Error::InvalidBodyId)?;
+        body.apply_force(GRAVITY * body.mass());
+        Ok(())
+    }
+
+    /// Per sub-step collision after the physics step, sweeping the segment the
+    /// step moved the sphere along.
+    pub fn collide_terrain(&self, physics: &mut Physics, terrain: &Terrain) -> Result<bool> {
+        let body = physics
+            .get_body_mut(self.body_id)
+            .ok_or(Error::InvalidBodyId)?;
+        let prev_position = body.prev_position();
+        Ok(collide_sphere_terrain(
+            body,
+            self.radius,
+            terrain,
+            prev_position,
+        ))
+    }
+
+    /// Applies rolling resistance while the sphere touches the terrain.
+    pub fn roll(&self, physics: &mut Physics, terrain: &Terrain, dt: f32) -> Result<()> {
+        let body = physics
+            .get_body_mut(self.body_id)
+            .ok_or(Error::InvalidBodyId)?;
+
+        let p = body.position();
+        let height = p.x1() - terrain.height_at(p.x0(), p.x2());
+        if height <= self.radius + CONTACT_SLOP {
+            let normal = terrain.normal_at(p.x0(), p.x2());
+            apply_rolling_resistance(body, normal, self.radius, dt);
+        }
+        Ok(())
+    }
+
     pub fn transform(&mut self) -> &mut Transform {
         &mut self.object.transform
     }
 
     pub fn update_debug_arrows(&mut self, context: &mut RenderContext) -> Result<()> {
-        use crate::core::gl_pipeline_colored::arrow;
-
         let center = self.position().into();
         let v = V3::new([0.0, 0.0, -1.0]);
         let arrow_verts = arrow(center, center + v)?;
@@ -91,3 +239,223 @@ impl PhysicsSphere {
         Ok(())
     }
 }
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::x2d::RUBBER;
+
+    // ------------------------------------------------------------------------
+    fn test_sphere(physics: &mut Physics, position: V3, radius: f32) -> PhysicsSphere {
+        let body = PhysicsSphere::new_body(position, radius, RUBBER).unwrap();
+        PhysicsSphere {
+            object: RenderObject::default(),
+            debug_arrow: RenderObject::default(),
+            body_id: physics.add_body(body),
+            radius,
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    #[test]
+    fn mesh_errors_are_returned() {
+        let mut created = 0;
+        let meshes = create_sphere_meshes(|_, _| {
+            created += 1;
+            Ok(GlMeshId::default())
+        });
+        assert!(meshes.is_ok());
+        assert_eq!(created, 2);
+
+        let meshes = create_sphere_meshes(|_, _| Err(Error::GpuOutOfMemory));
+        assert!(matches!(meshes, Err(Error::GpuOutOfMemory)));
+    }
+
+    // ------------------------------------------------------------------------
+    #[test]
+    fn sweep_plane_time_of_impact() {
+        let n = V3::X1;
+        let p0 = V3::new([0.0, 2.0, 0.0]);
+        let p1 = V3::new([0.0, -2.0, 0.0]);
+        assert_eq!(sweep_sphere_plane(p0, p1, 0.0, V3::zero(), n), Some(0.5));
+        assert_eq!(sweep_sphere_plane(p0, p1, 1.0, V3::zero(), n), Some(0.25));
+        assert_eq!(sweep_sphere_plane(p0, p0, 1.0, V3::zero(), n), None);
+    }
+
+    // ------------------------------------------------------------------------
+    #[test]
+    fn initial_velocities_do_not_depend_on_dt() {
+        let velocity = V3::new([3.0, 1.0, -2.0]);
+        let angular_velocity = V3::new([0.0, 0.5, 4.0]);
+
+        for dt in [0.001, 1.0 / 60.0, 0.1] {
+            let mut physics = Physics::new();
+            let sphere = test_sphere(&mut physics, V3::new([0.0, 5.0, 0.0]), 0.5);
+
+            sphere
+                .apply_initial_impulse(&mut physics, velocity, angular_velocity)
+                .unwrap();
+
+            let body = physics.get_body_mut(sphere.id()).unwrap();
+            body.integrate_forces(dt);
+            body.integrate_velocities(dt);
+            assert_eq!(body.linear_velocity(), velocity);
+            assert_eq!(body.angular_velocity(), angular_velocity);
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    #[test]
+    fn rolling_sphere_slows_down_gradually() {
+        let radius = 0.5;
+        let mut body =
+            PhysicsSphere::new_body(V3::new([0.0, radius, 0.0]), radius, RUBBER).unwrap();
+
+        // Rolling along +x without slipping while spinning about the up axis
+        let v = V3::new([2.0, 0.0, 0.0]);
+        body.set_velocity(v);
+        body.set_angular_velocity(V3::new([0.0, 3.0, -v.x0() / radius]));
+
+        let dt = 1.0 / 60.0;
+        let (mut speed, mut spin) = (2.0, 3.0);
+        for _ in 0..120 {
+            apply_rolling_resistance(&mut body, V3::X1, radius, dt);
+            let (next_speed, next_spin) = (
+                body.linear_velocity().length(),
+                body.angular_velocity().x1(),
+            );
+            assert!(next_speed < speed && next_speed > 0.99 * speed);
+            assert!(next_spin < spin && next_spin > 0.99 * spin);
+            (speed, spin) = (next_speed, next_spin);
+        }
+
+        assert!(speed < 1.7 && spin < 1.2);
+        assert!(body.angular_velocity().x2() > -v.x0() / radius);
+    }
+
+    // ------------------------------------------------------------------------
+    #[test]
+    fn fast_sphere_is_caught_at_surface() {
+        let terrain = Terrain::new_flat(1, 1);
+        let radius = 0.5;
+        let start = V3::new([4.0, 1.0, 4.0]);
+
+        let mut body = PhysicsSphere::new_body(start, radius, RUBBER).unwrap();
+        body.apply_impulse(V3::new([0.0, -100.0 * body.mass(), 0.0]), "launch");
+
+        // One step moves the sphere 10m, straight through the ground.
+        let dt = 0.1;
+        let prev = body.position();
+        body.integrate_forces(dt);
+        body.integrate_velocities(dt);
+        assert!(body.position().x1() < -radius);
+
+        assert!(collide_sphere_terrain(&mut body, radius, &terrain, prev));
+        assert!((body.position().x1() - radius).abs() < 1e-4);
+        assert!(body.linear_velocity().x1() > 0.0);
+    }
+
+    // ------------------------------------------------------------------------
+    #[test]
+    fn fast_sphere_does_not_tunnel_through_terrain() {
+        let terrain = Terrain::new_flat(1, 1);
+        let radius = 0.5;
+        let mut physics = Physics::new();
+        let sphere = test_sphere(&mut physics, V3::new([4.0, 1.0, 4.0]), radius);
+        sphere
+            .apply_initial_impulse(&mut physics, V3::new([0.0, -100.0, 0.0]), V3::zero())
+            .unwrap();
+
+        // Each frame moves the sphere 10m, far more than its diameter.
+        for frame in 0..5 {
+            physics
+                .step_substeps_resolved(
+                    0.1,
+                    |physics, h| sphere.update(physics, &terrain, h),
+                    |physics| sphere.collide_terrain(physics, &terrain).map(|_| ()),
+                )
+                .unwrap();
+
+            let body = physics.get_body(sphere.id()).unwrap();
+            assert!(body.position().x1() >= radius - 1e-4, "frame {frame}");
+            if frame == 0 {
+                assert!(body.linear_velocity().x1() > 0.0);
+            }
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    #[test]
+    fn rolling_sphere_moves_across_terrain() {
+        let terrain = Terrain::new_flat(1, 1);
+        let radius = 0.5;
+        let mut physics = Physics::new();
+        let sphere = test_sphere(&mut physics, V3::new([4.0, radius, 4.0]), radius);
+        let v = V3::new([2.0, 0.0, 0.0]);
+        sphere
+            .apply_initial_impulse(&mut physics, v, V3::new([0.0, 0.0, -v.x0() / radius]))
+            .unwrap();
+
+        for _ in 0..30 {
+            physics
+                .step_substeps_resolved(
+                    1.0 / 60.0,
+                    |physics, h| sphere.update(physics, &terrain, h),
+                    |physics| sphere.collide_terrain(physics, &terrain).map(|_| ()),
+                )
+                .unwrap();
+        }
+
+        // Half a second at almost 2m/s, resting on the ground the whole time
+        let p = physics.get_body(sphere.id()).unwrap().position();
+        assert!(p.x0() > 4.9, "{p:?}");
+        assert!((p.x1() - radius).abs() < 1e-3, "{p:?}");
+        assert!((p.x2() - 4.0).abs() < 1e-4, "{p:?}");
+    }
+
+    // ------------------------------------------------------------------------
+    #[test]
+    fn spinning_sphere_slows_down_on_terrain() {
+        let terrain = Terrain::new_flat(1, 1);
+        let radius = 0.5;
+        let mut physics = Physics::new();
+        let sphere = test_sphere(&mut physics, V3::new([4.0, radius, 4.0]), radius);
+        sphere
+            .apply_initial_impulse(&mut physics, V3::zero(), V3::new([0.0, 2.0, -4.0]))
+            .unwrap();
+
+        let spin = |physics: &Physics| {
+            let body = physics.get_body(sphere.id()).unwrap();
+            body.angular_velocity().length()
+        };
+        let position = |physics: &Physics| physics.get_body(sphere.id()).unwrap().position();
+        let initial_spin = spin(&physics);
+        let mut prev_spin = initial_spin;
+        for _ in 0..60 {
+            let p0 = position(&physics);
+            physics
+                .step_substeps_resolved(
+                    1.0 / 60.0,
+                    |physics, h| sphere.update(physics, &terrain, h),
+                    |physics| sphere.collide_terrain(physics, &terrain).map(|_| ()),
+                )
+                .unwrap();
+            let next_spin = spin(&physics);
+            assert!(next_spin < prev_spin);
+            prev_spin = next_spin;
+
+            // The sphere moves along its velocity and stays on the ground.
+            let body = physics.get_body(sphere.id()).unwrap();
+            let moved = position(&physics) - p0;
+            let expected = body.linear_velocity().x0() / 60.0;
+            assert!(expected > 0.0 && (moved.x0() - expected).abs() < 0.05 * expected);
+            assert!(moved.x1().abs() < 1e-4);
+        }
+        assert!(prev_spin < 0.8 * initial_spin);
+
+        // The slipping contact point pushes the sphere into a forward roll.
+        let body = physics.get_body(sphere.id()).unwrap();
+        assert!(body.linear_velocity().x0() > 0.0);
+    }
+}
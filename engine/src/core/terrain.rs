@@ -1,5 +1,6 @@
+use crate::core::bounds::Aabb;
 use crate::core::gl_pipeline::GlMeshId;
-use crate::core::gl_pipeline_colored::{self, Vertex};
+use crate::core::gl_pipeline_colored::{self, OccludedVertex};
 use crate::core::gl_renderer::RenderContext;
 use crate::error::{Error, Result};
 use crate::v2d::v3::V3;
@@ -10,6 +11,20 @@ const TERRAIN_RESOLUTION: f32 = 0.5;
 const TERRAIN_RESOLUTION_INV: f32 = 1.0 / TERRAIN_RESOLUTION;
 const TERRAIN_CHUNK_SIZE: usize = 32;
 
+// Ambient occlusion compares a sample with its neighbors AO_RADIUS samples
+// away; each meter the neighbors are higher on average darkens by AO_STRENGTH.
+const AO_RADIUS: usize = 2;
+const AO_STRENGTH: f32 = 0.5;
+const AO_MIN: f32 = 0.2;
+
+// Vertex colors: slopes steeper than ROCK_SLOPE (normal.y below it) show bare
+// rock, anything above SNOW_HEIGHT meters is covered in snow.
+const GRASS_COLOR: V3 = V3::new([0.3, 0.55, 0.2]);
+const ROCK_COLOR: V3 = V3::new([0.45, 0.43, 0.4]);
+const SNOW_COLOR: V3 = V3::new([0.95, 0.95, 0.97]);
+const ROCK_SLOPE: f32 = 0.8;
+const SNOW_HEIGHT: f32 = 3.5;
+
 // ----------------------------------------------------------------------------
 #[derive(Debug)]
 pub struct Terrain {
@@ -40,6 +55,23 @@ impl Terrain {
         }
     }
 
+    // ------------------------------------------------------------------------
+    pub fn new_flat(chunks_cx: usize, chunks_cz: usize) -> Self {
+        let width = chunks_cx * TERRAIN_CHUNK_SIZE;
+        let height = chunks_cz * TERRAIN_CHUNK_SIZE;
+
+        let mut heightmap: Vec<f32> = vec![0.0; width * height];
+        generate_flat(&mut heightmap, width, height);
+
+        Terrain {
+            chunks_cx,
+            chunks_cz,
+            width,
+            height,
+            heightmap,
+        }
+    }
+
     // ------------------------------------------------------------------------
     pub fn from_png(path: &Path) -> Result<Self> {
         let contents = std::fs::read(path)?;
@@ -49,19 +81,31 @@ impl Terrain {
             return Err(Error::InvalidColorFormat);
         }
 
+        Self::from_grey_rows(&data, png.width, png.height)
+    }
+
+    // ------------------------------------------------------------------------
+    // Heights from `cx` x `cy` 8 bit samples as decoded from a PNG, each row
+    // led by its filter type byte. The map is cropped to whole chunks, and
+    // data shorter than the size claims is rejected.
+    fn from_grey_rows(data: &[u8], cx: usize, cy: usize) -> Result<Self> {
+        let src_stride = cx + 1;
+        if data.len() < src_stride * cy {
+            return Err(Error::InvalidPng);
+        }
+
         let h_norm: f32 = 1.0 / 5.0; // 5 levels per meter
-        let chunks_cx = png.width / TERRAIN_CHUNK_SIZE;
-        let chunks_cz = png.height / TERRAIN_CHUNK_SIZE;
+        let chunks_cx = cx / TERRAIN_CHUNK_SIZE;
+        let chunks_cz = cy / TERRAIN_CHUNK_SIZE;
         let width = chunks_cx * TERRAIN_CHUNK_SIZE;
         let height = chunks_cz * TERRAIN_CHUNK_SIZE;
 
         let mut heightmap: Vec<f32> = vec![0.0; width * height];
-        for y in 0..png.height {
-            let src_offset = y * (png.width + 1) + 1;
-            let dst_offset = y * width;
-            for x in 0..png.width {
-                let height = data[src_offset + x] as f32;
-                heightmap[dst_offset + x] = height * h_norm;
+        for y in 0..height {
+            let src = &data[y * src_stride + 1..][..width];
+            let dst = &mut heightmap[y * width..(y + 1) * width];
+            for (dst, src) in dst.iter_mut().zip(src) {
+                *dst = f32::from(*src) * h_norm;
             }
         }
 
@@ -98,9 +142,11 @@ impl Terrain {
                 let height = self.get_height_at(x, z);
                 let normal = self.get_normal_at(x, z);
 
-                vertices.push(Vertex {
+                vertices.push(OccludedVertex {
                     pos: V3::new([world_x, height, world_z]),
                     n: normal,
+                    color: biome_color(height, normal),
+                    ao: self.get_ao_at(x, z),
                 });
             }
         }
@@ -127,18 +173,9 @@ impl Terrain {
 
     // ------------------------------------------------------------------------
     pub fn height_at(&self, x: f32, z: f32) -> f32 {
-        // Convert world coordinates to heightmap indices
-        let hx = x * TERRAIN_RESOLUTION_INV;
-        let hz = z * TERRAIN_RESOLUTION_INV;
-
         // Bilinear interpolation between 4 neighboring samples
-        let x0 = hx.floor() as usize;
-        let z0 = hz.floor() as usize;
-        let x1 = x0 + 1;
-        let z1 = z0 + 1;
-
-        let fx = hx.fract();
-        let fz = hz.fract();
+        let (x0, z0, fx, fz) = self.cell_at(x, z);
+        let (x1, z1) = (x0 + 1, z0 + 1);
 
         let h00 = self.get_height_at(x0, z0);
         let h10 = self.get_height_at(x1, z0);
@@ -153,18 +190,9 @@ impl Terrain {
 
     // ------------------------------------------------------------------------
     pub fn normal_at(&self, x: f32, z: f32) -> V3 {
-        // Convert world coordinates to heightmap indices
-        let hx = x * TERRAIN_RESOLUTION_INV;
-        let hz = z * TERRAIN_RESOLUTION_INV;
-
         // Bilinear interpolation between 4 neighboring samples
-        let x0 = hx.floor() as usize;
-        let z0 = hz.floor() as usize;
-        let x1 = x0 + 1;
-        let z1 = z0 + 1;
-
-        let fx = hx.fract();
-        let fz = hz.fract();
+        let (x0, z0, fx, fz) = self.cell_at(x, z);
+        let (x1, z1) = (x0 + 1, z0 + 1);
 
         let n00 = self.get_normal_at(x0, z0);
         let n10 = self.get_normal_at(x1, z0);
@@ -177,6 +205,84 @@ impl Terrain {
         (n0 * (1.0 - fz) + n1 * fz).norm()
     }
 
+    // ------------------------------------------------------------------------
+    // Height on the rendered triangle below (x, z). Each quad is split along
+    // the diagonal from (x0 + 1, z0) to (x0, z0 + 1), as in `create_chunk_mesh`.
+    pub fn triangle_height_at(&self, x: f32, z: f32) -> f32 {
+        let (x0, z0, fx, fz) = self.cell_at(x, z);
+        let h00 = self.get_height_at(x0, z0);
+        let h10 = self.get_height_at(x0 + 1, z0);
+        let h01 = self.get_height_at(x0, z0 + 1);
+        let h11 = self.get_height_at(x0 + 1, z0 + 1);
+
+        if fx + fz <= 1.0 {
+            h00 + (h10 - h00) * fx + (h01 - h00) * fz
+        } else {
+            h11 + (h01 - h11) * (1.0 - fx) + (h10 - h11) * (1.0 - fz)
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // Face normal of the rendered triangle below (x, z).
+    pub fn triangle_normal_at(&self, x: f32, z: f32) -> V3 {
+        let (x0, z0, fx, fz) = self.cell_at(x, z);
+        let h00 = self.get_height_at(x0, z0);
+        let h10 = self.get_height_at(x0 + 1, z0);
+        let h01 = self.get_height_at(x0, z0 + 1);
+        let h11 = self.get_height_at(x0 + 1, z0 + 1);
+
+        // Height differences along x and z across the triangle
+        let (dx, dz) = if fx + fz <= 1.0 {
+            (h10 - h00, h01 - h00)
+        } else {
+            (h11 - h01, h11 - h10)
+        };
+        V3::new([-dx, TERRAIN_RESOLUTION, -dz]).norm()
+    }
+
+    // ------------------------------------------------------------------------
+    // Mesh triangles overlapping `region`, with the same vertices and winding
+    // as `create_chunk_mesh`: clockwise seen from above.
+    pub fn collider_triangles(&self, region: &Aabb) -> Vec<[V3; 3]> {
+        if region.is_empty() {
+            return Vec::new();
+        }
+
+        // Quads whose footprint overlaps the region; negative bounds saturate
+        let cells = |min: f32, max: f32, n: usize| {
+            let first = (min * TERRAIN_RESOLUTION_INV).floor() as usize;
+            let last = ((max * TERRAIN_RESOLUTION_INV).ceil() as usize).min(n);
+            first..last
+        };
+        let xs = cells(region.min.x0(), region.max.x0(), self.width);
+        let zs = cells(region.min.x2(), region.max.x2(), self.height);
+
+        let vertex = |x: usize, z: usize| {
+            let height = self.get_height_at(x, z);
+            V3::new([
+                x as f32 * TERRAIN_RESOLUTION,
+                height,
+                z as f32 * TERRAIN_RESOLUTION,
+            ])
+        };
+
+        let mut triangles = Vec::new();
+        for z in zs {
+            for x in xs.clone() {
+                let p00 = vertex(x, z);
+                let p10 = vertex(x + 1, z);
+                let p01 = vertex(x, z + 1);
+                let p11 = vertex(x + 1, z + 1);
+                triangles.extend(
+                    [[p00, p10, p01], [p10, p11, p01]]
+                        .into_iter()
+                        .filter(|tri| Aabb::from_points(*tri).intersects(region)),
+                );
+            }
+        }
+        triangles
+    }
+
     // ------------------------------------------------------------------------
     pub fn create_normal_arrow_mesh(
         &self,
@@ -191,6 +297,19 @@ impl Terrain {
         context.create_colored_mesh(&verts, &[], true)
     }
 
+    // ------------------------------------------------------------------------
+    // Heightmap sample below (x, z) and the fraction within its quad.
+    fn cell_at(&self, x: f32, z: f32) -> (usize, usize, f32, f32) {
+        let hx = x * TERRAIN_RESOLUTION_INV;
+        let hz = z * TERRAIN_RESOLUTION_INV;
+        (
+            hx.floor() as usize,
+            hz.floor() as usize,
+            hx.fract(),
+            hz.fract(),
+        )
+    }
+
     // ------------------------------------------------------------------------
     fn get_height_at(&self, x: usize, z: usize) -> f32 {
         let x = x.min(self.width - 1);
@@ -198,6 +317,32 @@ impl Terrain {
         self.heightmap[x + z * self.width]
     }
 
+    // ------------------------------------------------------------------------
+    // Cheap ambient occlusion: samples below the average of their neighbors
+    // sit in a hollow and receive less ambient light. Convex areas stay at 1.
+    fn get_ao_at(&self, x: usize, z: usize) -> f32 {
+        let r = AO_RADIUS;
+        let (x0, x1) = (x.saturating_sub(r), x + r);
+        let (z0, z1) = (z.saturating_sub(r), z + r);
+        let neighbors = [
+            (x0, z0),
+            (x, z0),
+            (x1, z0),
+            (x0, z),
+            (x1, z),
+            (x0, z1),
+            (x, z1),
+            (x1, z1),
+        ];
+
+        let sum: f32 = neighbors
+            .iter()
+            .map(|&(x, z)| self.get_height_at(x, z))
+            .sum();
+        let depth = sum / neighbors.len() as f32 - self.get_height_at(x, z);
+        (1.0 - AO_STRENGTH * depth.max(0.0)).max(AO_MIN)
+    }
+
     // ------------------------------------------------------------------------
     fn get_normal_at(&self, x: usize, z: usize) -> V3 {
         let west = if x > 0 {
@@ -230,6 +375,17 @@ impl Terrain {
     }
 }
 
+// ----------------------------------------------------------------------------
+fn biome_color(height: f32, normal: V3) -> V3 {
+    if normal.x1() < ROCK_SLOPE {
+        ROCK_COLOR
+    } else if height > SNOW_HEIGHT {
+        SNOW_COLOR
+    } else {
+        GRASS_COLOR
+    }
+}
+
 // ----------------------------------------------------------------------------
 fn generate_flat(_heightmap: &mut [f32], _width: usize, _height: usize) {}
 
@@ -251,3 +407,146 @@ fn generate_hills(heightmap: &mut [f32], width: usize, height: usize) {
         }
     }
 }
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_float_eq;
+
+    // ------------------------------------------------------------------------
+    // Flat terrain with a single raised sample at (1, 1).
+    fn terrain_with_peak(peak: f32) -> Terrain {
+        let mut terrain = Terrain::new_flat(1, 1);
+        terrain.heightmap[1 + terrain.width] = peak;
+        terrain
+    }
+
+    #[test]
+    fn grey_rows_are_cropped_to_chunks() {
+        let (cx, cy) = (TERRAIN_CHUNK_SIZE + 3, TERRAIN_CHUNK_SIZE + 1);
+        let mut data = vec![0; (cx + 1) * cy];
+        data[(cx + 1) + 1 + 2] = 10; // row 1, column 2
+        data[cx] = 255; // row 0, column cx - 1 is cropped
+
+        let terrain = Terrain::from_grey_rows(&data, cx, cy).unwrap();
+        assert_eq!(
+            (terrain.width, terrain.height),
+            (TERRAIN_CHUNK_SIZE, TERRAIN_CHUNK_SIZE)
+        );
+        assert_float_eq!(terrain.heightmap[terrain.width + 2], 2.0);
+        assert!(terrain.heightmap.iter().all(|h| *h <= 2.0));
+    }
+
+    #[test]
+    fn truncated_grey_rows_are_rejected() {
+        let (cx, cy) = (TERRAIN_CHUNK_SIZE, TERRAIN_CHUNK_SIZE);
+        let data = vec![0; (cx + 1) * cy - 1];
+        assert_eq!(
+            Terrain::from_grey_rows(&data, cx, cy).err(),
+            Some(Error::InvalidPng)
+        );
+        assert!(Terrain::from_grey_rows(&[], 2, 0).is_ok());
+    }
+
+    #[test]
+    fn triangle_height_follows_mesh_diagonal() {
+        let terrain = terrain_with_peak(2.0);
+        let r = TERRAIN_RESOLUTION;
+
+        // Quad (0, 0)..(1, 1): the peak is at its far corner, which only the
+        // second triangle touches.
+        let (x, z) = (0.3 * r, 0.3 * r);
+        assert_float_eq!(terrain.triangle_height_at(x, z), 0.0);
+        assert_float_eq!(terrain.height_at(x, z), 2.0 * 0.3 * 0.3);
+
+        let (x, z) = (0.7 * r, 0.7 * r);
+        assert_float_eq!(terrain.triangle_height_at(x, z), 2.0 * 0.4);
+        assert_float_eq!(terrain.height_at(x, z), 2.0 * 0.7 * 0.7);
+
+        // Both triangles agree on the diagonal
+        let (x, z) = (0.4 * r, 0.6 * r);
+        assert_float_eq!(terrain.triangle_height_at(x, z), 0.0);
+    }
+
+    #[test]
+    fn pits_are_darker_than_peaks() {
+        let mut terrain = Terrain::new_flat(1, 1);
+        let (pit, peak) = ((8, 8), (20, 20));
+        terrain.heightmap[pit.0 + pit.1 * terrain.width] = -1.0;
+        terrain.heightmap[peak.0 + peak.1 * terrain.width] = 1.0;
+
+        let ao_pit = terrain.get_ao_at(pit.0, pit.1);
+        let ao_peak = terrain.get_ao_at(peak.0, peak.1);
+        assert!(ao_pit < ao_peak);
+        assert_eq!(ao_peak, 1.0);
+        assert_eq!(terrain.get_ao_at(4, 20), 1.0);
+        assert!(ao_pit >= AO_MIN);
+    }
+
+    #[test]
+    fn triangle_normal_is_face_normal() {
+        let terrain = terrain_with_peak(2.0);
+        let r = TERRAIN_RESOLUTION;
+
+        assert_eq!(terrain.triangle_normal_at(0.3 * r, 0.3 * r), V3::X1);
+
+        // Second triangle spans (1, 0), (1, 1) and (0, 1)
+        let p10 = V3::new([r, 0.0, 0.0]);
+        let p11 = V3::new([r, 2.0, r]);
+        let p01 = V3::new([0.0, 0.0, r]);
+        let expected = (p10 - p11).cross(p01 - p11).norm();
+        assert!(expected.x1() > 0.0);
+        assert_eq!(terrain.triangle_normal_at(0.7 * r, 0.7 * r), expected);
+    }
+
+    #[test]
+    fn collider_triangles_match_mesh_quad() {
+        let terrain = terrain_with_peak(2.0);
+        let r = TERRAIN_RESOLUTION;
+        let region = Aabb::new(V3::new([0.0, -1.0, 0.0]), V3::new([r, 1.0, r]));
+
+        let p00 = V3::new([0.0, 0.0, 0.0]);
+        let p10 = V3::new([r, 0.0, 0.0]);
+        let p01 = V3::new([0.0, 0.0, r]);
+        let p11 = V3::new([r, 2.0, r]);
+        let triangles = terrain.collider_triangles(&region);
+        assert_eq!(triangles, vec![[p00, p10, p01], [p10, p11, p01]]);
+
+        // Winding agrees with the face normals
+        for ([a, b, c], f) in triangles.into_iter().zip([0.3, 0.7]) {
+            let n = (c - a).cross(b - a).norm();
+            assert_eq!(n, terrain.triangle_normal_at(f * r, f * r));
+        }
+    }
+
+    #[test]
+    fn collider_triangles_are_culled_by_height() {
+        let terrain = terrain_with_peak(2.0);
+        let r = TERRAIN_RESOLUTION;
+
+        // Above the flat triangles, only those leaning on the peak remain
+        let region = Aabb::new(V3::new([0.0, 1.0, 0.0]), V3::new([2.0 * r, 3.0, 2.0 * r]));
+        assert_eq!(terrain.collider_triangles(&region).len(), 6);
+
+        let below = Aabb::new(V3::new([0.0, -3.0, 0.0]), V3::new([2.0 * r, -1.0, 2.0 * r]));
+        assert!(terrain.collider_triangles(&below).is_empty());
+        assert!(terrain.collider_triangles(&Aabb::EMPTY).is_empty());
+    }
+
+    #[test]
+    fn slopes_are_rock_and_lowlands_grass() {
+        let terrain = terrain_with_peak(2.0);
+
+        // next to the peak the ground rises by 2 m over one sample
+        let (x, z) = (1, 0);
+        let steep = biome_color(terrain.get_height_at(x, z), terrain.get_normal_at(x, z));
+        assert_eq!(steep, ROCK_COLOR);
+
+        let (x, z) = (20, 20);
+        let flat = biome_color(terrain.get_height_at(x, z), terrain.get_normal_at(x, z));
+        assert_eq!(flat, GRASS_COLOR);
+
+        assert_eq!(biome_color(SNOW_HEIGHT + 1.0, V3::X1), SNOW_COLOR);
+    }
+}
@@ -1,57 +1,75 @@
 use crate::core::{
-    camera::Camera,
-    car::{Car, Geometry},
+    IRenderer,
+    camera::{Camera, CameraMode, CameraTarget},
+    car::{Car, CarSetup, Geometry},
     component::{Component, Context},
-    game_input, gl_font,
+    coords::{self, FORWARD},
+    game_input::{self, GameKey},
+    gizmo::Gizmo,
+    gl_font,
     gl_pipeline::{self, GlMaterial},
-    gl_renderer::{DefaultMaterials, RenderContext, RenderObject, Rotation, Transform},
-    gl_text::create_text_mesh,
+    gl_renderer::{self, DefaultMaterials, RenderContext, RenderObject, Rotation, Transform},
+    gl_text::{TextLayout, create_text_mesh},
     input,
+    light::Light,
     player::Player,
+    slider::SliderDemo,
     terrain::Terrain,
 };
 use crate::error::Result;
 use crate::sys::opengl as gl;
-use crate::v2d::{v3::V3, v4::V4};
+use crate::v2d::{m4x4::M4x4, v3::V3, v4::V4};
 use crate::x2d::{self};
 use std::path::Path;
 use std::rc::Rc;
 
+// ----------------------------------------------------------------------------
+const GRID_EXTENT: f32 = 10.0;
+const GRID_SPACING: f32 = 1.0;
+
 // ----------------------------------------------------------------------------
 pub struct World {
     render_context: RenderContext,
     input_context: game_input::InputContext,
     terrain: Terrain,
     player: Player,
-    camera: Camera,
+    view: View,
+    camera_target: CameraTarget,
     physics: x2d::physics::Physics,
     car: Car,
+    slider: SliderDemo,
     debug: RenderObject,
     terrain_chunks: Vec<RenderObject>,
     terrain_normal_arrows: Vec<RenderObject>,
-    debug_arrows: Vec<RenderObject>,
+    gizmo: Gizmo,
+    show_debug: bool,
     _font: gl_font::Font,
 }
 
 // ----------------------------------------------------------------------------
 impl World {
     pub fn new(gl: Rc<gl::OpenGlFunctions>) -> Result<Self> {
-        let font = gl_font::Font::load(&gl, Path::new("assets/fonts/roboto"))?;
+        Self::with_assets(gl, Path::new("assets"))
+    }
+
+    // Loads the assets from `assets` instead of the working directory.
+    pub fn with_assets(gl: Rc<gl::OpenGlFunctions>, assets: &Path) -> Result<Self> {
+        let font = gl_font::Font::load(&gl, &assets.join("fonts/roboto"))?;
         let mut render_context = RenderContext::new(gl)?;
 
         let font_id = render_context.insert_material(GlMaterial::Texture {
             texture: font.texture,
         });
 
-        let camera = Camera::new(
+        let mut camera = Camera::new(
             V4::new([0.0, 4.0, -1.0, 1.0]),
             V4::new([0.0, 0.0, 0.0, 1.0]),
         );
 
-        let mesh = create_text_mesh(&font, "Debug Text: Hello, World!")?;
+        let mesh = create_text_mesh(&font, "Debug Text: Hello, World!", &TextLayout::default())?;
         let mesh_id = render_context.create_msdftex_mesh(&mesh)?;
         let debug = RenderObject {
-            name: String::from("debug"),
+            name: "debug".into(),
             transform: Transform {
                 position: V4::new([1.0, 0.0, 0.0, 1.0]),
                 rotation: Rotation::default(),
@@ -74,7 +92,7 @@ impl World {
             for z in 0..chunks_cz {
                 let mesh_id = terrain.create_chunk_mesh(&mut render_context, x, z)?;
                 terrain_chunks.push(RenderObject {
-                    name: format!("terrain_chunk_{x}_{z}"),
+                    name: format!("terrain_chunk_{x}_{z}").into(),
                     transform: Transform::default(),
                     pipe_id: gl_pipeline::GlPipelineType::Colored.into(),
                     mesh_id,
@@ -94,7 +112,7 @@ impl World {
                     1.0,
                 )?;
                 terrain_normal_arrows.push(RenderObject {
-                    name: format!("terrain_normal_arrow_{x}_{z}"),
+                    name: format!("terrain_normal_arrow_{x}_{z}").into(),
                     transform: Transform::default(),
                     pipe_id: gl_pipeline::GlPipelineType::Colored.into(),
                     mesh_id,
@@ -104,86 +122,61 @@ impl World {
             }
         }
 
-        use crate::core::gl_pipeline_colored::arrow;
-        let x0_arrow_verts = arrow(V3::ZERO, V3::X0)?;
-        let x1_arrow_verts = arrow(V3::ZERO, V3::X1)?;
-        let x2_arrow_verts = arrow(V3::ZERO, V3::X2)?;
-        let x0_debug_arrow_mesh_id =
-            render_context.create_colored_mesh(&x0_arrow_verts, &[], true)?;
-        let x1_debug_arrow_mesh_id =
-            render_context.create_colored_mesh(&x1_arrow_verts, &[], true)?;
-        let x2_debug_arrow_mesh_id =
-            render_context.create_colored_mesh(&x2_arrow_verts, &[], true)?;
-        let debug_arrows = vec![
-            RenderObject {
-                name: String::from("x0_debug_arrow"),
-                transform: Transform::default(),
-                pipe_id: gl_pipeline::GlPipelineType::Colored.into(),
-                mesh_id: x0_debug_arrow_mesh_id,
-                material_id: render_context.default_material(DefaultMaterials::Green),
-                ..Default::default()
-            },
-            RenderObject {
-                name: String::from("x1_debug_arrow"),
-                transform: Transform {
-                    position: V4::new([0.0, 0.0, 0.0, 1.0]),
-                    rotation: Rotation::default(),
-                    size: V4::new([1.0, 1.0, 1.0, 1.0]),
-                },
-                pipe_id: gl_pipeline::GlPipelineType::Colored.into(),
-                mesh_id: x1_debug_arrow_mesh_id,
-                material_id: render_context.default_material(DefaultMaterials::Red),
-                ..Default::default()
-            },
-            RenderObject {
-                name: String::from("x2_debug_arrow"),
-                transform: Transform {
-                    position: V4::new([0.0, 0.0, 0.0, 1.0]),
-                    rotation: Rotation::default(),
-                    size: V4::new([1.0, 1.0, 1.0, 1.0]),
-                },
-                pipe_id: gl_pipeline::GlPipelineType::Colored.into(),
-                mesh_id: x2_debug_arrow_mesh_id,
-                material_id: render_context.default_material(DefaultMaterials::Blue),
-                ..Default::default()
-            },
-        ];
+        let gizmo = Gizmo::new(&mut render_context, GRID_EXTENT, GRID_SPACING)?;
 
         let player = Player::new(&mut render_context)?;
 
-        let car_geo = Geometry {
-            length: 4.0,
-            width: 1.7,
-            height: 1.5,
-            wheel_base: 2.5,
-            wheel_track: 2.0,
-            wheel_radius: 0.4,
-            wheel_width: 0.3,
+        let car_setup = CarSetup {
+            geometry: Geometry {
+                length: 4.0,
+                width: 1.7,
+                height: 1.5,
+                wheel_base: 2.5,
+                wheel_track: 2.0,
+                wheel_radius: 0.4,
+                wheel_width: 0.3,
+                ride_height: 0.6,
+            },
+            ..Default::default()
         };
 
         let mut physics = x2d::physics::Physics::new();
 
-        let car = Car::new(&mut render_context, &mut physics, car_geo)?;
+        let car = Car::new(&mut render_context, &mut physics, car_setup)?;
+        let slider = SliderDemo::new(&mut render_context, &mut physics, V3::new([-4.0, 2.0, 4.0]))?;
+
+        let (forward, position) = car.transform(&physics)?;
+        camera.look_at(position, forward);
+        camera.snap_to_target();
 
-        Ok(World {
+        let mut world = World {
             render_context,
             input_context: game_input::InputContext::default(),
             terrain,
-            camera,
+            view: View::new(camera),
+            camera_target: CameraTarget::default(),
             player,
             physics,
             debug,
             terrain_chunks,
             terrain_normal_arrows,
-            debug_arrows,
+            gizmo,
+            show_debug: true,
             car,
+            slider,
             _font: font,
-        })
+        };
+        world.refresh_objects();
+        Ok(world)
     }
 
-    pub fn input(&mut self, events: &input::Events, state: input::State) -> Result<()> {
-        self.input_context.update_state(state);
-        self.camera.input(events)?;
+    pub fn input(&mut self, events: &input::Events, state: &input::State) -> Result<()> {
+        self.input_context.update_state(state.clone());
+        self.view.camera.input(events)?;
+        self.show_debug = toggle_debug(self.show_debug, &self.input_context);
+        if self.camera_target != CameraTarget::Free {
+            self.car.handle_actions(&self.input_context);
+        }
         Ok(())
     }
 
@@ -194,15 +187,31 @@ impl World {
             terrain: &self.terrain,
         };
 
-        self.camera.update(&ctx)?;
+        self.view.camera.update(&ctx)?;
         //self.player.update(&ctx)?;
-        self.car.update(&ctx, &mut self.physics)?;
 
-        self.car.apply_gravity(&mut self.physics)?;
+        // The keys fly the free camera, so the car doesn't see them.
+        let idle = game_input::InputContext::default();
+        let car_input = if self.camera_target == CameraTarget::Free {
+            &idle
+        } else {
+            ctx.state
+        };
 
-        self.physics.step(ctx.dt_secs());
+        let car = &mut self.car;
+        let slider = &self.slider;
+        self.physics.step_substeps(ctx.dt_secs(), |physics, h| {
+            let ctx = Context {
+                dt: std::time::Duration::from_secs_f32(h),
+                state: car_input,
+                ..ctx
+            };
+            car.apply_controls(&ctx, physics)?;
+            car.apply_gravity(physics)?;
+            slider.apply_forces(physics)
+        })?;
 
-        self.camera.integrate_positions(ctx.dt_secs());
+        self.view.camera.integrate_positions(ctx.dt_secs());
         //self.player.integrate_positions(ctx.dt_secs());
 
         self.player.update_debug_arrows(&mut self.render_context)?;
@@ -210,39 +219,442 @@ impl World {
             .update_debug_arrows(&mut self.render_context, &self.physics)?;
 
         self.car.update_render_objects(&self.physics)?;
-
-        //let (forward, position) = self.player.transform();
-        let (forward, position) = self.car.transform(&self.physics)?;
-        //let (forward, position) = (V4::X2, V4::X3);
+        self.slider
+            .update_debug_arrow(&mut self.render_context, &self.physics)?;
+        self.slider.update_render_objects(&self.physics)?;
 
         {
-            let mesh = create_text_mesh(&self._font, &self.car.drive_state())?;
+            let (_, position) = self.car.transform(&self.physics)?;
+            let mesh =
+                create_text_mesh(&self._font, &self.car.drive_state(), &TextLayout::default())?;
             self.render_context
                 .update_msdftex_mesh(self.debug.mesh_id, &mesh)?;
             self.debug.transform.position = position + V4::new([0.0, 0.5, 0.0, 0.0]);
         }
-        self.camera.look_at(position, forward);
+
+        if let Some((forward, position)) = self.camera_focus()? {
+            self.view.camera.look_at(position, forward);
+        }
+
+        self.refresh_objects();
         Ok(())
     }
 
+    fn refresh_objects(&mut self) {
+        let mut objects = std::mem::take(&mut self.view.objects);
+        gl_renderer::copy_objects(&mut objects, self.visible_objects());
+        self.view.objects = objects;
+    }
+
+    pub fn camera_target(&self) -> &CameraTarget {
+        &self.camera_target
+    }
+
+    // A free target also switches the camera to free flight, any other
+    // target brings a free camera back to chasing.
+    pub fn set_camera_target(&mut self, target: CameraTarget) {
+        if target == CameraTarget::Free {
+            self.view.camera.set_mode(CameraMode::Free);
+        } else if self.view.camera.mode() == CameraMode::Free {
+            self.view.camera.set_mode(CameraMode::Chase);
+        }
+        self.camera_target = target;
+    }
+
+    fn camera_focus(&self) -> Result<Option<(V4, V4)>> {
+        target_focus(
+            &self.camera_target,
+            self.player.transform(),
+            || self.car.transform(&self.physics),
+            // a debug object stays the target while the overlays are hidden
+            self.objects_with_debug(true),
+        )
+    }
+
+    pub fn set_physics_substeps(&mut self, substeps: usize) {
+        self.physics.set_substeps(substeps);
+    }
+
+    pub fn set_physics_solver_iterations(&mut self, iterations: usize) {
+        self.physics.set_solver_iterations(iterations);
+    }
+
+    pub fn input_context(&self) -> &game_input::InputContext {
+        &self.input_context
+    }
+
     pub fn camera(&self) -> &Camera {
-        &self.camera
+        &self.view.camera
+    }
+
+    pub fn show_debug(&self) -> bool {
+        self.show_debug
+    }
+
+    pub fn set_show_debug(&mut self, show_debug: bool) {
+        self.show_debug = show_debug;
+    }
+
+    fn visible_objects(&self) -> impl Iterator<Item = &RenderObject> {
+        self.objects_with_debug(self.show_debug)
+    }
+
+    fn objects_with_debug(&self, show_debug: bool) -> impl Iterator<Item = &RenderObject> {
+        let scene = self
+            .terrain_chunks
+            .iter()
+            //.chain(self.player.render_objects())
+            .chain(self.car.render_objects())
+            .chain(self.slider.render_objects());
+        let debug = std::iter::once(&self.debug)
+            .chain(&self.terrain_normal_arrows)
+            //.chain(self.player.debug_objects())
+            .chain(self.car.debug_objects())
+            .chain(self.slider.debug_objects())
+            .chain(self.gizmo.render_objects());
+        with_debug(scene, debug, show_debug)
+    }
+
+    // Visible objects as of the last update.
+    pub fn objects(&self) -> &[RenderObject] {
+        self.view.objects()
     }
 
-    pub fn objects(&self) -> Vec<RenderObject> {
-        let mut objects = self.terrain_chunks.clone();
-        //objects.extend(self.terrain_normal_arrows.iter().cloned());
-        //objects.extend(self.player.objects.iter().cloned());
-        //objects.extend(self.player.debug_arrows.iter().cloned());
-        objects.push(self.debug.clone());
-        objects.extend(self.car.objects.iter().cloned());
-        objects.extend(self.car.debug_arrows.iter().cloned());
-        objects.extend(self.debug_arrows.iter().cloned());
+    // Nearest visible object whose bounds are hit by the ray.
+    pub fn pick(&self, ray_origin: V3, ray_dir: V3) -> Option<&RenderObject> {
+        let meshes = self.render_context.meshes();
+        gl_renderer::pick(self.visible_objects(), meshes, ray_origin, ray_dir)
+    }
+
+    pub fn light(&self) -> &Light {
+        self.render_context.light()
+    }
 
-        objects
+    pub fn set_light(&mut self, light: Light) {
+        self.render_context.set_light(light);
     }
 
     pub fn render_context(&self) -> &RenderContext {
         &self.render_context
     }
+
+    pub fn render(&self, renderer: &impl IRenderer) -> Result<()> {
+        self.view.render(renderer, &self.render_context)
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Camera and visible objects as of the last update, the part of a frame that
+// the world hands to the renderer besides the render context.
+#[derive(Debug)]
+struct View {
+    camera: Camera,
+    // kept between frames so that rendering doesn't allocate
+    objects: Vec<RenderObject>,
+}
+
+// ----------------------------------------------------------------------------
+impl View {
+    fn new(camera: Camera) -> Self {
+        Self {
+            camera,
+            objects: Vec::new(),
+        }
+    }
+
+    fn objects(&self) -> &[RenderObject] {
+        &self.objects
+    }
+
+    fn render(&self, renderer: &impl IRenderer, context: &RenderContext) -> Result<()> {
+        renderer.render(&self.camera, &self.objects, context)
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Whether the debug overlays are shown after this frame's input.
+fn toggle_debug(show_debug: bool, input: &game_input::InputContext) -> bool {
+    show_debug ^ input.just_pressed(GameKey::ToggleDebug)
+}
+
+// ----------------------------------------------------------------------------
+// Scene objects followed by the debug overlays, if they are shown.
+fn with_debug<'a>(
+    scene: impl Iterator<Item = &'a RenderObject>,
+    debug: impl Iterator<Item = &'a RenderObject>,
+    show_debug: bool,
+) -> impl Iterator<Item = &'a RenderObject> {
+    scene.chain(debug.filter(move |_| show_debug))
+}
+
+// ----------------------------------------------------------------------------
+// Forward direction and position the camera should follow for `target`, None
+// if it is free or none of `objects` has the target's name.
+fn target_focus<'a>(
+    target: &CameraTarget,
+    player: (V4, V4),
+    car: impl FnOnce() -> Result<(V4, V4)>,
+    objects: impl Iterator<Item = &'a RenderObject>,
+) -> Result<Option<(V4, V4)>> {
+    match target {
+        CameraTarget::Player => Ok(Some(player)),
+        CameraTarget::Car => car().map(Some),
+        CameraTarget::Free => Ok(None),
+        CameraTarget::Object(name) => Ok(find_focus(objects, name)),
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Forward direction and position of the object named `name`.
+fn find_focus<'a>(
+    mut objects: impl Iterator<Item = &'a RenderObject>,
+    name: &str,
+) -> Option<(V4, V4)> {
+    let object = objects.find(|object| object.name() == name)?;
+    let model: M4x4 = object.transform.into();
+    let forward = (model * coords::direction(FORWARD)).norm();
+    Some((forward, object.transform.position))
+}
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v2d::affine4x4;
+    use std::cell::Cell;
+
+    // World on the mock OpenGL functions, with the assets of the repository.
+    fn test_world() -> World {
+        let gl = Rc::new(gl::tests::mock_gl());
+        let assets = Path::new(env!("CARGO_MANIFEST_DIR")).join("../assets");
+        World::with_assets(gl, &assets).unwrap()
+    }
+
+    // One frame of input followed by `updates` game updates.
+    fn run_frame(world: &mut World, input: &input::Input, updates: usize) {
+        world
+            .input(&input::Events::new(), &input.take_state())
+            .unwrap();
+        for _ in 0..updates {
+            world.update(&std::time::Duration::from_millis(10)).unwrap();
+        }
+    }
+
+    fn named(name: &str) -> RenderObject {
+        RenderObject {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    // Remembers how many objects it was last asked to render.
+    struct MockRenderer {
+        objects: Cell<Option<usize>>,
+    }
+
+    impl IRenderer for MockRenderer {
+        fn render(
+            &self,
+            _camera: &Camera,
+            objects: &[RenderObject],
+            _context: &RenderContext,
+        ) -> Result<()> {
+            self.objects.set(Some(objects.len()));
+            Ok(())
+        }
+
+        fn resize(&self, _cx: i32, _cy: i32) {}
+    }
+
+    #[test]
+    fn camera_follows_named_object() {
+        let turned = Rotation::Matrix(affine4x4::rotate_x1(std::f32::consts::FRAC_PI_2));
+        let objects = [
+            RenderObject {
+                name: "crate".into(),
+                transform: Transform {
+                    position: V4::new([1.0, 0.0, 2.0, 1.0]),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            RenderObject {
+                name: "sign".into(),
+                transform: Transform {
+                    position: V4::new([-3.0, 1.0, 0.0, 1.0]),
+                    rotation: turned,
+                    size: V4::new([2.0, 2.0, 2.0, 1.0]),
+                },
+                ..Default::default()
+            },
+        ];
+
+        let mut camera = Camera::new(V4::zero(), V4::zero());
+        for object in &objects {
+            let (forward, position) = find_focus(objects.iter(), &object.name).unwrap();
+            camera.look_at(position, forward);
+            assert_eq!(camera.target(), object.transform.position);
+            assert_eq!(forward.length(), 1.0);
+        }
+
+        let (forward, _) = find_focus(objects.iter(), "sign").unwrap();
+        assert_eq!(forward, -coords::direction(coords::RIGHT));
+        assert!(find_focus(objects.iter(), "missing").is_none());
+    }
+
+    #[test]
+    fn camera_focus_per_target() {
+        let player = (V4::new([1.0, 0.0, 0.0, 0.0]), V4::new([1.0, 2.0, 3.0, 1.0]));
+        let car = (V4::new([0.0, 0.0, 1.0, 0.0]), V4::new([4.0, 0.0, 5.0, 1.0]));
+        let mut crate_object = named("crate");
+        crate_object.transform.position = V4::new([-2.0, 0.0, 7.0, 1.0]);
+        let objects = [named("terrain"), crate_object];
+
+        let focus = |target: CameraTarget| {
+            target_focus(&target, player, || Ok(car), objects.iter()).unwrap()
+        };
+        assert_eq!(focus(CameraTarget::Player), Some(player));
+        assert_eq!(focus(CameraTarget::Car), Some(car));
+        assert_eq!(focus(CameraTarget::Free), None);
+
+        let (forward, position) = focus(CameraTarget::Object("crate".into())).unwrap();
+        assert_eq!(position, objects[1].transform.position);
+        assert_eq!(forward, coords::direction(FORWARD));
+        assert_eq!(focus(CameraTarget::Object("missing".into())), None);
+
+        // The car is only asked for its transform when it is the target.
+        let failing_car = || -> Result<(V4, V4)> { Err(crate::error::Error::InvalidBodyId) };
+        assert!(target_focus(&CameraTarget::Car, player, failing_car, objects.iter()).is_err());
+        assert!(target_focus(&CameraTarget::Free, player, failing_car, objects.iter()).is_ok());
+    }
+
+    #[test]
+    fn toggling_debug_hides_overlays() {
+        let scene = [named("terrain"), named("car")];
+        let debug = [named("x0_debug_arrow"), named("wheel_debug_arrow")];
+
+        let mut input = input::Input::new();
+        let mut ctx = game_input::InputContext::default();
+        let mut show_debug = false;
+        let names = |show_debug| -> Vec<String> {
+            with_debug(scene.iter(), debug.iter(), show_debug)
+                .map(|object| object.name().to_string())
+                .collect()
+        };
+        assert_eq!(names(show_debug), ["terrain", "car"]);
+
+        input.key_down(ctx.mapped_key(GameKey::ToggleDebug));
+        ctx.update_state(input.take_state());
+        show_debug = toggle_debug(show_debug, &ctx);
+        assert_eq!(
+            names(show_debug),
+            ["terrain", "car", "x0_debug_arrow", "wheel_debug_arrow"]
+        );
+
+        // Held down, the key doesn't toggle again
+        ctx.update_state(input.take_state());
+        show_debug = toggle_debug(show_debug, &ctx);
+        assert!(show_debug);
+
+        input.key_up(ctx.mapped_key(GameKey::ToggleDebug));
+        ctx.update_state(input.take_state());
+        input.key_down(ctx.mapped_key(GameKey::ToggleDebug));
+        ctx.update_state(input.take_state());
+        show_debug = toggle_debug(show_debug, &ctx);
+        assert_eq!(names(show_debug), ["terrain", "car"]);
+
+        // A debug object can still be followed while the overlays are hidden.
+        let all = with_debug(scene.iter(), debug.iter(), true);
+        let target = CameraTarget::Object("wheel_debug_arrow".into());
+        let focus = target_focus(&target, (V4::zero(), V4::zero()), || unreachable!(), all);
+        assert!(focus.unwrap().is_some());
+    }
+
+    #[test]
+    fn car_actions_fire_once_per_frame() {
+        let mut world = test_world();
+        let mut input = input::Input::new();
+
+        input.key_down(world.input_context().mapped_key(GameKey::Lights));
+        run_frame(&mut world, &input, 2);
+        assert!(world.car.lights_on);
+
+        run_frame(&mut world, &input, 4);
+        assert!(world.car.lights_on);
+
+        input.key_up(world.input_context().mapped_key(GameKey::Lights));
+        run_frame(&mut world, &input, 1);
+        input.key_down(world.input_context().mapped_key(GameKey::Lights));
+        run_frame(&mut world, &input, 2);
+        assert!(!world.car.lights_on);
+    }
+
+    #[test]
+    fn debug_toggles_once_per_frame() {
+        let mut world = test_world();
+        let mut input = input::Input::new();
+        let toggle = world.input_context().mapped_key(GameKey::ToggleDebug);
+        assert!(world.show_debug);
+
+        input.key_down(toggle);
+        run_frame(&mut world, &input, 2);
+        assert!(!world.show_debug);
+
+        // Held down, the key doesn't toggle again
+        run_frame(&mut world, &input, 4);
+        assert!(!world.show_debug);
+
+        input.key_up(toggle);
+        run_frame(&mut world, &input, 1);
+        input.key_down(toggle);
+        run_frame(&mut world, &input, 4);
+        assert!(world.show_debug);
+    }
+
+    #[test]
+    fn free_camera_keys_do_not_drive_the_car() {
+        // Where the chassis is after a second, the car still settles on the
+        // terrain at that point.
+        let chassis_after = |target: CameraTarget, keys: &[GameKey]| {
+            let mut world = test_world();
+            world.set_camera_target(target);
+
+            let mut input = input::Input::new();
+            for key in keys {
+                input.key_down(world.input_context().mapped_key(key.clone()));
+            }
+            for _ in 0..100 {
+                run_frame(&mut world, &input, 1);
+            }
+            world
+                .physics
+                .get_body(world.car.chassis)
+                .unwrap()
+                .position()
+        };
+
+        let at_rest = chassis_after(CameraTarget::Car, &[]);
+        let forward = [GameKey::MoveForward, GameKey::Accelerate];
+        assert_ne!(chassis_after(CameraTarget::Car, &forward), at_rest);
+        assert_eq!(chassis_after(CameraTarget::Free, &forward), at_rest);
+    }
+
+    #[test]
+    fn renderer_receives_the_visible_objects() {
+        let context = RenderContext::new(Rc::new(gl::tests::mock_gl())).unwrap();
+        let renderer = MockRenderer {
+            objects: Cell::new(None),
+        };
+        let scene = [named("terrain"), named("car")];
+        let debug = [named("x0_debug_arrow")];
+
+        let mut view = View::new(Camera::new(V4::zero(), V4::zero()));
+        for show_debug in [true, false] {
+            let visible = with_debug(scene.iter(), debug.iter(), show_debug);
+            gl_renderer::copy_objects(&mut view.objects, visible);
+            view.render(&renderer, &context).unwrap();
+            assert_eq!(renderer.objects.get(), Some(view.objects().len()));
+        }
+        assert_eq!(view.objects().len(), 2);
+    }
 }
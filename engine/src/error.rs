@@ -19,6 +19,7 @@ pub enum Error {
     InvalidDate,
     InvalidTime,
     InvalidMeshId,
+    InvalidVertexFormat,
     InvalidMaterialId,
     InvalidTextureId,
     InvalidShaderId,
@@ -0,0 +1,148 @@
+// Packs several small images into one atlas texture, so they share a texture
+// unit and can be drawn in a single call.
+use crate::error::{Error, Result};
+use crate::gfx::color_conversion::ImageRgb32;
+
+// ----------------------------------------------------------------------------
+// Pixel rectangle of one packed image inside the atlas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AtlasRect {
+    pub x: usize,
+    pub y: usize,
+    pub cx: usize,
+    pub cy: usize,
+}
+
+// ----------------------------------------------------------------------------
+impl AtlasRect {
+    pub fn overlaps(&self, other: &AtlasRect) -> bool {
+        self.x < other.x + other.cx
+            && other.x < self.x + self.cx
+            && self.y < other.y + other.cy
+            && other.y < self.y + self.cy
+    }
+
+    // ------------------------------------------------------------------------
+    // Texture coordinates as [u0, v0, u1, v1], like the font glyphs.
+    pub fn uv(&self, atlas_cx: usize, atlas_cy: usize) -> [f32; 4] {
+        let (w, h) = (atlas_cx as f32, atlas_cy as f32);
+        [
+            self.x as f32 / w,
+            self.y as f32 / h,
+            (self.x + self.cx) as f32 / w,
+            (self.y + self.cy) as f32 / h,
+        ]
+    }
+}
+
+// ----------------------------------------------------------------------------
+pub struct Atlas {
+    pub image: ImageRgb32,
+    pub uvs: Vec<[f32; 4]>,
+}
+
+// ----------------------------------------------------------------------------
+// Shelf packer: images are placed left to right in rows, tallest first, and
+// a new row starts when the current one is full.
+#[derive(Debug, Clone, Copy)]
+pub struct AtlasPacker {
+    cx: usize,
+    cy: usize,
+    padding: usize,
+}
+
+// ----------------------------------------------------------------------------
+impl AtlasPacker {
+    pub fn new(cx: usize, cy: usize) -> Self {
+        Self { cx, cy, padding: 1 }
+    }
+
+    // ------------------------------------------------------------------------
+    // Empty pixels kept between images so filtering does not bleed.
+    pub fn with_padding(self, padding: usize) -> Self {
+        Self { padding, ..self }
+    }
+
+    // ------------------------------------------------------------------------
+    // Rectangles in the order of `sizes`; fails if they do not all fit.
+    pub fn pack_rects(&self, sizes: &[(usize, usize)]) -> Result<Vec<AtlasRect>> {
+        let mut order: Vec<usize> = (0..sizes.len()).collect();
+        order.sort_by_key(|&i| std::cmp::Reverse(sizes[i].1));
+
+        let mut rects = vec![AtlasRect::default(); sizes.len()];
+        let (mut x, mut y, mut shelf_cy) = (0, 0, 0);
+        for i in order {
+            let (cx, cy) = sizes[i];
+            if x + cx > self.cx {
+                x = 0;
+                y += shelf_cy + self.padding;
+                shelf_cy = 0;
+            }
+            if x + cx > self.cx || y + cy > self.cy {
+                return Err(Error::InvalidTextureSize);
+            }
+            rects[i] = AtlasRect { x, y, cx, cy };
+            x += cx + self.padding;
+            shelf_cy = shelf_cy.max(cy);
+        }
+
+        Ok(rects)
+    }
+
+    // ------------------------------------------------------------------------
+    pub fn pack(&self, images: &[ImageRgb32]) -> Result<Atlas> {
+        let sizes: Vec<_> = images.iter().map(|i| (i.width(), i.height())).collect();
+        let rects = self.pack_rects(&sizes)?;
+
+        let mut atlas = ImageRgb32::new(self.cx, self.cy);
+        for (image, rect) in images.iter().zip(&rects) {
+            for (row, src) in image.rows().enumerate() {
+                let ofs = (rect.y + row) * atlas.stride + rect.x * 4;
+                atlas.data[ofs..ofs + src.len()].copy_from_slice(src);
+            }
+        }
+
+        Ok(Atlas {
+            image: atlas,
+            uvs: rects.iter().map(|r| r.uv(self.cx, self.cy)).collect(),
+        })
+    }
+}
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packed_rects_fit_and_do_not_overlap() {
+        let sizes = [(30, 10), (12, 40), (64, 8), (20, 20), (5, 5), (40, 30)];
+        let packer = AtlasPacker::new(64, 128);
+        let rects = packer.pack_rects(&sizes).unwrap();
+
+        for (i, (rect, size)) in rects.iter().zip(sizes).enumerate() {
+            assert_eq!((rect.cx, rect.cy), size);
+            assert!(rect.x + rect.cx <= 64 && rect.y + rect.cy <= 128);
+            for other in &rects[i + 1..] {
+                assert!(!rect.overlaps(other), "{rect:?} overlaps {other:?}");
+            }
+        }
+
+        assert!(packer.pack_rects(&[(65, 1)]).is_err());
+        assert!(packer.pack_rects(&[(64, 100), (64, 100)]).is_err());
+    }
+
+    #[test]
+    fn images_are_copied_to_their_uvs() {
+        let mut a = ImageRgb32::new(2, 2);
+        a.set_pixel(1, 1, [1, 2, 3, 4]);
+        let mut b = ImageRgb32::new(3, 1);
+        b.set_pixel(0, 0, [5, 6, 7, 8]);
+
+        let atlas = AtlasPacker::new(8, 4).pack(&[a, b]).unwrap();
+        assert_eq!(atlas.uvs[0], [0.0, 0.0, 0.25, 0.5]);
+        assert_eq!(atlas.uvs[1], [0.375, 0.0, 0.75, 0.25]);
+        assert_eq!(atlas.image.get_pixel(1, 1), [1, 2, 3, 4]);
+        assert_eq!(atlas.image.get_pixel(3, 0), [5, 6, 7, 8]);
+    }
+}
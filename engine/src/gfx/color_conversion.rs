@@ -14,30 +14,112 @@ pub struct Image {
     pub palette: Vec<u32>,
 }
 
+// ----------------------------------------------------------------------------
+impl Image {
+    // ------------------------------------------------------------------------
+    // Copy with each row padded to a multiple of `alignment` bytes (a power of
+    // 2), e.g. for uploads with GL_UNPACK_ALIGNMENT. The padding is zeroed.
+    pub fn to_aligned(&self, geo: &ImageGeometry, alignment: usize) -> Image {
+        let row_len = geo.cf.stride(geo.cx, 1);
+        let stride = geo.cf.stride(geo.cx, alignment);
+
+        let mut data = vec![0; make_buffersize(stride, geo.cy)];
+        for (dst, src) in data
+            .chunks_exact_mut(stride)
+            .zip(self.data.chunks(self.stride))
+        {
+            dst[..row_len].copy_from_slice(&src[..row_len]);
+        }
+
+        Image {
+            data,
+            stride,
+            palette: self.palette.clone(),
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Tightly packed 32 bit image with bytes in R, G, B, A order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageRgb32 {
+    pub data: Vec<u8>,
+    pub stride: usize,
+}
+
+// ----------------------------------------------------------------------------
+impl ImageRgb32 {
+    // ------------------------------------------------------------------------
+    pub fn new(cx: usize, cy: usize) -> Self {
+        Self {
+            data: vec![0; cx * cy * 4],
+            stride: cx * 4,
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    pub fn width(&self) -> usize {
+        self.stride / 4
+    }
+
+    // ------------------------------------------------------------------------
+    pub fn height(&self) -> usize {
+        self.data.len().checked_div(self.stride).unwrap_or(0)
+    }
+
+    // ------------------------------------------------------------------------
+    pub fn get_pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        let ofs = self.offset(x, y);
+        [
+            self.data[ofs],
+            self.data[ofs + 1],
+            self.data[ofs + 2],
+            self.data[ofs + 3],
+        ]
+    }
+
+    // ------------------------------------------------------------------------
+    pub fn set_pixel(&mut self, x: usize, y: usize, rgba: [u8; 4]) {
+        let ofs = self.offset(x, y);
+        self.data[ofs..ofs + 4].copy_from_slice(&rgba);
+    }
+
+    // ------------------------------------------------------------------------
+    pub fn rows(&self) -> impl Iterator<Item = &[u8]> {
+        self.data.chunks_exact(self.stride.max(1))
+    }
+
+    // ------------------------------------------------------------------------
+    fn offset(&self, x: usize, y: usize) -> usize {
+        assert!(
+            x < self.width() && y < self.height(),
+            "pixel ({x}, {y}) outside of {}x{} image",
+            self.width(),
+            self.height()
+        );
+        y * self.stride + x * 4
+    }
+}
+
+// ----------------------------------------------------------------------------
+fn palette_rgba(color: u32) -> [u8; 4] {
+    [(color >> 16) as u8, (color >> 8) as u8, color as u8, 255]
+}
+
 // ----------------------------------------------------------------------------
 pub fn make_buffersize(stride: usize, cy: usize) -> usize {
     stride * cy
 }
 
 // ----------------------------------------------------------------------------
-pub fn pal1_to_rgb32(pal1: Image, geo: &ImageGeometry) -> Image {
-    let mut rgb32 = Image {
-        data: vec![0; geo.cx * geo.cy * 4],
-        stride: geo.cx * 4,
-        palette: Vec::new(),
-    };
+pub fn pal1_to_rgb32(pal1: Image, geo: &ImageGeometry) -> ImageRgb32 {
+    let mut rgb32 = ImageRgb32::new(geo.cx, geo.cy);
 
     for y in 0..geo.cy {
         let src = &pal1.data[y * pal1.stride..(y + 1) * pal1.stride];
-        let dst = &mut rgb32.data[y * rgb32.stride..(y + 1) * rgb32.stride];
-
         for x in 0..geo.cx {
             let idx = (src[x / 8] >> (7 - (x & 7))) & 1;
-            let color = pal1.palette[idx as usize];
-            dst[x * 4] = (color >> 16) as u8;
-            dst[x * 4 + 1] = (color >> 8) as u8;
-            dst[x * 4 + 2] = color as u8;
-            dst[x * 4 + 3] = 255;
+            rgb32.set_pixel(x, y, palette_rgba(pal1.palette[idx as usize]));
         }
     }
 
@@ -45,24 +127,13 @@ pub fn pal1_to_rgb32(pal1: Image, geo: &ImageGeometry) -> Image {
 }
 
 // ----------------------------------------------------------------------------
-pub fn pal8_to_rgb32(pal8: Image, geo: &ImageGeometry) -> Image {
-    let mut rgb32 = Image {
-        data: vec![0; geo.cx * geo.cy * 4],
-        stride: geo.cx * 4,
-        palette: Vec::new(),
-    };
+pub fn pal8_to_rgb32(pal8: Image, geo: &ImageGeometry) -> ImageRgb32 {
+    let mut rgb32 = ImageRgb32::new(geo.cx, geo.cy);
 
     for y in 0..geo.cy {
         let src = &pal8.data[y * pal8.stride..(y + 1) * pal8.stride];
-        let dst = &mut rgb32.data[y * rgb32.stride..(y + 1) * rgb32.stride];
-
-        for x in 0..geo.cx {
-            let idx = src[x];
-            let color = pal8.palette[idx as usize];
-            dst[x * 4] = (color >> 16) as u8;
-            dst[x * 4 + 1] = (color >> 8) as u8;
-            dst[x * 4 + 2] = color as u8;
-            dst[x * 4 + 3] = 255;
+        for (x, idx) in src[..geo.cx].iter().enumerate() {
+            rgb32.set_pixel(x, y, palette_rgba(pal8.palette[*idx as usize]));
         }
     }
 
@@ -70,7 +141,96 @@ pub fn pal8_to_rgb32(pal8: Image, geo: &ImageGeometry) -> Image {
 }
 
 // ----------------------------------------------------------------------------
-pub fn ycbcr420_to_rgb24(ybuf: &[u8], ubuf: &[u8], vbuf: &[u8], geo: &ImageGeometry) -> Image {
+// Luma weights of the RGB primaries used to encode YCbCr.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum YuvMatrix {
+    #[default]
+    Bt601, // SD video, JPEG and WebP
+    Bt709, // HD video
+}
+
+// ----------------------------------------------------------------------------
+impl YuvMatrix {
+    // Factors for Cr to red, Cb and Cr to green and Cb to blue.
+    fn coefficients(self) -> [f32; 4] {
+        match self {
+            YuvMatrix::Bt601 => [1.402, 0.344136, 0.714136, 1.772],
+            YuvMatrix::Bt709 => [1.5748, 0.187324, 0.468124, 1.8556],
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Full range uses all of 0..=255, limited ("studio swing") range only 16..=235
+// for luma and 16..=240 for chroma.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum YuvRange {
+    #[default]
+    Full,
+    Limited,
+}
+
+// ----------------------------------------------------------------------------
+impl YuvRange {
+    // Luma in 0..=255 and chroma centered around 0.
+    fn expand(self, y: f32, u: f32, v: f32) -> (f32, f32, f32) {
+        let (u, v) = (u - 128.0, v - 128.0);
+        match self {
+            YuvRange::Full => (y, u, v),
+            YuvRange::Limited => (
+                (y - 16.0) * (255.0 / 219.0),
+                u * (255.0 / 224.0),
+                v * (255.0 / 224.0),
+            ),
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+// How the half resolution chroma planes are scaled up to the luma size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChromaUpsampling {
+    #[default]
+    Nearest, // fast, but color edges are blocky
+    Bilinear,
+}
+
+// ----------------------------------------------------------------------------
+// The two chroma samples nearest to luma sample `i` and their weights. Chroma
+// is sited between each pair of luma samples, so these are 1/4 and 3/4 of a
+// chroma sample away.
+fn chroma_taps(i: usize, n: usize) -> [(usize, f32); 2] {
+    let near = (i / 2).min(n - 1);
+    let far = if i.is_multiple_of(2) {
+        near.saturating_sub(1)
+    } else {
+        (near + 1).min(n - 1)
+    };
+    [(near, 0.75), (far, 0.25)]
+}
+
+// ----------------------------------------------------------------------------
+pub fn ycbcr420_to_rgb24(
+    ybuf: &[u8],
+    ubuf: &[u8],
+    vbuf: &[u8],
+    geo: &ImageGeometry,
+    matrix: YuvMatrix,
+    range: YuvRange,
+    upsampling: ChromaUpsampling,
+) -> Image {
+    let [r_v, g_u, g_v, b_u] = matrix.coefficients();
+    let (cw, ch) = (geo.cx / 2, geo.cy / 2);
+    let chroma = |plane: &[u8], x: usize, y: usize| match upsampling {
+        ChromaUpsampling::Nearest => f32::from(plane[(y / 2) * cw + x / 2]),
+        ChromaUpsampling::Bilinear => {
+            let (rows, cols) = (chroma_taps(y, ch), chroma_taps(x, cw));
+            rows.iter()
+                .flat_map(|&(r, wr)| cols.iter().map(move |&(c, wc)| (r * cw + c, wr * wc)))
+                .map(|(i, w)| w * f32::from(plane[i]))
+                .sum()
+        }
+    };
     let mut rgb = Image {
         data: vec![0; geo.cx * geo.cy * 3],
         stride: geo.cx * 3,
@@ -79,18 +239,15 @@ pub fn ycbcr420_to_rgb24(ybuf: &[u8], ubuf: &[u8], vbuf: &[u8], geo: &ImageGeome
 
     for y in 0..geo.cy {
         let ysrc = &ybuf[y * geo.cx..(y + 1) * geo.cx];
-        let usrc = &ubuf[(y / 2) * (geo.cx / 2)..(y / 2 + 1) * (geo.cx / 2)];
-        let vsrc = &vbuf[(y / 2) * (geo.cx / 2)..(y / 2 + 1) * (geo.cx / 2)];
         let dst = &mut rgb.data[y * rgb.stride..(y + 1) * rgb.stride];
 
         for x in 0..geo.cx {
-            let y = ysrc[x] as f32;
-            let u = usrc[x / 2] as f32 - 128.0;
-            let v = vsrc[x / 2] as f32 - 128.0;
+            let (u, v) = (chroma(ubuf, x, y), chroma(vbuf, x, y));
+            let (y, u, v) = range.expand(f32::from(ysrc[x]), u, v);
 
-            let r = (y + 1.402 * v).clamp(0.0, 255.0) as u8;
-            let g = (y - 0.344136 * u - 0.714136 * v).clamp(0.0, 255.0) as u8;
-            let b = (y + 1.772 * u).clamp(0.0, 255.0) as u8;
+            let r = (y + r_v * v).clamp(0.0, 255.0) as u8;
+            let g = (y - g_u * u - g_v * v).clamp(0.0, 255.0) as u8;
+            let b = (y + b_u * u).clamp(0.0, 255.0) as u8;
 
             dst[x * 3] = r;
             dst[x * 3 + 1] = g;
@@ -100,3 +257,190 @@ pub fn ycbcr420_to_rgb24(ybuf: &[u8], ubuf: &[u8], vbuf: &[u8], geo: &ImageGeome
 
     rgb
 }
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pixel_get_set() {
+        let mut img = ImageRgb32::new(3, 2);
+        assert_eq!(img.width(), 3);
+        assert_eq!(img.height(), 2);
+
+        img.set_pixel(2, 1, [1, 2, 3, 4]);
+        assert_eq!(img.get_pixel(2, 1), [1, 2, 3, 4]);
+        assert_eq!(img.get_pixel(1, 1), [0, 0, 0, 0]);
+        assert_eq!(&img.data[img.stride + 8..], [1, 2, 3, 4]);
+
+        let rows: Vec<_> = img.rows().collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1], [0, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    #[should_panic(expected = "outside of 3x2 image")]
+    fn pixel_out_of_range() {
+        let img = ImageRgb32::new(3, 2);
+        img.get_pixel(3, 0);
+    }
+
+    // Converts a 2x2 image of a single YCbCr color.
+    fn ycbcr_pixel(yuv: [u8; 3], matrix: YuvMatrix, range: YuvRange) -> [u8; 3] {
+        let geo = ImageGeometry {
+            cx: 2,
+            cy: 2,
+            cf: ColorFormat::YCbCr420,
+        };
+        let rgb = ycbcr420_to_rgb24(
+            &[yuv[0]; 4],
+            &[yuv[1]],
+            &[yuv[2]],
+            &geo,
+            matrix,
+            range,
+            ChromaUpsampling::Nearest,
+        );
+        assert!(rgb.data.chunks(3).all(|px| px == &rgb.data[..3]));
+        [rgb.data[0], rgb.data[1], rgb.data[2]]
+    }
+
+    // Allows for rounding of the 8 bit YCbCr inputs
+    fn assert_rgb_near(actual: [u8; 3], expected: [u8; 3]) {
+        let near = actual.iter().zip(expected).all(|(a, e)| a.abs_diff(e) <= 2);
+        assert!(near, "{actual:?} != {expected:?}");
+    }
+
+    #[test]
+    fn ycbcr_matrices_and_ranges() {
+        use YuvMatrix::*;
+        use YuvRange::*;
+        // Pure red as encoded by each matrix and range
+        assert_rgb_near(ycbcr_pixel([76, 85, 255], Bt601, Full), [255, 0, 0]);
+        assert_rgb_near(ycbcr_pixel([81, 90, 240], Bt601, Limited), [255, 0, 0]);
+        assert_rgb_near(ycbcr_pixel([54, 99, 255], Bt709, Full), [255, 0, 0]);
+        assert_rgb_near(ycbcr_pixel([63, 102, 240], Bt709, Limited), [255, 0, 0]);
+
+        // Decoding with the wrong matrix shifts the hue
+        assert_rgb_near(ycbcr_pixel([81, 90, 240], Bt709, Limited), [255, 24, 0]);
+    }
+
+    #[test]
+    fn limited_range_is_stretched() {
+        for matrix in [YuvMatrix::Bt601, YuvMatrix::Bt709] {
+            assert_rgb_near(
+                ycbcr_pixel([16, 128, 128], matrix, YuvRange::Limited),
+                [0; 3],
+            );
+            assert_rgb_near(
+                ycbcr_pixel([235, 128, 128], matrix, YuvRange::Limited),
+                [255; 3],
+            );
+            assert_rgb_near(
+                ycbcr_pixel([235, 128, 128], matrix, YuvRange::Full),
+                [235; 3],
+            );
+        }
+    }
+
+    // Blue channel of the first row of an 8x2 image whose Cb rises to the
+    // right.
+    fn cb_gradient_row(upsampling: ChromaUpsampling) -> Vec<u8> {
+        let geo = ImageGeometry {
+            cx: 8,
+            cy: 2,
+            cf: ColorFormat::YCbCr420,
+        };
+        let (ybuf, ubuf, vbuf) = ([128; 16], [108, 118, 128, 138], [128; 4]);
+        let rgb = ycbcr420_to_rgb24(
+            &ybuf,
+            &ubuf,
+            &vbuf,
+            &geo,
+            YuvMatrix::Bt601,
+            YuvRange::Full,
+            upsampling,
+        );
+        rgb.data[..rgb.stride].chunks(3).map(|px| px[2]).collect()
+    }
+
+    #[test]
+    fn bilinear_chroma_is_smoother() {
+        let nearest = cb_gradient_row(ChromaUpsampling::Nearest);
+        let bilinear = cb_gradient_row(ChromaUpsampling::Bilinear);
+
+        let distinct = |row: &[u8]| {
+            let mut row = row.to_vec();
+            row.dedup();
+            row.len()
+        };
+        assert_eq!(distinct(&nearest), 4, "{nearest:?}");
+        assert_eq!(distinct(&bilinear), 8, "{bilinear:?}");
+        assert!(bilinear.is_sorted(), "{bilinear:?}");
+
+        // Edges are clamped, the largest step is halved
+        assert_eq!((bilinear[0], bilinear[7]), (nearest[0], nearest[7]));
+        let max_step = |row: &[u8]| row.windows(2).map(|w| w[1] - w[0]).max().unwrap();
+        assert!(2 * max_step(&bilinear) <= max_step(&nearest) + 1);
+    }
+
+    #[test]
+    fn rows_are_padded_to_alignment() {
+        let geo = ImageGeometry {
+            cx: 5,
+            cy: 2,
+            cf: ColorFormat::RGB8888,
+        };
+        let image = Image {
+            data: (0..40).collect(),
+            stride: 20,
+            palette: Vec::new(),
+        };
+
+        // 4 pixel alignment
+        let aligned = image.to_aligned(&geo, 16);
+        assert_eq!(aligned.stride, 32);
+        assert_eq!(aligned.data.len(), 64);
+        for y in 0..2 {
+            let row = &aligned.data[y * 32..(y + 1) * 32];
+            assert_eq!(row[..20], image.data[y * 20..(y + 1) * 20]);
+            assert_eq!(row[20..], [0; 12]);
+        }
+
+        // Rows of 20 bytes are already 4 byte aligned
+        assert_eq!(image.to_aligned(&geo, 4).data, image.data);
+    }
+
+    #[test]
+    fn palette_to_rgb32() {
+        let geo = ImageGeometry {
+            cx: 10,
+            cy: 1,
+            cf: ColorFormat::PAL1,
+        };
+        let pal1 = Image {
+            data: vec![0b1000_0000, 0b0100_0000],
+            stride: 2,
+            palette: vec![0x000000, 0x112233],
+        };
+        let rgb32 = pal1_to_rgb32(pal1, &geo);
+        assert_eq!(rgb32.get_pixel(0, 0), [0x11, 0x22, 0x33, 255]);
+        assert_eq!(rgb32.get_pixel(1, 0), [0, 0, 0, 255]);
+        assert_eq!(rgb32.get_pixel(9, 0), [0x11, 0x22, 0x33, 255]);
+
+        let geo = ImageGeometry {
+            cx: 2,
+            cy: 2,
+            cf: ColorFormat::PAL8,
+        };
+        let pal8 = Image {
+            data: vec![0, 1, 1, 0],
+            stride: 2,
+            palette: vec![0xff0000, 0x00ff00],
+        };
+        let rgb32 = pal8_to_rgb32(pal8, &geo);
+        assert_eq!(rgb32.get_pixel(0, 1), [0, 255, 0, 255]);
+        assert_eq!(rgb32.get_pixel(1, 1), [255, 0, 0, 255]);
+    }
+}
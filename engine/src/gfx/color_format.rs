@@ -1,3 +1,5 @@
+use std::fmt;
+
 // ----------------------------------------------------------------------------
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ColorFormat {
@@ -39,6 +41,73 @@ impl ColorFormat {
         )
     }
 
+    // ------------------------------------------------------------------------
+    pub fn has_alpha(&self) -> bool {
+        matches!(
+            self,
+            ColorFormat::YA8
+                | ColorFormat::YA16
+                | ColorFormat::RGB4444
+                | ColorFormat::RGB1555
+                | ColorFormat::RGB8888
+                | ColorFormat::BGR8888
+                | ColorFormat::RGBgggg
+        )
+    }
+
+    // ------------------------------------------------------------------------
+    // Channels stored per pixel, alpha included. Paletted formats store a
+    // single index.
+    pub fn channels(&self) -> usize {
+        match self {
+            ColorFormat::Y1
+            | ColorFormat::Y2
+            | ColorFormat::Y4
+            | ColorFormat::Y8
+            | ColorFormat::Y16
+            | ColorFormat::PAL1
+            | ColorFormat::PAL2
+            | ColorFormat::PAL4
+            | ColorFormat::PAL8 => 1,
+            ColorFormat::YA8 | ColorFormat::YA16 => 2,
+            ColorFormat::RGB0555
+            | ColorFormat::RGB0565
+            | ColorFormat::RGB0888
+            | ColorFormat::BGR0888
+            | ColorFormat::RGB0ggg
+            | ColorFormat::YCbCr420 => 3,
+            ColorFormat::RGB4444
+            | ColorFormat::RGB1555
+            | ColorFormat::RGB8888
+            | ColorFormat::BGR8888
+            | ColorFormat::RGBgggg => 4,
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // Bits of the widest channel, e.g. 6 for the green of RGB0565. Sample
+    // values fit into this many bits.
+    pub fn bits_per_channel(&self) -> usize {
+        match self {
+            ColorFormat::Y1 | ColorFormat::PAL1 => 1,
+            ColorFormat::Y2 | ColorFormat::PAL2 => 2,
+            ColorFormat::Y4 | ColorFormat::PAL4 | ColorFormat::RGB4444 => 4,
+            ColorFormat::RGB0555 | ColorFormat::RGB1555 => 5,
+            ColorFormat::RGB0565 => 6,
+            ColorFormat::Y8
+            | ColorFormat::PAL8
+            | ColorFormat::YA8
+            | ColorFormat::RGB0888
+            | ColorFormat::RGB8888
+            | ColorFormat::BGR0888
+            | ColorFormat::BGR8888
+            | ColorFormat::YCbCr420 => 8,
+            ColorFormat::Y16 | ColorFormat::YA16 | ColorFormat::RGB0ggg | ColorFormat::RGBgggg => {
+                16
+            }
+        }
+    }
+
     // ------------------------------------------------------------------------
     pub fn bpp(&self) -> usize {
         match self {
@@ -61,9 +130,169 @@ impl ColorFormat {
     }
 
     // ------------------------------------------------------------------------
+    // Bytes per pixel rounded up, i.e. 1 for sub-byte formats. This is the
+    // distance to the corresponding byte of the previous pixel used by PNG
+    // filters.
+    pub fn bytes_per_pixel(&self) -> usize {
+        self.bpp().div_ceil(8)
+    }
+
+    // ------------------------------------------------------------------------
+    // Bytes per row of `cx` pixels: packed pixels are rounded up to whole
+    // bytes, then the row is padded to `alignment` (a power of 2).
     pub fn stride(&self, cx: usize, alignment: usize) -> usize {
         assert!((alignment & (alignment - 1)) == 0); // Make sure alignment is a power of 2
         let mask = !(alignment - 1);
         ((cx * self.bpp()).div_ceil(8) + alignment - 1) & mask
     }
 }
+
+// ----------------------------------------------------------------------------
+impl fmt::Display for ColorFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ColorFormat::Y1 => "1 bit grayscale",
+            ColorFormat::Y2 => "2 bit grayscale",
+            ColorFormat::Y4 => "4 bit grayscale",
+            ColorFormat::Y8 => "8 bit grayscale",
+            ColorFormat::Y16 => "16 bit grayscale",
+            ColorFormat::YA8 => "8 bit grayscale with alpha",
+            ColorFormat::YA16 => "16 bit grayscale with alpha",
+            ColorFormat::PAL1 => "1 bit paletted",
+            ColorFormat::PAL2 => "2 bit paletted",
+            ColorFormat::PAL4 => "4 bit paletted",
+            ColorFormat::PAL8 => "8 bit paletted",
+            ColorFormat::RGB4444 => "ARGB 4:4:4:4",
+            ColorFormat::RGB0555 => "RGB 5:5:5",
+            ColorFormat::RGB0565 => "RGB 5:6:5",
+            ColorFormat::RGB1555 => "ARGB 1:5:5:5",
+            ColorFormat::RGB0888 => "RGB 8:8:8",
+            ColorFormat::RGB8888 => "ARGB 8:8:8:8",
+            ColorFormat::BGR0888 => "BGR 8:8:8",
+            ColorFormat::BGR8888 => "BGRA 8:8:8:8",
+            ColorFormat::RGB0ggg => "RGB 16:16:16",
+            ColorFormat::RGBgggg => "ARGB 16:16:16:16",
+            ColorFormat::YCbCr420 => "YCbCr 4:2:0",
+        };
+        f.write_str(name)
+    }
+}
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [ColorFormat; 22] = [
+        ColorFormat::Y1,
+        ColorFormat::Y2,
+        ColorFormat::Y4,
+        ColorFormat::Y8,
+        ColorFormat::Y16,
+        ColorFormat::YA8,
+        ColorFormat::YA16,
+        ColorFormat::PAL1,
+        ColorFormat::PAL2,
+        ColorFormat::PAL4,
+        ColorFormat::PAL8,
+        ColorFormat::RGB4444,
+        ColorFormat::RGB0555,
+        ColorFormat::RGB0565,
+        ColorFormat::RGB1555,
+        ColorFormat::RGB0888,
+        ColorFormat::RGB8888,
+        ColorFormat::BGR0888,
+        ColorFormat::BGR8888,
+        ColorFormat::RGB0ggg,
+        ColorFormat::RGBgggg,
+        ColorFormat::YCbCr420,
+    ];
+
+    #[test]
+    fn channel_layout() {
+        use ColorFormat::*;
+        let expected = [
+            (Y1, 1, 1, false),
+            (Y16, 1, 16, false),
+            (YA8, 2, 8, true),
+            (PAL4, 1, 4, false),
+            (RGB0565, 3, 6, false),
+            (RGB1555, 4, 5, true),
+            (RGB0888, 3, 8, false),
+            (BGR8888, 4, 8, true),
+            (RGBgggg, 4, 16, true),
+            (YCbCr420, 3, 8, false),
+        ];
+        for (cf, channels, bits, alpha) in expected {
+            assert_eq!(cf.channels(), channels, "{cf:?}");
+            assert_eq!(cf.bits_per_channel(), bits, "{cf:?}");
+            assert_eq!(cf.has_alpha(), alpha, "{cf:?}");
+        }
+    }
+
+    #[test]
+    fn display_names() {
+        assert_eq!(ColorFormat::PAL8.to_string(), "8 bit paletted");
+        assert_eq!(ColorFormat::RGB0565.to_string(), "RGB 5:6:5");
+        assert_eq!(ColorFormat::YCbCr420.to_string(), "YCbCr 4:2:0");
+    }
+
+    #[test]
+    fn bytes_per_pixel() {
+        use ColorFormat::*;
+        for cf in [Y1, Y2, Y4, Y8, PAL1, PAL2, PAL4, PAL8] {
+            assert_eq!(cf.bytes_per_pixel(), 1, "{cf:?}");
+        }
+        for cf in [Y16, YA8, RGB4444, RGB0555, RGB0565, RGB1555] {
+            assert_eq!(cf.bytes_per_pixel(), 2, "{cf:?}");
+        }
+        assert_eq!(RGB0888.bytes_per_pixel(), 3);
+        assert_eq!(BGR0888.bytes_per_pixel(), 3);
+        assert_eq!(RGB8888.bytes_per_pixel(), 4);
+        assert_eq!(BGR8888.bytes_per_pixel(), 4);
+        assert_eq!(YA16.bytes_per_pixel(), 4);
+        assert_eq!(RGB0ggg.bytes_per_pixel(), 6);
+        assert_eq!(RGBgggg.bytes_per_pixel(), 8);
+    }
+
+    #[test]
+    fn packed_strides_round_up_to_bytes() {
+        use ColorFormat::*;
+        // 13 pixels: 13, 26, 52 bits
+        assert_eq!(Y1.stride(13, 1), 2);
+        assert_eq!(PAL2.stride(13, 1), 4);
+        assert_eq!(Y4.stride(13, 1), 7);
+        assert_eq!(PAL8.stride(13, 1), 13);
+
+        assert_eq!(Y1.stride(1, 1), 1);
+        assert_eq!(Y1.stride(8, 1), 1);
+        assert_eq!(Y1.stride(9, 1), 2);
+    }
+
+    #[test]
+    fn wide_strides() {
+        use ColorFormat::*;
+        assert_eq!(Y16.stride(7, 1), 14);
+        assert_eq!(RGB0888.stride(7, 1), 21);
+        assert_eq!(RGB8888.stride(7, 1), 28);
+        assert_eq!(RGB0ggg.stride(7, 1), 42);
+        assert_eq!(RGBgggg.stride(7, 1), 56);
+    }
+
+    #[test]
+    fn strides_are_aligned() {
+        for cf in ALL {
+            for cx in [0, 1, 3, 7, 13, 31, 33, 1023] {
+                let packed = (cx * cf.bpp()).div_ceil(8);
+                assert_eq!(cf.stride(cx, 1), packed, "{cf:?} x {cx}");
+                for alignment in [2, 4, 8] {
+                    let stride = cf.stride(cx, alignment);
+                    assert_eq!(stride % alignment, 0, "{cf:?} x {cx}");
+                    assert!(stride >= packed && stride < packed + alignment);
+                }
+            }
+        }
+        assert_eq!(ColorFormat::RGB0888.stride(3, 4), 12);
+        assert_eq!(ColorFormat::RGB0888.stride(5, 4), 16);
+    }
+}
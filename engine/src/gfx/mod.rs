@@ -1,2 +1,3 @@
+pub mod atlas;
 pub mod color_conversion;
 pub mod color_format;
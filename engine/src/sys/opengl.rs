@@ -131,6 +131,10 @@ pub const TEXTURE_WRAP_R: GLenum = 0x8072;
 pub const TEXTURE_WRAP_S: GLenum = 0x2802;
 pub const TEXTURE_WRAP_T: GLenum = 0x2803;
 pub const CLAMP_TO_EDGE: GLint = 0x812F;
+pub const REPEAT: GLint = 0x2901;
+
+pub const TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FE;
+pub const MAX_TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FF;
 
 pub const TEXTURE0: GLenum = 0x84C0;
 pub const TEXTURE1: GLenum = 0x84C1;
@@ -228,6 +232,7 @@ pub type FnTexParameteri = unsafe fn(GLenum, GLenum, GLint);
 pub type FnTexParameteriv = unsafe fn(GLenum, GLenum, *const GLint);
 
 pub type FnActiveTexture = unsafe extern "system" fn(GLenum);
+pub type FnGenerateMipmap = unsafe extern "system" fn(GLenum);
 
 pub type FnCreateProgram = unsafe extern "system" fn() -> GLuint;
 pub type FnDeleteProgram = unsafe extern "system" fn(GLuint);
@@ -331,6 +336,7 @@ pub struct OpenGlFunctions {
     fnTexParameteriv: FnTexParameteriv,
 
     fnActiveTexture: FnActiveTexture,
+    fnGenerateMipmap: FnGenerateMipmap,
 
     fnCreateProgram: FnCreateProgram,
     fnDeleteProgram: FnDeleteProgram,
@@ -469,6 +475,7 @@ impl OpenGlFunctions {
             fnTexParameteriv: load_gl_fn!(load_fn, "glTexParameteriv\0" => FnTexParameteriv)?,
 
             fnActiveTexture: load_gl_fn!(load_fn, "glActiveTexture\0" => FnActiveTexture)?,
+            fnGenerateMipmap: load_gl_fn!(load_fn, "glGenerateMipmap\0" => FnGenerateMipmap)?,
 
             fnCreateProgram: load_gl_fn!(load_fn, "glCreateProgram\0" => FnCreateProgram)?,
             fnDeleteProgram: load_gl_fn!(load_fn, "glDeleteProgram\0" => FnDeleteProgram)?,
@@ -572,6 +579,7 @@ impl OpenGlFunctions {
     impl_gl_fn!(fnTexParameteriv, TexParameteriv(target: GLenum, pname: GLenum, params: *const GLint));
 
     impl_gl_fn!(fnActiveTexture, ActiveTexture(texture: GLenum));
+    impl_gl_fn!(fnGenerateMipmap, GenerateMipmap(target: GLenum));
 
     impl_gl_fn!(fnCreateProgram, CreateProgram() -> GLuint);
     impl_gl_fn!(fnDeleteProgram, DeleteProgram(program: GLuint));
@@ -636,3 +644,407 @@ impl OpenGlFunctions {
 
     impl_gl_fn!(fnSwapIntervalEXT, SwapIntervalEXT(interval: i32) -> i32);
 }
+
+// ----------------------------------------------------------------------------
+// Also built with the `mock-gl` feature, for headless tests in other crates.
+#[cfg(any(test, feature = "mock-gl"))]
+pub mod tests {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+
+    thread_local! {
+        static DELETED_BUFFERS: Cell<usize> = const { Cell::new(0) };
+        static DELETED_VERTEX_ARRAYS: Cell<usize> = const { Cell::new(0) };
+        static DELETED_TEXTURES: Cell<usize> = const { Cell::new(0) };
+        static TEX_IMAGE_FORMATS: Cell<Option<(GLint, GLenum, GLenum)>> = const { Cell::new(None) };
+        static TEX_PARAMETERS_I: RefCell<Vec<(GLenum, GLint)>> = const { RefCell::new(Vec::new()) };
+        static TEX_PARAMETERS_F: RefCell<Vec<(GLenum, GLfloat)>> = const { RefCell::new(Vec::new()) };
+        static GENERATED_MIPMAPS: Cell<usize> = const { Cell::new(0) };
+        static UNIFORMS_3F: RefCell<Vec<(GLint, [GLfloat; 3])>> = const { RefCell::new(Vec::new()) };
+        static DRAW_CALLS: Cell<usize> = const { Cell::new(0) };
+        static DRAW_MODES: RefCell<Vec<GLenum>> = const { RefCell::new(Vec::new()) };
+        static CLEAR_COLOR: Cell<Option<[GLfloat; 4]>> = const { Cell::new(None) };
+        static VIEWPORT: Cell<Option<[GLint; 4]>> = const { Cell::new(None) };
+        static PROGRAM: Cell<GLuint> = const { Cell::new(0) };
+        static PROGRAM_BINDS: Cell<usize> = const { Cell::new(0) };
+        static UNIFORMS_1F: RefCell<Vec<(GLint, GLfloat)>> = const { RefCell::new(Vec::new()) };
+        static UNIFORMS_1I: RefCell<Vec<(GLint, GLint)>> = const { RefCell::new(Vec::new()) };
+        static UNIFORM_LOCATIONS: RefCell<Vec<GLint>> = const { RefCell::new(Vec::new()) };
+    }
+
+    unsafe extern "system" fn mock_unimplemented() {
+        panic!("mock OpenGL function not implemented");
+    }
+
+    unsafe extern "system" fn mock_delete_buffers(n: GLsizei, _buffers: *const GLuint) {
+        DELETED_BUFFERS.with(|c| c.set(c.get() + n as usize));
+    }
+
+    unsafe extern "system" fn mock_delete_vertex_arrays(n: GLsizei, _arrays: *const GLuint) {
+        DELETED_VERTEX_ARRAYS.with(|c| c.set(c.get() + n as usize));
+    }
+
+    unsafe fn mock_delete_textures(n: GLsizei, _textures: *const GLuint) {
+        DELETED_TEXTURES.with(|c| c.set(c.get() + n as usize));
+    }
+
+    unsafe extern "system" fn mock_get_error() -> GLenum {
+        0
+    }
+
+    unsafe extern "system" fn mock_get_string(_name: GLenum) -> *const GLubyte {
+        c"mock".as_ptr().cast()
+    }
+
+    unsafe extern "system" fn mock_get_integerv(_pname: GLenum, data: *mut GLint) {
+        unsafe { *data = 0 };
+    }
+
+    unsafe fn mock_gen_textures(n: GLsizei, textures: *mut GLuint) {
+        for i in 0..n as usize {
+            unsafe { *textures.add(i) = i as GLuint + 1 };
+        }
+    }
+
+    unsafe fn mock_bind_texture(_target: GLenum, _texture: GLuint) {}
+
+    unsafe fn mock_tex_parameteri(_target: GLenum, pname: GLenum, param: GLint) {
+        TEX_PARAMETERS_I.with(|c| c.borrow_mut().push((pname, param)));
+    }
+
+    unsafe fn mock_tex_parameterf(_target: GLenum, pname: GLenum, param: GLfloat) {
+        TEX_PARAMETERS_F.with(|c| c.borrow_mut().push((pname, param)));
+    }
+
+    unsafe extern "system" fn mock_generate_mipmap(_target: GLenum) {
+        GENERATED_MIPMAPS.with(|c| c.set(c.get() + 1));
+    }
+
+    // Pretends to support 16x anisotropic filtering.
+    unsafe extern "system" fn mock_get_floatv(pname: GLenum, data: *mut GLfloat) {
+        if pname == MAX_TEXTURE_MAX_ANISOTROPY {
+            unsafe { *data = 16.0 };
+        }
+    }
+
+    unsafe fn mock_clear_color(r: GLfloat, g: GLfloat, b: GLfloat, a: GLfloat) {
+        CLEAR_COLOR.with(|c| c.set(Some([r, g, b, a])));
+    }
+
+    unsafe fn mock_clear(_mask: GLbitfield) {}
+
+    unsafe fn mock_viewport(x: GLint, y: GLint, width: GLsizei, height: GLsizei) {
+        VIEWPORT.with(|c| c.set(Some([x, y, width, height])));
+    }
+
+    unsafe fn mock_enable(_cap: GLenum) {}
+
+    unsafe fn mock_disable(_cap: GLenum) {}
+
+    unsafe extern "system" fn mock_active_texture(_texture: GLenum) {}
+
+    unsafe extern "system" fn mock_bind_framebuffer(_target: GLenum, _framebuffer: GLuint) {}
+
+    unsafe extern "system" fn mock_framebuffer_texture_2d(
+        _target: GLenum,
+        _attachment: GLenum,
+        _textarget: GLenum,
+        _texture: GLuint,
+        _level: GLint,
+    ) {
+    }
+
+    unsafe extern "system" fn mock_draw_buffers(_n: GLsizei, _bufs: *const GLenum) {}
+
+    // Every framebuffer is complete.
+    unsafe extern "system" fn mock_check_framebuffer_status(_target: GLenum) -> GLenum {
+        FRAMEBUFFER_COMPLETE
+    }
+
+    unsafe extern "system" fn mock_use_program(program: GLuint) {
+        PROGRAM.with(|c| c.set(program));
+        PROGRAM_BINDS.with(|c| c.set(c.get() + 1));
+    }
+
+    unsafe extern "system" fn mock_delete_program(_program: GLuint) {}
+
+    unsafe extern "system" fn mock_create_object() -> GLuint {
+        1
+    }
+
+    unsafe extern "system" fn mock_create_shader(_shader_type: GLenum) -> GLuint {
+        1
+    }
+
+    unsafe extern "system" fn mock_shader_source(
+        _shader: GLuint,
+        _count: GLsizei,
+        _string: *const *const GLchar,
+        _length: *const GLint,
+    ) {
+    }
+
+    unsafe extern "system" fn mock_object_op(_object: GLuint) {}
+
+    unsafe extern "system" fn mock_attach_shader(_program: GLuint, _shader: GLuint) {}
+
+    // Every shader compiles and every program links.
+    unsafe extern "system" fn mock_get_objectiv(_object: GLuint, _pname: GLenum, params: *mut GLint) {
+        unsafe { *params = 1 };
+    }
+
+    unsafe extern "system" fn mock_bind_vertex_array(_array: GLuint) {}
+
+    fn record_uniform(location: GLint) {
+        UNIFORM_LOCATIONS.with(|c| c.borrow_mut().push(location));
+    }
+
+    // Uniforms named `missing*` are not found, all others are at location 0.
+    unsafe extern "system" fn mock_get_uniform_location(_program: GLuint, name: *const GLchar) -> GLint {
+        let name = unsafe { std::ffi::CStr::from_ptr(name) };
+        if name.to_bytes().starts_with(b"missing") { -1 } else { 0 }
+    }
+
+    unsafe extern "system" fn mock_uniform_1i(location: GLint, v0: GLint) {
+        record_uniform(location);
+        UNIFORMS_1I.with(|c| c.borrow_mut().push((location, v0)));
+    }
+
+    unsafe extern "system" fn mock_uniform_1f(location: GLint, v0: GLfloat) {
+        record_uniform(location);
+        UNIFORMS_1F.with(|c| c.borrow_mut().push((location, v0)));
+    }
+
+    unsafe extern "system" fn mock_uniform_3fv(location: GLint, count: GLsizei, value: *const GLfloat) {
+        record_uniform(location);
+        let values = unsafe { std::slice::from_raw_parts(value, 3 * count as usize) };
+        UNIFORMS_3F.with(|c| {
+            let mut c = c.borrow_mut();
+            c.extend(values.chunks_exact(3).map(|v| (location, [v[0], v[1], v[2]])));
+        });
+    }
+
+    unsafe extern "system" fn mock_uniform_matrix_4fv(
+        location: GLint,
+        _count: GLsizei,
+        _transpose: GLboolean,
+        _value: *const GLfloat,
+    ) {
+        record_uniform(location);
+    }
+
+    unsafe extern "system" fn mock_uniform_matrix_3fv(
+        location: GLint,
+        _count: GLsizei,
+        _transpose: GLboolean,
+        _value: *const GLfloat,
+    ) {
+        record_uniform(location);
+    }
+
+    unsafe fn mock_polygon_mode(_face: GLenum, _mode: GLenum) {}
+
+    unsafe extern "system" fn mock_draw_arrays(mode: GLenum, _first: GLint, _count: GLsizei) {
+        DRAW_CALLS.with(|c| c.set(c.get() + 1));
+        DRAW_MODES.with(|c| c.borrow_mut().push(mode));
+    }
+
+    unsafe extern "system" fn mock_draw_elements(
+        mode: GLenum,
+        _count: GLsizei,
+        _ty: GLenum,
+        _indices: *const GLvoid,
+    ) {
+        DRAW_CALLS.with(|c| c.set(c.get() + 1));
+        DRAW_MODES.with(|c| c.borrow_mut().push(mode));
+    }
+
+    unsafe extern "system" fn mock_gen_names(n: GLsizei, names: *mut GLuint) {
+        for i in 0..n as usize {
+            unsafe { *names.add(i) = i as GLuint + 1 };
+        }
+    }
+
+    unsafe extern "system" fn mock_bind_buffer(_target: GLenum, _buffer: GLuint) {}
+
+    unsafe extern "system" fn mock_buffer_data(
+        _target: GLenum,
+        _size: usize,
+        _data: *const GLvoid,
+        _usage: GLenum,
+    ) {
+    }
+
+    unsafe extern "system" fn mock_enable_vertex_attrib_array(_index: GLuint) {}
+
+    unsafe extern "system" fn mock_vertex_attrib_pointer(
+        _index: GLuint,
+        _size: GLint,
+        _ty: GLenum,
+        _normalized: GLboolean,
+        _stride: GLsizei,
+        _pointer: *const GLvoid,
+    ) {
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn mock_tex_image_2d(
+        _target: GLenum,
+        _level: GLint,
+        internal: GLint,
+        _width: GLsizei,
+        _height: GLsizei,
+        _border: GLint,
+        format: GLenum,
+        ty: GLenum,
+        _pixels: *const GLvoid,
+    ) {
+        TEX_IMAGE_FORMATS.with(|c| c.set(Some((internal, format, ty))));
+    }
+
+    // Function table that only records object deletions, texture uploads,
+    // int, float and vec3 uniforms, the clear color, the bound program and
+    // draw calls. Buffer and vertex array uploads are accepted and ignored,
+    // every shader compiles and links and every framebuffer is complete.
+    // Counters are per thread, so tests running in parallel don't interfere
+    // with each other.
+    pub fn mock_gl() -> OpenGlFunctions {
+        OpenGlFunctions::load(|name| {
+            let f = match name {
+                "glDeleteBuffers\0" => mock_delete_buffers as FnDeleteBuffers as FnOpenGL,
+                "glDeleteVertexArrays\0" => mock_delete_vertex_arrays as FnDeleteVertexArrays as FnOpenGL,
+                "glDeleteTextures\0" => mock_delete_textures as FnDeleteTextures as FnOpenGL,
+                "glGetError\0" => mock_get_error as FnGetError as FnOpenGL,
+                "glGetString\0" => mock_get_string as FnGetString as FnOpenGL,
+                "glGetIntegerv\0" => mock_get_integerv as FnGetIntegerv as FnOpenGL,
+                "glGenTextures\0" => mock_gen_textures as FnGenTextures as FnOpenGL,
+                "glBindTexture\0" => mock_bind_texture as FnBindTexture as FnOpenGL,
+                "glTexParameteri\0" => mock_tex_parameteri as FnTexParameteri as FnOpenGL,
+                "glTexParameterf\0" => mock_tex_parameterf as FnTexParameterf as FnOpenGL,
+                "glGenerateMipmap\0" => mock_generate_mipmap as FnGenerateMipmap as FnOpenGL,
+                "glGetFloatv\0" => mock_get_floatv as FnGetFloatv as FnOpenGL,
+                "glTexImage2D\0" => mock_tex_image_2d as FnTexImage2D as FnOpenGL,
+                "glClearColor\0" => mock_clear_color as FnClearColor as FnOpenGL,
+                "glClear\0" => mock_clear as FnClear as FnOpenGL,
+                "glViewport\0" => mock_viewport as FnViewport as FnOpenGL,
+                "glEnable\0" => mock_enable as FnEnable as FnOpenGL,
+                "glDisable\0" => mock_disable as FnDisable as FnOpenGL,
+                "glActiveTexture\0" => mock_active_texture as FnActiveTexture as FnOpenGL,
+                "glBindFramebuffer\0" => mock_bind_framebuffer as FnBindFramebuffer as FnOpenGL,
+                "glGenFramebuffers\0" => mock_gen_names as FnGenFramebuffers as FnOpenGL,
+                "glFramebufferTexture2D\0" => mock_framebuffer_texture_2d as FnFramebufferTexture2D as FnOpenGL,
+                "glDrawBuffers\0" => mock_draw_buffers as FnDrawBuffers as FnOpenGL,
+                "glCheckFramebufferStatus\0" => mock_check_framebuffer_status as FnCheckFramebufferStatus as FnOpenGL,
+                "glUseProgram\0" => mock_use_program as FnUseProgram as FnOpenGL,
+                "glDeleteProgram\0" => mock_delete_program as FnDeleteProgram as FnOpenGL,
+                "glCreateShader\0" => mock_create_shader as FnCreateShader as FnOpenGL,
+                "glShaderSource\0" => mock_shader_source as FnShaderSource as FnOpenGL,
+                "glCompileShader\0" => mock_object_op as FnCompileShader as FnOpenGL,
+                "glGetShaderiv\0" => mock_get_objectiv as FnGetShaderiv as FnOpenGL,
+                "glDeleteShader\0" => mock_object_op as FnDeleteShader as FnOpenGL,
+                "glCreateProgram\0" => mock_create_object as FnCreateProgram as FnOpenGL,
+                "glAttachShader\0" => mock_attach_shader as FnAttachShader as FnOpenGL,
+                "glLinkProgram\0" => mock_object_op as FnLinkProgram as FnOpenGL,
+                "glGetProgramiv\0" => mock_get_objectiv as FnGetProgramiv as FnOpenGL,
+                "glBindVertexArray\0" => mock_bind_vertex_array as FnBindVertexArray as FnOpenGL,
+                "glGetUniformLocation\0" => mock_get_uniform_location as FnGetUniformLocation as FnOpenGL,
+                "glUniform1i\0" => mock_uniform_1i as FnUniform1i as FnOpenGL,
+                "glUniform1f\0" => mock_uniform_1f as FnUniform1f as FnOpenGL,
+                "glUniform3fv\0" => mock_uniform_3fv as FnUniform3fv as FnOpenGL,
+                "glUniformMatrix3fv\0" => mock_uniform_matrix_3fv as FnUniformMatrix3fv as FnOpenGL,
+                "glUniformMatrix4fv\0" => mock_uniform_matrix_4fv as FnUniformMatrix4fv as FnOpenGL,
+                "glPolygonMode\0" => mock_polygon_mode as FnPolygonMode as FnOpenGL,
+                "glDrawArrays\0" => mock_draw_arrays as FnDrawArrays as FnOpenGL,
+                "glDrawElements\0" => mock_draw_elements as FnDrawElements as FnOpenGL,
+                "glGenBuffers\0" => mock_gen_names as FnGenBuffers as FnOpenGL,
+                "glGenVertexArrays\0" => mock_gen_names as FnGenVertexArrays as FnOpenGL,
+                "glBindBuffer\0" => mock_bind_buffer as FnBindBuffer as FnOpenGL,
+                "glBufferData\0" => mock_buffer_data as FnBufferData as FnOpenGL,
+                "glEnableVertexAttribArray\0" => mock_enable_vertex_attrib_array as FnEnableVertexAttribArray as FnOpenGL,
+                "glVertexAttribPointer\0" => mock_vertex_attrib_pointer as FnVertexAttribPointer as FnOpenGL,
+                _ => mock_unimplemented as unsafe extern "system" fn() as FnOpenGL,
+            };
+            Some(f)
+        })
+        .unwrap()
+    }
+
+    pub fn deleted_buffers() -> usize {
+        DELETED_BUFFERS.with(|c| c.get())
+    }
+
+    pub fn deleted_vertex_arrays() -> usize {
+        DELETED_VERTEX_ARRAYS.with(|c| c.get())
+    }
+
+    pub fn deleted_textures() -> usize {
+        DELETED_TEXTURES.with(|c| c.get())
+    }
+
+    // Internal format, format and type of the last glTexImage2D call.
+    pub fn tex_image_formats() -> Option<(GLint, GLenum, GLenum)> {
+        TEX_IMAGE_FORMATS.with(|c| c.get())
+    }
+
+    // (pname, param) of all glTexParameteri calls, in order.
+    pub fn tex_parameters_i() -> Vec<(GLenum, GLint)> {
+        TEX_PARAMETERS_I.with(|c| c.borrow().clone())
+    }
+
+    pub fn tex_parameters_f() -> Vec<(GLenum, GLfloat)> {
+        TEX_PARAMETERS_F.with(|c| c.borrow().clone())
+    }
+
+    pub fn generated_mipmaps() -> usize {
+        GENERATED_MIPMAPS.with(|c| c.get())
+    }
+
+    // Program of the last glUseProgram call.
+    pub fn used_program() -> GLuint {
+        PROGRAM.with(|c| c.get())
+    }
+
+    // Number of glUseProgram calls.
+    pub fn program_binds() -> usize {
+        PROGRAM_BINDS.with(|c| c.get())
+    }
+
+    // (location, value) of all glUniform1f uploads, in order.
+    pub fn uniforms_1f() -> Vec<(GLint, GLfloat)> {
+        UNIFORMS_1F.with(|c| c.borrow().clone())
+    }
+
+    // (location, value) of all glUniform1i uploads, in order.
+    pub fn uniforms_1i() -> Vec<(GLint, GLint)> {
+        UNIFORMS_1I.with(|c| c.borrow().clone())
+    }
+
+    // Locations of all glUniform* uploads, in order.
+    pub fn uniform_locations() -> Vec<GLint> {
+        UNIFORM_LOCATIONS.with(|c| c.borrow().clone())
+    }
+
+    // (location, value) of all glUniform3fv uploads, in order.
+    pub fn uniforms_3f() -> Vec<(GLint, [GLfloat; 3])> {
+        UNIFORMS_3F.with(|c| c.borrow().clone())
+    }
+
+    // Color of the last glClearColor call.
+    pub fn clear_color() -> Option<[GLfloat; 4]> {
+        CLEAR_COLOR.with(|c| c.get())
+    }
+
+    // Arguments of the last glViewport call.
+    pub fn viewport() -> Option<[GLint; 4]> {
+        VIEWPORT.with(|c| c.get())
+    }
+
+    pub fn draw_calls() -> usize {
+        DRAW_CALLS.with(|c| c.get())
+    }
+
+    // Primitive modes of all draw calls, in order.
+    pub fn draw_modes() -> Vec<GLenum> {
+        DRAW_MODES.with(|c| c.borrow().clone())
+    }
+}
@@ -27,6 +27,7 @@ pub trait IWindow {
     fn on_key_event(&mut self, msg: u32, key: u32) -> LRESULT;
     fn on_mouse_event(&mut self, msg: u32, x: i32, y: i32, keys: u32, delta: i32) -> LRESULT;
     fn on_input(&mut self, _raw_input: HRAWINPUT) -> LRESULT;
+    fn on_focus_lost(&mut self) -> LRESULT;
 }
 
 pub struct WindowProc<T> {
@@ -143,6 +144,7 @@ impl<T: IWindow> WindowProc<T> {
                 let delta = hiword(wparam.0 as u32);
                 self.data.on_mouse_event(msg, x, y, keys, delta)
             }
+            WM_KILLFOCUS => self.data.on_focus_lost(),
             WM_INPUT => {
                 let raw_input = HRAWINPUT(lparam.0 as *mut core::ffi::c_void);
                 self.data.on_input(raw_input)
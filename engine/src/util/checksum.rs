@@ -0,0 +1,91 @@
+// Checksums used by zlib (Adler-32) and PNG chunks (CRC-32).
+
+// ----------------------------------------------------------------------------
+const MOD_ADLER: u32 = 65521;
+
+// largest n such that 255 * n * (n + 1) / 2 + (n + 1) * (MOD_ADLER - 1) fits u32
+const ADLER_NMAX: usize = 5552;
+
+// ----------------------------------------------------------------------------
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 {
+                0xedb8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+};
+
+// ----------------------------------------------------------------------------
+pub fn adler32(data: &[u8]) -> u32 {
+    adler32_update(1, data)
+}
+
+// ----------------------------------------------------------------------------
+// Continues an Adler-32 checksum; start with 1 for an empty input.
+pub fn adler32_update(adler: u32, data: &[u8]) -> u32 {
+    let (mut a, mut b) = (adler & 0xffff, adler >> 16);
+    for chunk in data.chunks(ADLER_NMAX) {
+        for &x in chunk {
+            a += x as u32;
+            b += a;
+        }
+        a %= MOD_ADLER;
+        b %= MOD_ADLER;
+    }
+    (b << 16) | a
+}
+
+// ----------------------------------------------------------------------------
+pub fn crc32(data: &[u8]) -> u32 {
+    crc32_update(0, data)
+}
+
+// ----------------------------------------------------------------------------
+// Continues a CRC-32 checksum; start with 0 for an empty input.
+pub fn crc32_update(crc: u32, data: &[u8]) -> u32 {
+    let mut c = !crc;
+    for &x in data {
+        c = CRC_TABLE[((c ^ x as u32) & 0xff) as usize] ^ (c >> 8);
+    }
+    !c
+}
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adler32_known_values() {
+        assert_eq!(adler32(b""), 1);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn crc32_known_values() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+    }
+
+    #[test]
+    fn incremental_matches_one_shot() {
+        let data: Vec<u8> = (0..20_000).map(|i| (i * 31 % 251) as u8).collect();
+        let (head, tail) = data.split_at(7_777);
+
+        assert_eq!(adler32_update(adler32(head), tail), adler32(&data));
+        assert_eq!(crc32_update(crc32(head), tail), crc32(&data));
+    }
+}
@@ -0,0 +1,123 @@
+// Interpolation and easing curves shared by animation, camera and UI code.
+// Easing functions map t in [0, 1] to [0, 1] with f(0) = 0 and f(1) = 1.
+use crate::v2d::Lerp;
+
+// ----------------------------------------------------------------------------
+// Quadratic Bézier from p0 to p2, pulled towards the control point p1.
+pub fn bezier_quad<T: Lerp>(p0: &T, p1: &T, p2: &T, t: f32) -> T {
+    let a = p0.lerp(p1, t);
+    let b = p1.lerp(p2, t);
+    a.lerp(&b, t)
+}
+
+// ----------------------------------------------------------------------------
+// Cubic Bézier from p0 to p3 with the control points p1 and p2.
+pub fn bezier_cubic<T: Lerp>(p0: &T, p1: &T, p2: &T, p3: &T, t: f32) -> T {
+    let a = bezier_quad(p0, p1, p2, t);
+    let b = bezier_quad(p1, p2, p3, t);
+    a.lerp(&b, t)
+}
+
+// ----------------------------------------------------------------------------
+// Hermite step from 0 at `edge0` to 1 at `edge1`, clamped outside.
+pub fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    if edge0 == edge1 {
+        return 0.0; // Avoid division by zero
+    }
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+// ----------------------------------------------------------------------------
+// Like `smoothstep` but also with zero second derivative at the edges.
+pub fn smootherstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    if edge0 == edge1 {
+        return 0.0;
+    }
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * t * (t * (6.0 * t - 15.0) + 10.0)
+}
+
+// ----------------------------------------------------------------------------
+pub fn ease_in(t: f32) -> f32 {
+    t * t
+}
+
+// ----------------------------------------------------------------------------
+pub fn ease_out(t: f32) -> f32 {
+    t * (2.0 - t)
+}
+
+// ----------------------------------------------------------------------------
+pub fn ease_in_out(t: f32) -> f32 {
+    if t < 0.5 {
+        2.0 * t * t
+    } else {
+        1.0 - 2.0 * (1.0 - t) * (1.0 - t)
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Parabolic bump that is 0 at t = 0 and t = 1 and peaks with 1 at t = 0.5.
+pub fn arch(t: f32) -> f32 {
+    let x = 2.0 * t - 1.0;
+    1.0 - x * x
+}
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v2d::{v2::V2, v3::V3};
+
+    #[test]
+    fn easing_endpoints() {
+        let curves: [fn(f32) -> f32; 5] = [
+            ease_in,
+            ease_out,
+            ease_in_out,
+            |t| smoothstep(0.0, 1.0, t),
+            |t| smootherstep(0.0, 1.0, t),
+        ];
+        for f in curves {
+            assert_eq!(f(0.0), 0.0);
+            assert_eq!(f(1.0), 1.0);
+        }
+        assert_eq!(ease_in_out(0.5), 0.5);
+        assert_eq!(arch(0.0), 0.0);
+        assert_eq!(arch(0.5), 1.0);
+        assert_eq!(arch(1.0), 0.0);
+    }
+
+    #[test]
+    fn smoothstep_is_monotonic_and_clamped() {
+        let samples = (0..=100).map(|i| -0.5 + 0.02 * i as f32);
+        let values: Vec<f32> = samples.map(|x| smoothstep(0.0, 1.0, x)).collect();
+        assert!(values.windows(2).all(|v| v[1] >= v[0]));
+        assert_eq!(values[0], 0.0);
+        assert_eq!(values[100], 1.0);
+        assert_eq!(smoothstep(2.0, 4.0, 3.0), 0.5);
+        assert_eq!(smoothstep(1.0, 1.0, 3.0), 0.0);
+    }
+
+    #[test]
+    fn bezier_endpoints_and_midpoint() {
+        let (p0, p1, p2) = (
+            V2::new([0.0, 0.0]),
+            V2::new([1.0, 2.0]),
+            V2::new([2.0, 0.0]),
+        );
+        assert_eq!(bezier_quad(&p0, &p1, &p2, 0.0), p0);
+        assert_eq!(bezier_quad(&p0, &p1, &p2, 1.0), p2);
+        assert_eq!(bezier_quad(&p0, &p1, &p2, 0.5), V2::new([1.0, 1.0]));
+
+        let p3 = V3::new([3.0, 0.0, 0.0]);
+        let (q0, q1, q2) = (V3::ZERO, V3::new([1.0, 3.0, 0.0]), V3::new([2.0, 3.0, 0.0]));
+        assert_eq!(bezier_cubic(&q0, &q1, &q2, &p3, 0.0), q0);
+        assert_eq!(bezier_cubic(&q0, &q1, &q2, &p3, 1.0), p3);
+        assert_eq!(
+            bezier_cubic(&q0, &q1, &q2, &p3, 0.5),
+            V3::new([1.5, 2.25, 0.0])
+        );
+    }
+}
@@ -0,0 +1,89 @@
+// zlib (RFC 1950) encoder writing DEFLATE (RFC 1951) stored blocks. The output
+// is not compressed, but any inflate implementation can read it, which is
+// all PNG writing needs for a start.
+
+use crate::util::checksum::adler32;
+
+// ----------------------------------------------------------------------------
+const MAX_STORED_BLOCK: usize = 0xffff;
+
+// ----------------------------------------------------------------------------
+pub fn deflate(input: &[u8]) -> Vec<u8> {
+    let num_blocks = input.len().div_ceil(MAX_STORED_BLOCK).max(1);
+    let mut out = Vec::with_capacity(2 + input.len() + num_blocks * 5 + 4);
+
+    // CMF: deflate with 32K window, FLG: no dictionary, fastest, FCHECK
+    out.extend_from_slice(&[0x78, 0x01]);
+
+    let mut blocks = input.chunks(MAX_STORED_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        write_stored_block(&mut out, &[], true);
+    }
+    while let Some(block) = blocks.next() {
+        write_stored_block(&mut out, block, blocks.peek().is_none());
+    }
+
+    out.extend_from_slice(&adler32(input).to_be_bytes());
+    out
+}
+
+// ----------------------------------------------------------------------------
+fn write_stored_block(out: &mut Vec<u8>, block: &[u8], is_final: bool) {
+    // BFINAL + BTYPE=00, padded to the byte boundary
+    out.push(is_final as u8);
+
+    let len = block.len() as u16;
+    out.extend_from_slice(&len.to_le_bytes());
+    out.extend_from_slice(&(!len).to_le_bytes());
+    out.extend_from_slice(block);
+}
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::util::inflate::{inflate, inflate_to_vec};
+
+    // ------------------------------------------------------------------------
+    fn assert_zlib_framing(z: &[u8], data: &[u8]) {
+        let (cmf, flg) = (z[0], z[1]);
+        assert_eq!(cmf & 0x0f, 8, "compression method must be deflate");
+        assert_eq!(((cmf as u16) << 8 | flg as u16) % 31, 0, "bad FCHECK");
+        assert_eq!(flg & 0x20, 0, "no preset dictionary");
+        assert_eq!(z[z.len() - 4..], adler32(data).to_be_bytes());
+    }
+
+    #[test]
+    fn round_trip_empty() {
+        let z = deflate(&[]);
+        assert_eq!(
+            z,
+            [
+                0x78, 0x01, 0x01, 0x00, 0x00, 0xff, 0xff, 0x00, 0x00, 0x00, 0x01
+            ]
+        );
+        assert!(inflate_to_vec(&z).unwrap().is_empty());
+    }
+
+    #[test]
+    fn round_trip_small() {
+        let data = b"hello, world";
+        let z = deflate(data);
+        assert_zlib_framing(&z, data);
+        assert_eq!(inflate_to_vec(&z).unwrap(), data);
+    }
+
+    #[test]
+    fn round_trip_repetitive_spans_blocks() {
+        let data: Vec<u8> = (0..200_000).map(|i| (i % 7) as u8).collect();
+        let z = deflate(&data);
+        assert_eq!(z.len(), 2 + data.len() + 4 * 5 + 4);
+        assert_zlib_framing(&z, &data);
+        assert_eq!(inflate_to_vec(&z).unwrap(), data);
+
+        let mut out = vec![0; data.len()];
+        inflate(&mut out, &z).unwrap();
+        assert_eq!(out, data);
+    }
+}
@@ -0,0 +1,537 @@
+// zlib (RFC 1950) decoder for DEFLATE (RFC 1951) streams. `Inflater` takes the
+// input in chunks of any size and appends the output to a growing buffer,
+// `inflate` decodes a whole stream into a buffer of known size.
+
+use crate::error::{Error, Result};
+use crate::util::checksum::adler32_update;
+
+// ----------------------------------------------------------------------------
+const WINDOW_SIZE: usize = 32 * 1024;
+const MAX_BITS: usize = 15;
+const MAX_LIT_CODES: usize = 286;
+const MAX_DIST_CODES: usize = 30;
+const END_OF_BLOCK: u16 = 256;
+
+// Base values and extra bits of the length symbols 257..285
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+
+// Base values and extra bits of the distance symbols 0..29
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+// Order in which a dynamic block header lists the code length code lengths
+const CLEN_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+// ----------------------------------------------------------------------------
+// LSB-first bit reader; running out of input is reported as `Underflow`.
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl BitReader<'_> {
+    fn bits(&mut self, n: usize) -> Result<u32> {
+        if self.pos + n > self.data.len() * 8 {
+            return Err(Error::Underflow);
+        }
+        let mut v = 0;
+        for i in 0..n {
+            let p = self.pos + i;
+            v |= (((self.data[p / 8] >> (p % 8)) & 1) as u32) << i;
+        }
+        self.pos += n;
+        Ok(v)
+    }
+
+    fn align(&mut self) {
+        self.pos = self.pos.next_multiple_of(8);
+    }
+
+    // Whole bytes left after a byte aligned position
+    fn bytes(&self) -> &[u8] {
+        &self.data[self.pos / 8..]
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Canonical Huffman code: number of codes per length and the symbols sorted
+// by code.
+#[derive(Debug)]
+struct Huffman {
+    counts: [u16; MAX_BITS + 1],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Self> {
+        let mut counts = [0u16; MAX_BITS + 1];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+
+        if unused_codes(&counts) < 0 {
+            return Err(Error::OverSubscribedTree);
+        }
+        let num_codes: u16 = counts.iter().sum();
+
+        let mut offsets = [0u16; MAX_BITS + 2];
+        for len in 1..=MAX_BITS {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = vec![0; num_codes as usize];
+        for (sym, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len as usize] as usize] = sym as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+
+        Ok(Self { counts, symbols })
+    }
+
+    fn fixed() -> (Self, Self) {
+        let mut lengths = [0u8; 288];
+        lengths[..144].fill(8);
+        lengths[144..256].fill(9);
+        lengths[256..280].fill(7);
+        lengths[280..].fill(8);
+        let lit = Self::new(&lengths).unwrap();
+        let dist = Self::new(&[5; 30]).unwrap();
+        (lit, dist)
+    }
+
+    // Incomplete codes are only valid with at most one symbol, as a single
+    // used distance is still coded with one bit.
+    fn check_complete(&self, allow_single: bool) -> Result<()> {
+        if unused_codes(&self.counts) > 0 && !(allow_single && self.symbols.len() <= 1) {
+            return Err(Error::UnderSubscribedTree);
+        }
+        Ok(())
+    }
+
+    fn decode(&self, bits: &mut BitReader) -> Result<u16> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for &count in &self.counts[1..] {
+            code |= bits.bits(1)? as i32;
+            let count = count as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(Error::InvalidSymbol)
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Codes left unassigned, negative if the lengths over-subscribe the tree
+fn unused_codes(counts: &[u16; MAX_BITS + 1]) -> i32 {
+    counts[1..]
+        .iter()
+        .fold(1, |left, &count| 2 * left - count as i32)
+}
+
+// ----------------------------------------------------------------------------
+#[derive(Debug)]
+enum State {
+    Header,
+    BlockHeader,
+    Stored { remaining: usize },
+    Codes { lit: Huffman, dist: Huffman },
+    Trailer,
+    Done,
+}
+
+// ----------------------------------------------------------------------------
+// Incremental decoder. Each step of the stream (header, block header, symbol,
+// run of stored bytes, trailer) is decoded as a whole or, if the input ends
+// in the middle of it, retried once more input arrives.
+#[derive(Debug)]
+pub struct Inflater {
+    input: Vec<u8>,
+    bit_pos: usize,
+    state: State,
+    last_block: bool,
+    window: Vec<u8>,
+    adler: u32,
+    checked: usize,
+}
+
+impl Default for Inflater {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Inflater {
+    pub fn new() -> Self {
+        Self {
+            input: Vec::new(),
+            bit_pos: 0,
+            state: State::Header,
+            last_block: false,
+            window: Vec::with_capacity(2 * WINDOW_SIZE),
+            adler: 1,
+            checked: 0,
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    pub fn is_done(&self) -> bool {
+        matches!(self.state, State::Done)
+    }
+
+    // ------------------------------------------------------------------------
+    // Decodes as much of `input` as possible, appending to `out`. Returns the
+    // bytes consumed and produced; input past the end of the stream is not
+    // consumed.
+    pub fn decompress(&mut self, input: &[u8], out: &mut Vec<u8>) -> Result<(usize, usize)> {
+        if self.is_done() {
+            return Ok((0, 0));
+        }
+
+        let produced_before = out.len();
+        self.checked = out.len();
+        self.input.extend_from_slice(input);
+
+        loop {
+            let start = self.bit_pos;
+            match self.step(out) {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(Error::Underflow) => {
+                    self.bit_pos = start;
+                    break;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        self.adler = adler32_update(self.adler, &out[self.checked..]);
+
+        let used = self.bit_pos / 8;
+        let consumed = if self.is_done() {
+            input.len() - (self.input.len() - used)
+        } else {
+            input.len()
+        };
+        self.input.drain(..used);
+        self.bit_pos -= used * 8;
+
+        Ok((consumed, out.len() - produced_before))
+    }
+
+    // ------------------------------------------------------------------------
+    // Decodes one step, returns false once the stream has ended.
+    fn step(&mut self, out: &mut Vec<u8>) -> Result<bool> {
+        let mut bits = BitReader {
+            data: &self.input,
+            pos: self.bit_pos,
+        };
+
+        let next = match &self.state {
+            State::Header => {
+                let cmf = bits.bits(8)?;
+                let flg = bits.bits(8)?;
+                if cmf & 0x0f != 8 || cmf >> 4 > 7 || (cmf << 8 | flg) % 31 != 0 || flg & 0x20 != 0
+                {
+                    return Err(Error::InvalidHeader);
+                }
+                Some(State::BlockHeader)
+            }
+            State::BlockHeader => {
+                let last_block = bits.bits(1)? != 0;
+                let next = match bits.bits(2)? {
+                    0 => {
+                        bits.align();
+                        let len = bits.bits(16)?;
+                        let nlen = bits.bits(16)?;
+                        if len != !nlen & 0xffff {
+                            return Err(Error::InvalidBlockLength);
+                        }
+                        State::Stored {
+                            remaining: len as usize,
+                        }
+                    }
+                    1 => {
+                        let (lit, dist) = Huffman::fixed();
+                        State::Codes { lit, dist }
+                    }
+                    2 => {
+                        let (lit, dist) = read_dynamic_codes(&mut bits)?;
+                        State::Codes { lit, dist }
+                    }
+                    _ => return Err(Error::InvalidBlockType),
+                };
+                self.last_block = last_block;
+                Some(next)
+            }
+            State::Stored { remaining } => {
+                let n = (*remaining).min(bits.bytes().len());
+                if n == 0 && *remaining > 0 {
+                    return Err(Error::Underflow);
+                }
+                emit(&mut self.window, out, &bits.bytes()[..n]);
+                bits.pos += n * 8;
+                Some(match remaining - n {
+                    0 => self.end_of_block(),
+                    remaining => State::Stored { remaining },
+                })
+            }
+            State::Codes { lit, dist } => {
+                let sym = lit.decode(&mut bits)?;
+                if sym < END_OF_BLOCK {
+                    emit(&mut self.window, out, &[sym as u8]);
+                    None
+                } else if sym == END_OF_BLOCK {
+                    Some(self.end_of_block())
+                } else {
+                    let sym = (sym - END_OF_BLOCK - 1) as usize;
+                    if sym >= LENGTH_BASE.len() {
+                        return Err(Error::InvalidSymbol);
+                    }
+                    let len =
+                        LENGTH_BASE[sym] as usize + bits.bits(LENGTH_EXTRA[sym] as usize)? as usize;
+
+                    let sym = dist.decode(&mut bits)? as usize;
+                    if sym >= DIST_BASE.len() {
+                        return Err(Error::InvalidDistance);
+                    }
+                    let distance =
+                        DIST_BASE[sym] as usize + bits.bits(DIST_EXTRA[sym] as usize)? as usize;
+                    if distance > self.window.len() {
+                        return Err(Error::InvalidDistance);
+                    }
+
+                    for _ in 0..len {
+                        let byte = self.window[self.window.len() - distance];
+                        emit(&mut self.window, out, &[byte]);
+                    }
+                    None
+                }
+            }
+            State::Trailer => {
+                bits.align();
+                let mut expected = 0;
+                for _ in 0..4 {
+                    expected = expected << 8 | bits.bits(8)?;
+                }
+                self.adler = adler32_update(self.adler, &out[self.checked..]);
+                self.checked = out.len();
+                if expected != self.adler {
+                    return Err(Error::InvalidData);
+                }
+                Some(State::Done)
+            }
+            State::Done => return Ok(false),
+        };
+
+        self.bit_pos = bits.pos;
+        if let Some(next) = next {
+            self.state = next;
+        }
+        Ok(true)
+    }
+
+    fn end_of_block(&self) -> State {
+        if self.last_block {
+            State::Trailer
+        } else {
+            State::BlockHeader
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Appends to the output and keeps at least the last 32K for back-references.
+fn emit(window: &mut Vec<u8>, out: &mut Vec<u8>, bytes: &[u8]) {
+    out.extend_from_slice(bytes);
+    window.extend_from_slice(bytes);
+    if window.len() >= 2 * WINDOW_SIZE {
+        window.drain(..window.len() - WINDOW_SIZE);
+    }
+}
+
+// ----------------------------------------------------------------------------
+fn read_dynamic_codes(bits: &mut BitReader) -> Result<(Huffman, Huffman)> {
+    let num_lit = bits.bits(5)? as usize + 257;
+    let num_dist = bits.bits(5)? as usize + 1;
+    let num_clen = bits.bits(4)? as usize + 4;
+    if num_lit > MAX_LIT_CODES || num_dist > MAX_DIST_CODES {
+        return Err(Error::InvalidCodeLength);
+    }
+
+    let mut clen_lengths = [0u8; 19];
+    for &i in &CLEN_ORDER[..num_clen] {
+        clen_lengths[i] = bits.bits(3)? as u8;
+    }
+    let clen = Huffman::new(&clen_lengths)?;
+    clen.check_complete(false)?;
+
+    let mut lengths = vec![0u8; num_lit + num_dist];
+    let mut i = 0;
+    while i < lengths.len() {
+        let sym = clen.decode(bits)?;
+        let (len, repeat) = match sym {
+            0..=15 => (sym as u8, 1),
+            16 if i > 0 => (lengths[i - 1], 3 + bits.bits(2)? as usize),
+            16 => return Err(Error::InvalidCodeLength),
+            17 => (0, 3 + bits.bits(3)? as usize),
+            _ => (0, 11 + bits.bits(7)? as usize),
+        };
+        if i + repeat > lengths.len() {
+            return Err(Error::InvalidCodeLength);
+        }
+        lengths[i..i + repeat].fill(len);
+        i += repeat;
+    }
+    if lengths[END_OF_BLOCK as usize] == 0 {
+        return Err(Error::InvalidCodeLength);
+    }
+
+    let lit = Huffman::new(&lengths[..num_lit])?;
+    let dist = Huffman::new(&lengths[num_lit..])?;
+    lit.check_complete(true)?;
+    dist.check_complete(true)?;
+    Ok((lit, dist))
+}
+
+// ----------------------------------------------------------------------------
+// Decodes a whole zlib stream into `data`, which must have exactly the size of
+// the decoded output.
+pub fn inflate(data: &mut [u8], idat: &[u8]) -> Result<()> {
+    let out = inflate_to_vec(idat)?;
+    if out.len() != data.len() {
+        return Err(Error::InvalidLength);
+    }
+    data.copy_from_slice(&out);
+    Ok(())
+}
+
+// ----------------------------------------------------------------------------
+// Decodes a whole zlib stream of unknown output size.
+pub fn inflate_to_vec(idat: &[u8]) -> Result<Vec<u8>> {
+    let mut inflater = Inflater::new();
+    let mut out = Vec::new();
+    inflater.decompress(idat, &mut out)?;
+    if !inflater.is_done() {
+        return Err(Error::Underflow);
+    }
+    Ok(out)
+}
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // zlib.compress(b"hello, hello, hello", 9), a fixed Huffman block
+    const FIXED: [u8; 17] = [
+        0x78, 0xda, 0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0xd7, 0x51, 0xc8, 0x40, 0xa2, 0x00, 0x44, 0x28,
+        0x06, 0xd5,
+    ];
+
+    // zlib.compress(dynamic_text(), 9), a dynamic Huffman block
+    const DYNAMIC: [u8; 128] = [
+        0x78, 0xda, 0x7d, 0xd1, 0x5d, 0x0e, 0x82, 0x30, 0x10, 0x04, 0xe0, 0xab, 0xcc, 0x11, 0xda,
+        0x52, 0x0a, 0xc4, 0xd3, 0x20, 0x54, 0x40, 0xc5, 0x62, 0xa1, 0x8a, 0x9c, 0x5e, 0xc2, 0xa3,
+        0xd9, 0xf1, 0x71, 0x93, 0xfd, 0xb2, 0x3f, 0xb3, 0xf4, 0x1e, 0xcf, 0x34, 0x34, 0x37, 0x9c,
+        0x63, 0x78, 0x3f, 0x70, 0x09, 0x2b, 0x14, 0xae, 0x69, 0x9c, 0x66, 0x84, 0x97, 0x8f, 0x58,
+        0xf6, 0x86, 0x7b, 0xbd, 0x7d, 0xd0, 0x86, 0x0e, 0xea, 0x74, 0xd4, 0xbf, 0x40, 0x53, 0xa0,
+        0x65, 0x60, 0x28, 0xb0, 0x32, 0xc8, 0x28, 0xa8, 0x64, 0x60, 0xf9, 0x4a, 0x4e, 0x16, 0x39,
+        0x15, 0x26, 0x97, 0x85, 0xa3, 0x22, 0x23, 0x33, 0x0a, 0x7e, 0x37, 0xb9, 0xa3, 0xa4, 0xc2,
+        0x91, 0x57, 0x55, 0x54, 0x94, 0x24, 0x0d, 0xcd, 0x03, 0xd7, 0x8a, 0x45, 0xfe, 0x27, 0x73,
+        0xb3, 0xcf, 0xf9, 0x02, 0x12, 0x45, 0xce, 0x2b,
+    ];
+
+    fn dynamic_text() -> Vec<u8> {
+        (0..12)
+            .map(|i| {
+                format!(
+                    "the quick brown fox {i} jumps over the lazy dog {}; ",
+                    i * i
+                )
+            })
+            .collect::<String>()
+            .into_bytes()
+    }
+
+    fn inflate_chunked(z: &[u8], chunk_size: usize) -> Vec<u8> {
+        let mut inflater = Inflater::new();
+        let mut out = Vec::new();
+        let (mut consumed, mut produced) = (0, 0);
+        for chunk in z.chunks(chunk_size) {
+            let (c, p) = inflater.decompress(chunk, &mut out).unwrap();
+            consumed += c;
+            produced += p;
+        }
+        assert!(inflater.is_done());
+        assert_eq!(consumed, z.len());
+        assert_eq!(produced, out.len());
+        out
+    }
+
+    #[test]
+    fn one_shot_decodes_known_streams() {
+        assert_eq!(inflate_to_vec(&FIXED).unwrap(), b"hello, hello, hello");
+        assert_eq!(inflate_to_vec(&DYNAMIC).unwrap(), dynamic_text());
+
+        let mut data = vec![0; 19];
+        inflate(&mut data, &FIXED).unwrap();
+        assert_eq!(data, b"hello, hello, hello");
+
+        let mut data = vec![0; 18];
+        assert_eq!(inflate(&mut data, &FIXED), Err(Error::InvalidLength));
+    }
+
+    #[test]
+    fn chunked_input_matches_one_shot() {
+        for z in [&FIXED[..], &DYNAMIC[..]] {
+            let expected = inflate_to_vec(z).unwrap();
+            for chunk_size in [1, 2, 3, 7, 16, z.len()] {
+                assert_eq!(
+                    inflate_chunked(z, chunk_size),
+                    expected,
+                    "chunks of {chunk_size}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn trailing_input_is_not_consumed() {
+        let mut z = FIXED.to_vec();
+        z.extend_from_slice(b"next");
+
+        let mut inflater = Inflater::new();
+        let mut out = Vec::new();
+        assert_eq!(inflater.decompress(&z, &mut out), Ok((FIXED.len(), 19)));
+        assert_eq!(inflater.decompress(b"more", &mut out), Ok((0, 0)));
+    }
+
+    #[test]
+    fn corrupt_streams_are_rejected() {
+        assert_eq!(inflate_to_vec(&[0x78, 0xdb]), Err(Error::InvalidHeader));
+        assert_eq!(inflate_to_vec(&FIXED[..10]), Err(Error::Underflow));
+
+        let mut z = FIXED;
+        z[16] ^= 1;
+        assert_eq!(inflate_to_vec(&z), Err(Error::InvalidData));
+    }
+}
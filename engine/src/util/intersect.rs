@@ -0,0 +1,105 @@
+// Ray intersection tests for picking and raycasts. Rays start at `origin`
+// and run along `dir`, which need not be normalized; hits are returned as
+// the parameter `t` of the point `origin + t * dir`, so only `t >= 0` counts.
+use crate::core::bounds::Aabb;
+use crate::v2d::v3::V3;
+
+// ----------------------------------------------------------------------------
+// Slab method; 0 if `origin` is inside the box.
+pub fn ray_aabb(origin: V3, dir: V3, aabb: &Aabb) -> Option<f32> {
+    let mut t_min = 0.0f32;
+    let mut t_max = f32::INFINITY;
+    let (origin, dir) = (origin.as_array(), dir.as_array());
+    let (min, max) = (aabb.min.as_array(), aabb.max.as_array());
+    for i in 0..3 {
+        let (o, d) = (origin[i], dir[i]);
+        let (lo, hi) = (min[i], max[i]);
+        if d.abs() < f32::EPSILON {
+            if o < lo || o > hi {
+                return None;
+            }
+            continue;
+        }
+        let (t0, t1) = ((lo - o) / d, (hi - o) / d);
+        t_min = t_min.max(t0.min(t1));
+        t_max = t_max.min(t0.max(t1));
+        if t_min > t_max {
+            return None;
+        }
+    }
+    Some(t_min)
+}
+
+// ----------------------------------------------------------------------------
+// Möller–Trumbore; both sides of the triangle are hit, edges included.
+pub fn ray_triangle(origin: V3, dir: V3, v0: V3, v1: V3, v2: V3) -> Option<f32> {
+    let (e1, e2) = (v1 - v0, v2 - v0);
+    let p = dir.cross(e2);
+    let det = e1.dot(p);
+    if det.abs() < f32::EPSILON {
+        return None; // parallel to the plane
+    }
+
+    let inv_det = 1.0 / det;
+    let s = origin - v0;
+    let u = s.dot(p) * inv_det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let q = s.cross(e1);
+    let v = dir.dot(q) * inv_det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = e2.dot(q) * inv_det;
+    (t >= 0.0).then_some(t)
+}
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_float_eq;
+
+    #[test]
+    fn ray_hits_and_misses_box() {
+        let aabb = Aabb::new(V3::new([1.0, -1.0, -1.0]), V3::new([3.0, 1.0, 1.0]));
+
+        assert_eq!(ray_aabb(V3::ZERO, V3::X0, &aabb), Some(1.0));
+        assert_eq!(ray_aabb(V3::ZERO, 2.0 * V3::X0, &aabb), Some(0.5));
+        assert_eq!(
+            ray_aabb(V3::ZERO, V3::new([1.0, 1.0, 0.0]), &aabb),
+            Some(1.0)
+        );
+        assert_eq!(ray_aabb(V3::ZERO, V3::new([1.0, 2.0, 0.0]), &aabb), None);
+        assert_eq!(ray_aabb(V3::ZERO, -V3::X0, &aabb), None);
+    }
+
+    #[test]
+    fn ray_hits_triangle_interior_and_edges() {
+        let (v0, v1, v2) = (V3::ZERO, V3::X0, V3::X1);
+        let hit = |x: f32, y: f32| ray_triangle(V3::new([x, y, -2.0]), V3::X2, v0, v1, v2);
+
+        assert_float_eq!(hit(0.25, 0.25).unwrap(), 2.0);
+        assert_float_eq!(hit(0.5, 0.0).unwrap(), 2.0); // edge v0-v1
+        assert_float_eq!(hit(0.5, 0.5).unwrap(), 2.0); // edge v1-v2
+        assert_float_eq!(hit(0.0, 0.0).unwrap(), 2.0); // corner
+        assert_eq!(hit(0.6, 0.6), None);
+        assert_eq!(hit(-0.1, 0.5), None);
+    }
+
+    #[test]
+    fn ray_misses_triangle_behind_or_parallel() {
+        let (v0, v1, v2) = (V3::ZERO, V3::X0, V3::X1);
+        let origin = V3::new([0.25, 0.25, -2.0]);
+
+        assert_eq!(ray_triangle(origin, -V3::X2, v0, v1, v2), None);
+        assert_eq!(ray_triangle(origin, V3::X0, v0, v1, v2), None);
+
+        // The back side is hit as well
+        let behind = V3::new([0.25, 0.25, 2.0]);
+        assert_float_eq!(ray_triangle(behind, -V3::X2, v0, v1, v2).unwrap(), 2.0);
+    }
+}
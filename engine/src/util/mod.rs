@@ -1,5 +1,11 @@
+pub mod checksum;
+pub mod curves;
 pub mod datetime;
+pub mod deflate;
 pub mod ik_solvers;
+pub mod inflate;
+pub mod intersect;
 pub mod logger;
 pub mod obj_pool;
+pub mod rng;
 pub mod utf8;
@@ -19,6 +19,15 @@ impl<T> Default for ObjId<T> {
     }
 }
 
+// ----------------------------------------------------------------------------
+impl<T> ObjId<T> {
+    // Slot of the object in its pool; stable for the object's lifetime, so it
+    // can be used as a sort key.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
 // ----------------------------------------------------------------------------
 impl<T> Copy for ObjId<T> {}
 
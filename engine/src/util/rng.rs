@@ -0,0 +1,67 @@
+// Small deterministic random number generator (xorshift32) for gameplay and
+// noise; the same seed always yields the same sequence.
+
+// ----------------------------------------------------------------------------
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rng {
+    state: u32,
+}
+
+// ----------------------------------------------------------------------------
+impl Rng {
+    // xorshift gets stuck at zero, so a zero seed is replaced.
+    pub fn new(seed: u32) -> Self {
+        Self {
+            state: if seed == 0 { 0x9e37_79b9 } else { seed },
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    pub fn next_u32(&mut self) -> u32 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+        x
+    }
+
+    // ------------------------------------------------------------------------
+    // Uniform in [0, 1); uses the upper 24 bits, which fit the f32 mantissa.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 * (1.0 / (1u32 << 24) as f32)
+    }
+
+    // ------------------------------------------------------------------------
+    // Uniform in [lo, hi).
+    pub fn range(&mut self, lo: f32, hi: f32) -> f32 {
+        lo + (hi - lo) * self.next_f32()
+    }
+}
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_sequence() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        for _ in 0..100 {
+            assert_eq!(a.next_u32(), b.next_u32());
+        }
+        assert_ne!(Rng::new(1).next_u32(), Rng::new(2).next_u32());
+    }
+
+    #[test]
+    fn floats_stay_in_range() {
+        let mut rng = Rng::new(0);
+        for _ in 0..10_000 {
+            let x = rng.next_f32();
+            assert!((0.0..1.0).contains(&x), "{x}");
+            let y = rng.range(-2.0, 3.0);
+            assert!((-2.0..3.0).contains(&y), "{y}");
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::v2d::{m4x4::M4x4, v4::V4};
+use crate::v2d::{m3x3::M3x3, m4x4::M4x4, q::Q, v3::V3, v4::V4};
 
 // ----------------------------------------------------------------------------
 #[rustfmt::skip]
@@ -149,3 +149,120 @@ pub fn perspective(fov: f32, aspect: f32, zn: f32, zf: f32) -> M4x4 {
         .with((3, 2), 1.0)
         .with((2, 3), -zn * zf * dz)
 }
+
+// ----------------------------------------------------------------------------
+// Splits an affine `translate * rotate * scale` matrix back into its parts.
+// A mirroring matrix is reported as a negative x scale. Shear cannot be
+// represented and is dropped by re-orthogonalizing the rotation axes.
+pub fn decompose(m: &M4x4) -> (V3, Q, V3) {
+    let translation = V3::new([m[(0, 3)], m[(1, 3)], m[(2, 3)]]);
+
+    let c0 = V3::new([m[(0, 0)], m[(1, 0)], m[(2, 0)]]);
+    let c1 = V3::new([m[(0, 1)], m[(1, 1)], m[(2, 1)]]);
+    let c2 = V3::new([m[(0, 2)], m[(1, 2)], m[(2, 2)]]);
+
+    let mut scale = V3::new([c0.length(), c1.length(), c2.length()]);
+    if M3x3::from_cols(c0, c1, c2).det() < 0.0 {
+        scale = V3::new([-scale.x0(), scale.x1(), scale.x2()]);
+    }
+
+    // Gram-Schmidt, so that shear doesn't leak into the rotation
+    let x0 = (c0 / scale.x0()).norm();
+    let x1 = (c1 - x0 * x0.dot(c1)).norm();
+    let x2 = x0.cross(x1);
+
+    let rotation = Q::from_mat3(&M3x3::from_cols(x0, x1, x2));
+    (translation, rotation, scale)
+}
+
+// ----------------------------------------------------------------------------
+// Inverse transpose of the upper left 3x3 of `m`, which transforms normals so
+// they stay perpendicular to surfaces under non-uniform scale.
+pub fn normal_matrix(m: &M4x4) -> M3x3 {
+    m.minor::<3, 3>().inverse().transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_float_eq;
+
+    // ------------------------------------------------------------------------
+    fn compose(t: V3, r: Q, s: V3) -> M4x4 {
+        translate(&V4::from_v3(t, 1.0)) * r.as_mat4x4() * scale(&V4::from_v3(s, 1.0))
+    }
+
+    #[test]
+    fn decompose_uniform_scale() {
+        let t = V3::new([1.0, -2.0, 3.0]);
+        let r = Q::from_axis_angle(V3::new([1.0, 2.0, 3.0]).norm(), 0.7);
+        let s = V3::uniform(2.5);
+
+        let (t1, r1, s1) = decompose(&compose(t, r, s));
+        assert_eq!(t1, t);
+        assert_eq!(r1, r);
+        assert_eq!(s1, s);
+    }
+
+    #[test]
+    fn decompose_non_uniform_scale() {
+        let t = V3::new([-4.0, 0.5, 10.0]);
+        let r = Q::from_axis_angle(V3::X1, -1.2);
+        let s = V3::new([0.5, 3.0, 1.5]);
+
+        let m = compose(t, r, s);
+        let (t1, r1, s1) = decompose(&m);
+        assert_eq!(t1, t);
+        assert_eq!(r1, r);
+        assert_eq!(s1, s);
+
+        // recomposing gives back the original matrix
+        let m1 = compose(t1, r1, s1);
+        for (a, b) in m.as_array().iter().zip(m1.as_array()) {
+            assert_float_eq!(*a, b);
+        }
+    }
+
+    #[test]
+    fn decompose_mirrored() {
+        let r = Q::from_axis_angle(V3::X2, 0.3);
+        let s = V3::new([-2.0, 1.0, 1.0]);
+
+        let (_, r1, s1) = decompose(&compose(V3::zero(), r, s));
+        assert_eq!(r1, r);
+        assert_eq!(s1, s);
+    }
+
+    #[test]
+    fn normals_stay_perpendicular_under_scale() {
+        let r = Q::from_axis_angle(V3::X1, 0.5);
+        let s = V3::new([1.7, 0.2, 4.0]);
+        let m = compose(V3::new([1.0, 2.0, 3.0]), r, s);
+
+        // The surface x + z = 0 becomes x / sx + z / sz = 0 before rotation
+        let n = V3::new([1.0, 0.0, 1.0]).norm();
+        let expected = r.rotate(V3::new([1.0 / s.x0(), 0.0, 1.0 / s.x2()]).norm());
+        assert_eq!((normal_matrix(&m) * n).norm(), expected);
+
+        let tangent = m.minor::<3, 3>() * V3::new([1.0, 0.0, -1.0]);
+        assert_float_eq!((normal_matrix(&m) * n).dot(tangent), 0.0);
+
+        // Transforming with the model matrix instead skews the normal
+        let skewed = (m.minor::<3, 3>() * n).norm();
+        assert!(skewed.dot(expected) < 0.9);
+    }
+
+    #[test]
+    fn decompose_drops_shear() {
+        let t = V3::new([1.0, 2.0, 3.0]);
+        let shear = M4x4::identity().with((0, 1), 0.5);
+        let m = translate(&V4::from_v3(t, 1.0)) * shear;
+
+        let (t1, r1, s1) = decompose(&m);
+        assert_eq!(t1, t);
+        assert_float_eq!(r1.length(), 1.0);
+        assert_eq!(r1, Q::identity());
+        assert_float_eq!(s1.x0(), 1.0);
+        assert_float_eq!(s1.x1(), 1.25f32.sqrt());
+    }
+}
@@ -30,3 +30,12 @@ macro_rules! assert_float_eq {
         );
     };
 }
+
+// ----------------------------------------------------------------------------
+// Debug-only check that a vector or quaternion holds no NaN or infinity.
+#[macro_export]
+macro_rules! debug_assert_finite {
+    ($v:expr) => {
+        debug_assert!($v.is_finite(), "non-finite {}: {:?}", stringify!($v), $v);
+    };
+}
@@ -473,6 +473,26 @@ impl M3x3 {
             && float_eq_rel(c1.dot(c2), 0.0)
             && float_eq_rel(c2.dot(c0), 0.0)
     }
+
+    // ------------------------------------------------------------------------
+    // Gram-Schmidt on the columns: col0 keeps its direction, col1 and col2
+    // lose their components along the previous columns. Keeps the handedness
+    // of a slightly skewed basis; degenerate columns come out zero.
+    pub fn orthonormalized(&self) -> Self {
+        let c0 = self.col0().norm();
+        let c1 = self.col1();
+        let c1 = (c1 - c1.dot(c0) * c0).norm();
+        let c2 = self.col2();
+        let c2 = (c2 - c2.dot(c0) * c0 - c2.dot(c1) * c1).norm();
+        M3x3::from_cols(c0, c1, c2)
+    }
+
+    // ------------------------------------------------------------------------
+    // Element-wise comparison with an absolute tolerance, unlike `==` which
+    // only allows for rounding.
+    pub fn approx_eq(&self, rhs: &Self, eps: f32) -> bool {
+        self.m.iter().zip(rhs.m).all(|(a, b)| (a - b).abs() <= eps)
+    }
 }
 
 #[cfg(test)]
@@ -559,4 +579,35 @@ mod tests {
         assert!(m1.is_orthonormal());
         assert!(m2.is_orthonormal());
     }
+
+    #[test]
+    fn skewed_basis_is_orthonormalized() {
+        let skewed = M3x3::from_cols(
+            V3::new([1.01, 0.02, 0.0]),
+            V3::new([0.03, 0.98, -0.01]),
+            V3::new([0.0, 0.02, 1.02]),
+        );
+        assert!(!skewed.is_orthonormal());
+
+        let m = skewed.orthonormalized();
+        assert!(m.is_orthonormal());
+        assert!(m.det() > 0.0);
+        assert_eq!(m.col0(), skewed.col0().norm());
+        assert!(m.approx_eq(&M3x3::identity(), 0.05));
+
+        // Already orthonormal bases are left alone
+        let r = M3x3::from_cols(V3::X1, V3::X2, V3::X0);
+        assert_eq!(r.orthonormalized(), r);
+    }
+
+    #[test]
+    fn approx_eq_respects_epsilon() {
+        let m = M3x3::identity();
+        let n = m.with((2, 1), 0.01);
+
+        assert!(m.approx_eq(&m, 0.0));
+        assert!(m.approx_eq(&n, 0.011));
+        assert!(!m.approx_eq(&n, 0.009));
+        assert_ne!(m, n);
+    }
 }
@@ -476,20 +476,20 @@ impl M4x4 {
         } else {
             let inv_d = 1.0 / d;
             let x00 =  self.minor::<0, 0>().det();
-            let x01 = -self.minor::<1, 0>().det();
-            let x02 =  self.minor::<2, 0>().det();
-            let x03 = -self.minor::<3, 0>().det();
-            let x10 = -self.minor::<0, 1>().det();
+            let x01 = -self.minor::<0, 1>().det();
+            let x02 =  self.minor::<0, 2>().det();
+            let x03 = -self.minor::<0, 3>().det();
+            let x10 = -self.minor::<1, 0>().det();
             let x11 =  self.minor::<1, 1>().det();
-            let x12 = -self.minor::<2, 1>().det();
-            let x13 =  self.minor::<3, 1>().det();
-            let x20 =  self.minor::<0, 2>().det();
-            let x21 = -self.minor::<1, 2>().det();
+            let x12 = -self.minor::<1, 2>().det();
+            let x13 =  self.minor::<1, 3>().det();
+            let x20 =  self.minor::<2, 0>().det();
+            let x21 = -self.minor::<2, 1>().det();
             let x22 =  self.minor::<2, 2>().det();
-            let x23 = -self.minor::<3, 2>().det();
-            let x30 = -self.minor::<0, 3>().det();
-            let x31 =  self.minor::<1, 3>().det();
-            let x32 = -self.minor::<2, 3>().det();
+            let x23 = -self.minor::<2, 3>().det();
+            let x30 = -self.minor::<3, 0>().det();
+            let x31 =  self.minor::<3, 1>().det();
+            let x32 = -self.minor::<3, 2>().det();
             let x33 =  self.minor::<3, 3>().det();
             inv_d
                 * M4x4::new([
@@ -500,4 +500,48 @@ impl M4x4 {
                 ])
         }
     }
+
+    // ------------------------------------------------------------------------
+    // Element-wise comparison with an absolute tolerance, unlike `==` which
+    // only allows for rounding.
+    pub fn approx_eq(&self, rhs: &Self, eps: f32) -> bool {
+        self.m.iter().zip(rhs.m).all(|(a, b)| (a - b).abs() <= eps)
+    }
+}
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v2d::{affine4x4, v4::V4};
+
+    #[test]
+    fn inverse_undoes_transform() {
+        let m = affine4x4::translate(&V4::new([1.0, -2.0, 3.0, 1.0]))
+            * affine4x4::rotate_x1(0.7)
+            * affine4x4::rotate_x0(-0.3)
+            * affine4x4::scale(&V4::new([2.0, 0.5, 3.0, 1.0]));
+        let p = V4::new([0.3, 4.0, -1.5, 1.0]);
+
+        assert_eq!(m * m.inverse(), M4x4::identity());
+        assert_eq!(m.inverse() * (m * p), p);
+
+        // not symmetric, so a transposed result would not map back
+        let projection = affine4x4::perspective(60.0, 1.5, 1.0, 10.0);
+        assert_eq!(projection.inverse() * (projection * p), p);
+    }
+
+    #[test]
+    fn singular_inverse_is_zero() {
+        assert_eq!(M4x4::zero().inverse(), M4x4::zero());
+    }
+
+    #[test]
+    fn approx_eq_respects_epsilon() {
+        let m = affine4x4::rotate_x1(0.7);
+        let n = m.with((0, 3), 1e-3);
+
+        assert!(m.approx_eq(&n, 2e-3));
+        assert!(!m.approx_eq(&n, 5e-4));
+    }
 }
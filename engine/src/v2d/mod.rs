@@ -21,3 +21,44 @@ impl Positive for f32 {
         *self > 0.0 && self.is_finite()
     }
 }
+
+// ----------------------------------------------------------------------------
+// Linear interpolation from `self` at t = 0 to `other` at t = 1.
+pub trait Lerp: Sized {
+    fn lerp(&self, other: &Self, t: f32) -> Self;
+}
+
+// ----------------------------------------------------------------------------
+impl Lerp for f32 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v2d::{q::Q, v2::V2, v3::V3, v4::V4};
+
+    #[test]
+    fn lerp_matches_per_type_methods() {
+        let (a, b) = (V3::new([1.0, -2.0, 0.5]), V3::new([-3.0, 4.0, 2.5]));
+        let (c, d) = (V4::new([0.0, 1.0, 2.0, 1.0]), V4::new([4.0, 3.0, 2.0, 1.0]));
+        let (p, q) = (Q::identity(), Q::from_axis_angle(V3::X1, 1.0));
+        let (u, v) = (V2::new([1.0, 2.0]), V2::new([3.0, -2.0]));
+
+        for t in [0.0, 0.5, 1.0] {
+            assert_eq!(Lerp::lerp(&2.0f32, &4.0, t), 2.0 + 2.0 * t);
+            assert_eq!(Lerp::lerp(&u, &v, t), u + (v - u) * t);
+            assert_eq!(Lerp::lerp(&a, &b, t), a.lerp(b, t));
+            assert_eq!(Lerp::lerp(&c, &d, t), c.lerp(d, t));
+            assert_eq!(Lerp::lerp(&p, &q, t), p.nlerp(q, t));
+        }
+
+        // nlerp and slerp agree at the ends and, by symmetry, halfway
+        for t in [0.0, 0.5, 1.0] {
+            assert_eq!(Lerp::lerp(&p, &q, t), p.slerp(q, t));
+        }
+    }
+}
@@ -1,12 +1,15 @@
 // Quaternion
+use serde::Serialize;
 use std::fmt;
 use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
 
+use super::Lerp;
 use super::float_eq::float_eq_rel;
 use super::{m3x3::M3x3, m4x4::M4x4, v3::V3};
 
 // ----------------------------------------------------------------------------
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Serialize)]
+#[serde(transparent)]
 pub struct Q {
     m: [f32; 4],
 }
@@ -18,6 +21,15 @@ impl Default for Q {
     }
 }
 
+// ----------------------------------------------------------------------------
+impl Lerp for Q {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        // Normalized lerp along the shorter arc; close to slerp for the small
+        // steps of an animation and much cheaper.
+        self.nlerp(*other, t)
+    }
+}
+
 // ----------------------------------------------------------------------------
 impl fmt::Display for Q {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -187,6 +199,11 @@ impl Q {
         self.x0() * b.x0() + self.x1() * b.x1() + self.x2() * b.x2() + self.x3() * b.x3()
     }
 
+    // ------------------------------------------------------------------------
+    pub fn is_finite(&self) -> bool {
+        self.m.iter().all(|x| x.is_finite())
+    }
+
     // ------------------------------------------------------------------------
     pub const fn length2(&self) -> f32 {
         self.dot(*self)
@@ -379,6 +396,72 @@ impl Q {
 
         q
     }
+
+    // ------------------------------------------------------------------------
+    // Rotation that turns +Z towards `forward` and keeps +Y as close to `up`
+    // as possible. None if `forward` is zero or parallel to `up`.
+    pub fn look_rotation(forward: V3, up: V3) -> Option<Self> {
+        let x_axis = up.cross(forward);
+        if x_axis.length() <= f32::EPSILON {
+            return None;
+        }
+        let (x_axis, z_axis) = (x_axis.norm(), forward.norm());
+        let y_axis = z_axis.cross(x_axis);
+        Some(Q::from_axes(x_axis, y_axis, z_axis))
+    }
+
+    // ------------------------------------------------------------------------
+    // Rotation angle in [0, pi]; q and -q describe the same rotation, so the
+    // shorter way around is reported.
+    pub fn angle(&self) -> f32 {
+        let v = V3::new([self.x0(), self.x1(), self.x2()]);
+        2.0 * v.length().atan2(self.x3().abs())
+    }
+
+    // ------------------------------------------------------------------------
+    // Unit rotation axis matching `angle`, or X0 if the rotation is (nearly)
+    // the identity and the axis is undefined.
+    pub fn axis(&self) -> V3 {
+        let v = V3::new([self.x0(), self.x1(), self.x2()]);
+        let length = v.length();
+        if length <= f32::EPSILON {
+            return V3::X0;
+        }
+        let sign = if self.x3() < 0.0 { -1.0 } else { 1.0 };
+        (sign / length) * v
+    }
+
+    // ------------------------------------------------------------------------
+    // Limits the rotation away from `reference` to at most `max_angle` about
+    // any axis, e.g. for joint limits. Both are absolute orientations.
+    pub fn clamp_swing(&self, reference: Q, max_angle: f32) -> Self {
+        let relative = reference.conjugate() * *self;
+        if relative.angle() <= max_angle {
+            return *self;
+        }
+        reference * Q::from_axis_angle(relative.axis(), max_angle)
+    }
+
+    // ------------------------------------------------------------------------
+    // Limits only the twist about the unit `axis` (in the reference frame) to
+    // [-max_angle, max_angle]; the remaining swing is kept.
+    pub fn clamp_twist(&self, reference: Q, axis: V3, max_angle: f32) -> Self {
+        let relative = reference.conjugate() * *self;
+        let v = V3::new([relative.x0(), relative.x1(), relative.x2()]);
+        let along = v.dot(axis);
+        // signed twist angle, the shorter way around
+        let w = relative.x3();
+        let (along, w) = if w < 0.0 { (-along, -w) } else { (along, w) };
+        let twist_angle = 2.0 * along.atan2(w);
+        if twist_angle.abs() <= max_angle {
+            return *self;
+        }
+
+        let twist = Q::from_axis_angle(axis, twist_angle);
+        let swing = relative * twist.conjugate();
+        let clamped = Q::from_axis_angle(axis, max_angle.copysign(twist_angle));
+        reference * swing * clamped
+    }
 }
 
 #[cfg(test)]
@@ -547,4 +630,74 @@ mod test {
         let v_rot_q = q.rotate([0.0, 0.0, 1.0].into());
         assert_eq!(v_rot_q, z_axis);
     }
+
+    #[test]
+    fn look_rotation_faces_forward() {
+        let forward = V3::new([1.0, 0.0, 1.0]);
+        let q = Q::look_rotation(forward, V3::X1).unwrap();
+        assert_eq!(q.rotate(V3::X2), forward.norm());
+        assert_eq!(q.rotate(V3::X1), V3::X1);
+
+        assert!(Q::look_rotation(V3::X1, V3::X1).is_none());
+        assert!(Q::look_rotation(V3::ZERO, V3::X1).is_none());
+    }
+
+    #[test]
+    fn axis_angle_roundtrip() {
+        let axes = [V3::X0, -V3::X1, V3::new([1.0, 2.0, -3.0]).norm()];
+        for axis in axes {
+            for angle in [0.1, 1.0, PI / 2.0, 3.0] {
+                let q = Q::from_axis_angle(axis, angle);
+                assert_float_eq!(q.angle(), angle);
+                assert_eq!(q.axis(), axis);
+
+                // the negated quaternion is the same rotation
+                let n = Q::new([-q.x0(), -q.x1(), -q.x2(), -q.x3()]);
+                assert_float_eq!(n.angle(), angle);
+                assert_eq!(n.axis(), axis);
+            }
+        }
+
+        // more than half a turn is reported the short way around
+        let q = Q::from_axis_angle(V3::X1, 1.5 * PI);
+        assert_float_eq!(q.angle(), 0.5 * PI);
+        assert_eq!(q.axis(), -V3::X1);
+    }
+
+    #[test]
+    fn identity_has_no_angle() {
+        assert_eq!(Q::identity().angle(), 0.0);
+        assert_eq!(Q::identity().axis(), V3::X0);
+    }
+
+    #[test]
+    fn swing_is_clamped_to_limit() {
+        let reference = Q::from_axis_angle(V3::X1, 0.5);
+        let axis = V3::new([1.0, 0.0, 1.0]).norm();
+        let limit = 0.4;
+
+        let within = reference * Q::from_axis_angle(axis, 0.3);
+        assert_eq!(within.clamp_swing(reference, limit), within);
+
+        let beyond = reference * Q::from_axis_angle(axis, 1.2);
+        let clamped = beyond.clamp_swing(reference, limit);
+        let relative = reference.conjugate() * clamped;
+        assert_float_eq!(relative.angle(), limit);
+        assert_eq!(relative.axis(), axis);
+    }
+
+    #[test]
+    fn twist_is_clamped_and_swing_kept() {
+        let reference = Q::from_axis_angle(V3::X0, 0.2);
+        let swing = Q::from_axis_angle(V3::X0, 0.3);
+        let limit = 0.5;
+
+        let within = reference * swing * Q::from_axis_angle(V3::X1, -0.4);
+        assert_eq!(within.clamp_twist(reference, V3::X1, limit), within);
+
+        let beyond = reference * swing * Q::from_axis_angle(V3::X1, -1.5);
+        let clamped = beyond.clamp_twist(reference, V3::X1, limit);
+        let expected = reference * swing * Q::from_axis_angle(V3::X1, -limit);
+        assert_eq!(clamped, expected);
+    }
 }
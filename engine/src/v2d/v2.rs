@@ -1,9 +1,9 @@
 use std::fmt;
 use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
 
-use super::Positive;
 use super::float_eq::float_eq_rel;
 use super::v3::V3;
+use super::{Lerp, Positive};
 
 // ----------------------------------------------------------------------------
 #[derive(Debug, Copy, Clone)]
@@ -41,6 +41,13 @@ impl Positive for V2 {
     }
 }
 
+// ----------------------------------------------------------------------------
+impl Lerp for V2 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        *self + (*other - *self) * t
+    }
+}
+
 // ----------------------------------------------------------------------------
 impl Add for V2 {
     type Output = Self;
@@ -240,6 +247,11 @@ impl V2 {
         self.m.as_ptr()
     }
 
+    // ------------------------------------------------------------------------
+    pub fn is_finite(&self) -> bool {
+        self.m.iter().all(|x| x.is_finite())
+    }
+
     // ------------------------------------------------------------------------
     pub const fn perpendicular(self) -> Self {
         V2::new([-self.x1(), self.x0()])
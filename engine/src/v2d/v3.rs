@@ -1,13 +1,15 @@
+use serde::Serialize;
 use std::fmt;
 use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
 
-use super::Positive;
 use super::float_eq::float_eq_rel;
 use super::v2::V2;
 use super::v4::V4;
+use super::{Lerp, Positive};
 
 // ----------------------------------------------------------------------------
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Serialize)]
+#[serde(transparent)]
 pub struct V3 {
     m: [f32; 3],
 }
@@ -49,6 +51,13 @@ impl Positive for V3 {
     }
 }
 
+// ----------------------------------------------------------------------------
+impl Lerp for V3 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        V3::lerp(*self, *other, t)
+    }
+}
+
 // ----------------------------------------------------------------------------
 impl Add for V3 {
     type Output = Self;
@@ -268,6 +277,11 @@ impl V3 {
         self.m.as_ptr()
     }
 
+    // ------------------------------------------------------------------------
+    pub fn is_finite(&self) -> bool {
+        self.m.iter().all(|x| x.is_finite())
+    }
+
     // ------------------------------------------------------------------------
     pub const fn length2(&self) -> f32 {
         self.x0() * self.x0() + self.x1() * self.x1() + self.x2() * self.x2()
@@ -303,6 +317,26 @@ impl V3 {
         V3::new([self.x0().abs(), self.x1().abs(), self.x2().abs()])
     }
 
+    // ------------------------------------------------------------------------
+    // Component-wise minimum
+    pub fn min(self, v1: Self) -> Self {
+        V3::new([
+            self.x0().min(v1.x0()),
+            self.x1().min(v1.x1()),
+            self.x2().min(v1.x2()),
+        ])
+    }
+
+    // ------------------------------------------------------------------------
+    // Component-wise maximum
+    pub fn max(self, v1: Self) -> Self {
+        V3::new([
+            self.x0().max(v1.x0()),
+            self.x1().max(v1.x1()),
+            self.x2().max(v1.x2()),
+        ])
+    }
+
     // ------------------------------------------------------------------------
     pub const fn dot(self, v1: Self) -> f32 {
         self.x0() * v1.x0() + self.x1() * v1.x1() + self.x2() * v1.x2()
@@ -320,6 +354,33 @@ impl V3 {
     pub fn lerp(self, other: Self, t: f32) -> V3 {
         self + (other - self) * t
     }
+
+    // ------------------------------------------------------------------------
+    /// Builds a vector from spherical coordinates with Y up. `azimuth` is the
+    /// angle around Y measured from +Z towards +X, `elevation` is the angle
+    /// above the XZ plane (both in radians).
+    pub fn from_spherical(radius: f32, azimuth: f32, elevation: f32) -> Self {
+        let (sin_az, cos_az) = azimuth.sin_cos();
+        let (sin_el, cos_el) = elevation.sin_cos();
+        V3::new([
+            radius * cos_el * sin_az,
+            radius * sin_el,
+            radius * cos_el * cos_az,
+        ])
+    }
+
+    // ------------------------------------------------------------------------
+    /// Inverse of `from_spherical`, returns `(radius, azimuth, elevation)`.
+    /// The azimuth is 0 at the poles and for the zero vector.
+    pub fn to_spherical(&self) -> (f32, f32, f32) {
+        let radius = self.length();
+        if radius < f32::EPSILON {
+            return (0.0, 0.0, 0.0);
+        }
+        let elevation = (self.x1() / radius).clamp(-1.0, 1.0).asin();
+        let azimuth = self.x0().atan2(self.x2());
+        (radius, azimuth, elevation)
+    }
 }
 
 #[cfg(test)]
@@ -353,4 +414,48 @@ mod tests {
         assert!(!v0.is_positive());
         assert!(v1.is_positive());
     }
+
+    #[test]
+    fn spherical_round_trip() {
+        use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+        assert_eq!(V3::from_spherical(1.0, 0.0, 0.0), V3::X2);
+        assert_eq!(V3::from_spherical(1.0, FRAC_PI_2, 0.0), V3::X0);
+        assert_eq!(V3::from_spherical(1.0, 0.0, FRAC_PI_2), V3::X1);
+
+        let dirs = [
+            (1.0, 0.0, 0.0),
+            (2.0, FRAC_PI_4, FRAC_PI_4),
+            (0.5, -FRAC_PI_2, -0.3),
+            (1.5, 0.9 * PI, 1.2),
+            (3.0, -0.75 * PI, -1.0),
+        ];
+        for (r, az, el) in dirs {
+            let v = V3::from_spherical(r, az, el);
+            let (r1, az1, el1) = v.to_spherical();
+            assert!((r1 - r).abs() < 1e-5, "{r1} != {r}");
+            assert!((az1 - az).abs() < 1e-5, "{az1} != {az}");
+            assert!((el1 - el).abs() < 1e-5, "{el1} != {el}");
+        }
+    }
+
+    #[test]
+    fn spherical_poles() {
+        let (r, az, el) = V3::new([0.0, 2.0, 0.0]).to_spherical();
+        assert_eq!((r, az), (2.0, 0.0));
+        assert!((el - std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+
+        let (_, _, el) = V3::new([0.0, -1.0, 0.0]).to_spherical();
+        assert!((el + std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+
+        let (r, az, el) = V3::zero().to_spherical();
+        assert!(!r.is_nan() && !az.is_nan() && !el.is_nan());
+    }
+
+    #[test]
+    fn nan_is_not_finite() {
+        assert!(V3::new([1.0, -2.0, 3.0]).is_finite());
+        assert!(!V3::new([1.0, f32::NAN, 3.0]).is_finite());
+        assert!(!V3::new([f32::INFINITY, 0.0, 0.0]).is_finite());
+    }
 }
@@ -1,9 +1,9 @@
 use std::fmt;
 use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
 
-use super::Positive;
 use super::float_eq::float_eq_rel;
 use super::v3::V3;
+use super::{Lerp, Positive};
 
 // ----------------------------------------------------------------------------
 #[derive(Debug, Copy, Clone)]
@@ -54,6 +54,13 @@ impl Positive for V4 {
     }
 }
 
+// ----------------------------------------------------------------------------
+impl Lerp for V4 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        V4::lerp(*self, *other, t)
+    }
+}
+
 // ----------------------------------------------------------------------------
 impl Add for V4 {
     type Output = Self;
@@ -282,6 +289,11 @@ impl V4 {
         self.m.as_ptr()
     }
 
+    // ------------------------------------------------------------------------
+    pub fn is_finite(&self) -> bool {
+        self.m.iter().all(|x| x.is_finite())
+    }
+
     // ------------------------------------------------------------------------
     pub const fn length2(self) -> f32 {
         self.x0() * self.x0()
@@ -302,6 +314,8 @@ impl V4 {
     }
 
     // ------------------------------------------------------------------------
+    // Normalizes all four components, so it is only meaningful for directions
+    // (w = 0); a zero vector stays zero.
     pub fn norm(self) -> Self {
         let l2 = self.length2();
         if l2 < f32::EPSILON {
@@ -342,6 +356,8 @@ impl V4 {
     }
 
     // ------------------------------------------------------------------------
+    // Interpolates all four components: points (w = 1) stay points and
+    // directions (w = 0) stay directions. `t` is not clamped.
     pub fn lerp(self, other: V4, t: f32) -> V4 {
         self + (other - self) * t
     }
@@ -371,4 +387,15 @@ mod tests {
         assert_eq!(v0.abs(), V4::new([1.0, 1.0, 5.0, 3.0]));
         assert!(!v0.is_positive());
     }
+
+    #[test]
+    fn lerp_endpoints_keep_w() {
+        let p0 = V4::new([1.0, 2.0, 3.0, 1.0]);
+        let p1 = V4::new([-3.0, 0.0, 5.0, 1.0]);
+
+        assert_eq!(p0.lerp(p1, 0.0), p0);
+        assert_eq!(p0.lerp(p1, 1.0), p1);
+        assert_eq!(p0.lerp(p1, 0.5), V4::new([-1.0, 1.0, 4.0, 1.0]));
+        assert_eq!(V4::X0.lerp(V4::X2, 0.25).x3(), 0.0);
+    }
 }
@@ -28,6 +28,13 @@ impl Contact {
         }
     }
 
+    // ------------------------------------------------------------------------
+    pub fn as_tire(&self) -> Option<&TireContact> {
+        match self {
+            Self::Tire { contact, .. } => Some(contact),
+        }
+    }
+
     // ------------------------------------------------------------------------
     pub fn pre_step(&mut self, bodies: &mut ObjPool<RigidBody>, dt: f32) {
         match self {
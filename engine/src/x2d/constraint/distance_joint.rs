@@ -1,4 +1,5 @@
 use crate::v2d::v3::V3;
+use crate::x2d::constraint::Constraint;
 use crate::x2d::rigid_body::RigidBody;
 
 // ----------------------------------------------------------------------------
@@ -43,9 +44,12 @@ impl DistanceJoint {
             error: 0.0,
         }
     }
+}
 
+// ----------------------------------------------------------------------------
+impl Constraint for DistanceJoint {
     // ------------------------------------------------------------------------
-    pub fn pre_step(&mut self, body_a: &RigidBody, body_b: &RigidBody, dt: f32) {
+    fn pre_step(&mut self, body_a: &RigidBody, body_b: &RigidBody, dt: f32) {
         self.world_anchor_a = body_a.to_world(self.local_anchor_a);
         self.world_anchor_b = body_b.to_world(self.local_anchor_b);
 
@@ -85,7 +89,7 @@ impl DistanceJoint {
     }
 
     // ------------------------------------------------------------------------
-    pub fn warm_start(&self, body_a: &mut RigidBody, body_b: &mut RigidBody) {
+    fn warm_start(&self, body_a: &mut RigidBody, body_b: &mut RigidBody) {
         let impulse = self.n * self.accumulated_lambda;
 
         body_a.apply_impulse_at(impulse, self.world_anchor_a, "distance_warm_start");
@@ -93,7 +97,7 @@ impl DistanceJoint {
     }
 
     // ------------------------------------------------------------------------
-    pub fn solve(&mut self, body_a: &mut RigidBody, body_b: &mut RigidBody) {
+    fn solve(&mut self, body_a: &mut RigidBody, body_b: &mut RigidBody, _dt: f32) {
         let v_a = body_a.velocity_at(self.world_anchor_a);
         let v_b = body_b.velocity_at(self.world_anchor_b);
 
@@ -110,7 +114,7 @@ impl DistanceJoint {
     }
 
     // ------------------------------------------------------------------------
-    pub fn reset(&mut self) {
+    fn reset(&mut self) {
         self.accumulated_lambda = 0.0;
     }
 }
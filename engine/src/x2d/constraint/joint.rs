@@ -1,6 +1,7 @@
 use crate::util::obj_pool::ObjPool;
 use crate::v2d::{m3x3::M3x3, v3::V3};
 use crate::x2d::BodyId;
+use crate::x2d::constraint::Constraint;
 use crate::x2d::constraint::softness::Softness;
 use crate::x2d::constraint::{
     distance_joint::DistanceJoint, slider_joint::SliderJoint, spring_joint::SpringJoint,
@@ -106,137 +107,69 @@ impl Joint {
     }
 
     // ------------------------------------------------------------------------
-    pub fn pre_step(&mut self, bodies: &mut ObjPool<RigidBody>, dt: f32) {
+    pub fn bodies(&self) -> (BodyId, BodyId) {
         match self {
-            Self::Distance {
-                body_a,
-                body_b,
-                joint,
-            } => {
-                if let Some((body_a, body_b)) = bodies.get_pair(*body_a, *body_b) {
-                    joint.pre_step(body_a, body_b, dt);
-                }
-            }
-
-            Self::Spring {
-                body_a,
-                body_b,
-                joint,
-            } => {
-                if let Some((body_a, body_b)) = bodies.get_pair(*body_a, *body_b) {
-                    joint.pre_step(body_a, body_b, dt);
-                }
-            }
-
-            Self::Slider {
-                body_a,
-                body_b,
-                joint,
-            } => {
-                if let Some((body_a, body_b)) = bodies.get_pair(*body_a, *body_b) {
-                    joint.pre_step(body_a, body_b, dt);
-                }
-            }
-
-            Self::Wheel {
-                body_a,
-                body_b,
-                joint,
-            } => {
-                if let Some((body_a, body_b)) = bodies.get_pair(*body_a, *body_b) {
-                    joint.pre_step(body_a, body_b, dt);
-                }
-            }
+            Self::Distance { body_a, body_b, .. }
+            | Self::Slider { body_a, body_b, .. }
+            | Self::Spring { body_a, body_b, .. }
+            | Self::Wheel { body_a, body_b, .. } => (*body_a, *body_b),
         }
     }
 
     // ------------------------------------------------------------------------
-    pub fn warm_start(&self, bodies: &mut ObjPool<RigidBody>) {
+    pub fn constraint(&self) -> &dyn Constraint {
         match self {
-            Self::Distance {
-                body_a,
-                body_b,
-                joint,
-            } => {
-                if let Some((body_a, body_b)) = bodies.get_pair_mut(*body_a, *body_b) {
-                    joint.warm_start(body_a, body_b);
-                }
-            }
+            Self::Distance { joint, .. } => joint,
+            Self::Slider { joint, .. } => joint,
+            Self::Spring { joint, .. } => joint,
+            Self::Wheel { joint, .. } => joint,
+        }
+    }
 
-            Self::Spring {
-                body_a,
-                body_b,
-                joint,
-            } => {
-                if let Some((body_a, body_b)) = bodies.get_pair_mut(*body_a, *body_b) {
-                    joint.warm_start(body_a, body_b);
-                }
-            }
+    // ------------------------------------------------------------------------
+    pub fn constraint_mut(&mut self) -> &mut dyn Constraint {
+        match self {
+            Self::Distance { joint, .. } => joint,
+            Self::Slider { joint, .. } => joint,
+            Self::Spring { joint, .. } => joint,
+            Self::Wheel { joint, .. } => joint,
+        }
+    }
 
-            Self::Slider {
-                body_a,
-                body_b,
-                joint,
-            } => {
-                if let Some((body_a, body_b)) = bodies.get_pair_mut(*body_a, *body_b) {
-                    joint.warm_start(body_a, body_b);
-                }
-            }
+    // ------------------------------------------------------------------------
+    pub fn pre_step(&mut self, bodies: &mut ObjPool<RigidBody>, dt: f32) {
+        let (body_a, body_b) = self.bodies();
+        if let Some((body_a, body_b)) = bodies.get_pair(body_a, body_b) {
+            self.constraint_mut().pre_step(body_a, body_b, dt);
+        }
+    }
 
-            Self::Wheel {
-                body_a,
-                body_b,
-                joint,
-            } => {
-                if let Some((body_a, body_b)) = bodies.get_pair_mut(*body_a, *body_b) {
-                    joint.warm_start(body_a, body_b);
-                }
-            }
+    // ------------------------------------------------------------------------
+    pub fn warm_start(&self, bodies: &mut ObjPool<RigidBody>) {
+        let (body_a, body_b) = self.bodies();
+        if let Some((body_a, body_b)) = bodies.get_pair_mut(body_a, body_b) {
+            self.constraint().warm_start(body_a, body_b);
         }
     }
 
     // ------------------------------------------------------------------------
     pub fn solve(&mut self, bodies: &mut ObjPool<RigidBody>, dt: f32) {
-        match self {
-            Self::Distance {
-                body_a,
-                body_b,
-                joint,
-            } => {
-                if let Some((body_a, body_b)) = bodies.get_pair_mut(*body_a, *body_b) {
-                    joint.solve(body_a, body_b);
-                }
-            }
-
-            Self::Spring {
-                body_a,
-                body_b,
-                joint,
-            } => {
-                if let Some((body_a, body_b)) = bodies.get_pair_mut(*body_a, *body_b) {
-                    joint.solve(body_a, body_b);
-                }
-            }
+        let (body_a, body_b) = self.bodies();
+        if let Some((body_a, body_b)) = bodies.get_pair_mut(body_a, body_b) {
+            self.constraint_mut().solve(body_a, body_b, dt);
+        }
+    }
 
-            Self::Slider {
-                body_a,
-                body_b,
-                joint,
-            } => {
-                if let Some((body_a, body_b)) = bodies.get_pair_mut(*body_a, *body_b) {
-                    joint.solve(body_a, body_b);
-                }
-            }
+    // ------------------------------------------------------------------------
+    pub fn reset(&mut self) {
+        self.constraint_mut().reset();
+    }
 
-            Self::Wheel {
-                body_a,
-                body_b,
-                joint,
-            } => {
-                if let Some((body_a, body_b)) = bodies.get_pair_mut(*body_a, *body_b) {
-                    joint.solve(body_a, body_b, dt);
-                }
-            }
+    // ------------------------------------------------------------------------
+    pub fn as_slider(&self) -> Option<&SliderJoint> {
+        match self {
+            Self::Slider { joint, .. } => Some(joint),
+            _ => None,
         }
     }
 
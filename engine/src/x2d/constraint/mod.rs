@@ -6,3 +6,72 @@ pub mod softness;
 pub mod spring_joint;
 pub mod tire_contact;
 pub mod wheel_joint;
+
+use crate::x2d::rigid_body::RigidBody;
+
+// ----------------------------------------------------------------------------
+// A two-body constraint driven by the sequential impulse solver: `pre_step`
+// caches per-step data, `warm_start` re-applies last step's impulses, `solve`
+// runs one velocity iteration, `reset` drops the accumulated impulses.
+pub trait Constraint {
+    fn pre_step(&mut self, body_a: &RigidBody, body_b: &RigidBody, dt: f32);
+    fn warm_start(&self, body_a: &mut RigidBody, body_b: &mut RigidBody);
+    fn solve(&mut self, body_a: &mut RigidBody, body_b: &mut RigidBody, dt: f32);
+    fn reset(&mut self);
+}
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v2d::{q::Q, v3::V3};
+    use crate::x2d::constraint::{distance_joint::DistanceJoint, slider_joint::SliderJoint};
+    use crate::x2d::{Material, mass::Mass};
+
+    // ------------------------------------------------------------------------
+    fn body(name: &str, pos: V3) -> RigidBody {
+        RigidBody::new(
+            String::from(name),
+            Mass::new(1.0, V3::new([1.0, 1.0, 1.0])).unwrap(),
+            Material::default(),
+            pos,
+            Q::identity(),
+        )
+    }
+
+    #[test]
+    fn heterogeneous_solve_loop() {
+        let mut a = body("a", V3::new([0.0, 0.5, 0.0]));
+        let mut b = body("b", V3::zero());
+        a.apply_impulse(V3::new([0.0, 1.0, 0.0]), "test");
+
+        let mut constraints: Vec<Box<dyn Constraint>> = vec![
+            Box::new(DistanceJoint::new(V3::zero(), V3::zero(), 0.5)),
+            Box::new(SliderJoint::new(V3::zero(), V3::zero(), V3::X1)),
+        ];
+
+        let dt = 1.0 / 60.0;
+        for c in constraints.iter_mut() {
+            c.pre_step(&a, &b, dt);
+            c.warm_start(&mut a, &mut b);
+        }
+        for _ in 0..10 {
+            for c in constraints.iter_mut() {
+                c.solve(&mut a, &mut b, dt);
+            }
+        }
+
+        // The distance joint removes the separating velocity along y, the
+        // slider keeps the bodies from drifting apart sideways.
+        let v_rel = a.relative_velocity_at(&b, V3::zero());
+        assert!(v_rel.length() < 1e-3, "{v_rel}");
+        assert_eq!(
+            a.linear_velocity() + b.linear_velocity(),
+            V3::new([0.0, 1.0, 0.0])
+        );
+
+        for c in constraints.iter_mut() {
+            c.reset();
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::v2d::{affine3x3, m3x3::M3x3, v3::V3};
+use crate::x2d::constraint::Constraint;
 use crate::x2d::rigid_body::RigidBody;
 
 // ----------------------------------------------------------------------------
@@ -27,7 +28,7 @@ pub struct SliderJoint {
 impl SliderJoint {
     // ------------------------------------------------------------------------
     pub fn new(local_anchor_a: V3, local_anchor_b: V3, local_line_dir_b: V3) -> Self {
-        let basis = affine3x3::basis_from_x0(local_line_dir_b);
+        let basis = affine3x3::basis_from_x0(local_line_dir_b.norm());
         Self {
             local_anchor_a,
             local_anchor_b,
@@ -44,9 +45,12 @@ impl SliderJoint {
             error: [0.0; 2],
         }
     }
+}
 
+// ----------------------------------------------------------------------------
+impl Constraint for SliderJoint {
     // ------------------------------------------------------------------------
-    pub fn pre_step(&mut self, body_a: &RigidBody, body_b: &RigidBody, dt: f32) {
+    fn pre_step(&mut self, body_a: &RigidBody, body_b: &RigidBody, dt: f32) {
         // Compute world anchor
         self.world_anchor_a = body_a.to_world(self.local_anchor_a);
         self.world_anchor_b = body_b.to_world(self.local_anchor_b);
@@ -54,11 +58,16 @@ impl SliderJoint {
         self.r_a = self.world_anchor_a - body_a.position();
         self.r_b = self.world_anchor_b - body_b.position();
 
-        // update the perpendicular basis
-        let n1 = body_b.orientation().rotate(self.basis.col1()).norm();
-        let n2 = body_b.orientation().rotate(self.basis.col2()).norm();
+        // update the perpendicular basis, re-orthonormalized as B's
+        // orientation is only approximately a rotation
+        let world_basis = M3x3::from_cols(
+            body_b.to_world_dir(self.basis.col0()),
+            body_b.to_world_dir(self.basis.col1()),
+            body_b.to_world_dir(self.basis.col2()),
+        )
+        .orthonormalized();
 
-        self.n = [n1, n2];
+        self.n = [world_basis.col1(), world_basis.col2()];
 
         let inv_mass_a = body_a.inv_mass();
         let inv_mass_b = body_b.inv_mass();
@@ -87,7 +96,7 @@ impl SliderJoint {
     }
 
     // ------------------------------------------------------------------------
-    pub fn warm_start(&self, body_a: &mut RigidBody, body_b: &mut RigidBody) {
+    fn warm_start(&self, body_a: &mut RigidBody, body_b: &mut RigidBody) {
         for i in 0..2 {
             let impulse = self.n[i] * self.accumulated_lambda[i];
             body_a.apply_impulse_at(impulse, self.world_anchor_a, "slider_warm_start");
@@ -96,12 +105,10 @@ impl SliderJoint {
     }
 
     // ------------------------------------------------------------------------
-    pub fn solve(&mut self, body_a: &mut RigidBody, body_b: &mut RigidBody) {
+    fn solve(&mut self, body_a: &mut RigidBody, body_b: &mut RigidBody, _dt: f32) {
         for i in 0..2 {
-            let v_a = body_a.velocity_at(self.world_anchor_a);
-            let v_b = body_b.velocity_at(self.world_anchor_b);
-
-            let c_dot = self.n[i].dot(v_a - v_b);
+            let v_rel = body_a.relative_velocity(body_b, self.world_anchor_a, self.world_anchor_b);
+            let c_dot = self.n[i].dot(v_rel);
 
             let lambda = -(c_dot + self.bias[i]) * self.effective_mass[i];
 
@@ -114,7 +121,80 @@ impl SliderJoint {
     }
 
     // ------------------------------------------------------------------------
-    pub fn reset(&mut self) {
+    fn reset(&mut self) {
         self.accumulated_lambda = [0.0; 2];
     }
 }
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v2d::q::Q;
+    use crate::x2d::{Material, mass::Mass};
+
+    // ------------------------------------------------------------------------
+    fn body(name: &str, pos: V3) -> RigidBody {
+        RigidBody::new(
+            String::from(name),
+            Mass::new(1.0, V3::new([1.0, 1.0, 1.0])).unwrap(),
+            Material::default(),
+            pos,
+            Q::identity(),
+        )
+    }
+
+    // ------------------------------------------------------------------------
+    fn perpendicular_error(joint: &SliderJoint) -> f32 {
+        (joint.error[0] * joint.error[0] + joint.error[1] * joint.error[1]).sqrt()
+    }
+
+    #[test]
+    fn converges_to_slider_line() {
+        // B's line runs along x through its origin; A starts off the line.
+        let mut a = body("a", V3::new([0.0, 0.5, -0.3]));
+        let mut b = body("b", V3::zero());
+        let mut joint = SliderJoint::new(V3::zero(), V3::zero(), V3::X0);
+
+        let dt = 1.0 / 60.0;
+        let mut last_error = f32::INFINITY;
+        for _ in 0..20 {
+            joint.pre_step(&a, &b, dt);
+            joint.warm_start(&mut a, &mut b);
+            for _ in 0..10 {
+                joint.solve(&mut a, &mut b, dt);
+            }
+            a.integrate_velocities(dt);
+            b.integrate_velocities(dt);
+
+            let error = perpendicular_error(&joint);
+            assert!(error < last_error, "{error} >= {last_error}");
+            last_error = error;
+        }
+    }
+
+    #[test]
+    fn warm_start_reapplies_accumulated_impulse() {
+        let mut a = body("a", V3::new([0.0, 0.5, 0.0]));
+        let mut b = body("b", V3::zero());
+        let mut joint = SliderJoint::new(V3::zero(), V3::zero(), V3::X0);
+
+        let dt = 1.0 / 60.0;
+        joint.pre_step(&a, &b, dt);
+        joint.solve(&mut a, &mut b, dt);
+        let v_a = a.linear_velocity();
+        assert!(v_a.x1() < 0.0);
+
+        // A fresh pair receives the same impulse from warm starting alone.
+        let mut a2 = body("a", V3::new([0.0, 0.5, 0.0]));
+        let mut b2 = body("b", V3::zero());
+        joint.warm_start(&mut a2, &mut b2);
+        assert_eq!(a2.linear_velocity(), v_a);
+
+        joint.reset();
+        let mut a3 = body("a", V3::new([0.0, 0.5, 0.0]));
+        let mut b3 = body("b", V3::zero());
+        joint.warm_start(&mut a3, &mut b3);
+        assert_eq!(a3.linear_velocity(), V3::zero());
+    }
+}
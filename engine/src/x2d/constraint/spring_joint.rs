@@ -1,4 +1,5 @@
 use crate::v2d::v3::V3;
+use crate::x2d::constraint::Constraint;
 use crate::x2d::constraint::softness::Softness;
 use crate::x2d::rigid_body::RigidBody;
 
@@ -53,9 +54,12 @@ impl SpringJoint {
             error: 0.0,
         }
     }
+}
 
+// ----------------------------------------------------------------------------
+impl Constraint for SpringJoint {
     // ------------------------------------------------------------------------
-    pub fn pre_step(&mut self, body_a: &RigidBody, body_b: &RigidBody, _dt: f32) {
+    fn pre_step(&mut self, body_a: &RigidBody, body_b: &RigidBody, _dt: f32) {
         self.world_anchor_a = body_a.to_world(self.local_anchor_a);
         self.world_anchor_b = body_b.to_world(self.local_anchor_b);
 
@@ -88,7 +92,7 @@ impl SpringJoint {
     }
 
     // ------------------------------------------------------------------------
-    pub fn warm_start(&self, body_a: &mut RigidBody, body_b: &mut RigidBody) {
+    fn warm_start(&self, body_a: &mut RigidBody, body_b: &mut RigidBody) {
         let impulse = self.n * self.accumulated_lambda;
 
         body_a.apply_impulse_at(impulse, self.world_anchor_a, "spring_warm_start");
@@ -96,7 +100,7 @@ impl SpringJoint {
     }
 
     // ------------------------------------------------------------------------
-    pub fn solve(&mut self, body_a: &mut RigidBody, body_b: &mut RigidBody) {
+    fn solve(&mut self, body_a: &mut RigidBody, body_b: &mut RigidBody, _dt: f32) {
         let v_a = body_a.velocity_at(self.world_anchor_a);
         let v_b = body_b.velocity_at(self.world_anchor_b);
 
@@ -118,7 +122,7 @@ impl SpringJoint {
     }
 
     // ------------------------------------------------------------------------
-    pub fn reset(&mut self) {
+    fn reset(&mut self) {
         self.accumulated_lambda = 0.0;
     }
 }
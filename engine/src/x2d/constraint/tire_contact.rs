@@ -1,6 +1,71 @@
 use crate::v2d::{m3x3::M3x3, v3::V3};
 use crate::x2d::rigid_body::RigidBody;
 
+// Below this ground speed slip is measured against MIN_SLIP_SPEED instead,
+// so slip does not blow up when the wheel is almost at rest.
+const MIN_SLIP_SPEED: f32 = 1.0;
+
+// ----------------------------------------------------------------------------
+// Friction coefficient over slip: rises to `peak` at `peak_slip` and then
+// falls off towards `sliding`, like a simplified Pacejka curve. Slip is the
+// slip ratio for the longitudinal and the slip angle in radians for the
+// lateral direction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TireCurve {
+    pub peak_slip: f32,
+    pub peak: f32,
+    pub sliding: f32,
+}
+
+// ----------------------------------------------------------------------------
+impl TireCurve {
+    pub fn coefficient(&self, slip: f32) -> f32 {
+        let s = slip.abs() / self.peak_slip;
+        if s <= 1.0 {
+            self.peak * s * (2.0 - s)
+        } else {
+            self.sliding + (self.peak - self.sliding) * (1.0 - s).exp()
+        }
+    }
+
+    // Impulse limit used by the solver. The constraint itself provides the
+    // rising part of the curve, so grip is only reduced past the peak.
+    pub fn grip(&self, slip: f32) -> f32 {
+        if slip.abs() <= self.peak_slip {
+            self.peak
+        } else {
+            self.coefficient(slip)
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Per wheel tire characteristics; less lateral grip on one axle than the
+// other makes the car oversteer or understeer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TireModel {
+    pub longitudinal: TireCurve,
+    pub lateral: TireCurve,
+}
+
+// ----------------------------------------------------------------------------
+impl Default for TireModel {
+    fn default() -> Self {
+        Self {
+            longitudinal: TireCurve {
+                peak_slip: 0.1,
+                peak: 2.8,
+                sliding: 2.0,
+            },
+            lateral: TireCurve {
+                peak_slip: 0.14,
+                peak: 2.8,
+                sliding: 2.1,
+            },
+        }
+    }
+}
+
 // ----------------------------------------------------------------------------
 #[derive(Debug, Clone)]
 pub struct TireContext {
@@ -10,7 +75,7 @@ pub struct TireContext {
     pub normal: V3,
     pub penetration: f32,
     pub normal_force: f32,
-    pub friction: f32,
+    pub tire: TireModel,
 }
 
 // ----------------------------------------------------------------------------
@@ -26,6 +91,9 @@ pub struct TireContact {
     normal_lambda: f32,
     lateral_lambda: f32,
     forward_lambda: f32,
+
+    slip_ratio: f32,
+    slip_angle: f32,
 }
 
 // ----------------------------------------------------------------------------
@@ -41,6 +109,8 @@ impl TireContact {
             normal_lambda: 0.0,
             lateral_lambda: 0.0,
             forward_lambda: 0.0,
+            slip_ratio: 0.0,
+            slip_angle: 0.0,
         }
     }
 
@@ -49,6 +119,26 @@ impl TireContact {
         self.context = context;
     }
 
+    // ------------------------------------------------------------------------
+    // Slip of the last solver iteration.
+    pub fn slip_ratio(&self) -> f32 {
+        self.slip_ratio
+    }
+
+    pub fn slip_angle(&self) -> f32 {
+        self.slip_angle
+    }
+
+    // Accumulated normal impulse, i.e. the load carried by the tire.
+    pub fn normal_impulse(&self) -> f32 {
+        self.normal_lambda
+    }
+
+    // Vertical load in newtons pushed through the suspension.
+    pub fn normal_force(&self) -> f32 {
+        self.context.normal_force
+    }
+
     // ------------------------------------------------------------------------
     pub fn pre_step(&mut self, body: &RigidBody, dt: f32) {
         let inv_mass = body.inv_mass();
@@ -109,8 +199,6 @@ impl TireContact {
 
     // ------------------------------------------------------------------------
     pub fn solve(&mut self, body: &mut RigidBody, dt: f32) {
-        let max_lambda = self.context.friction * self.context.normal_force * dt;
-
         let v = body.velocity_at(self.context.contact_point);
         let lin_v = body.linear_velocity();
 
@@ -122,6 +210,16 @@ impl TireContact {
         let forward_speed = forward.dot(v);
         let normal_speed = normal.dot(v);
 
+        // forward_speed is measured at the contact point, so it is the speed
+        // the tire surface slides over the ground
+        let ground_speed = forward.dot(lin_v).abs().max(MIN_SLIP_SPEED);
+        self.slip_ratio = forward_speed / ground_speed;
+        self.slip_angle = lateral_speed.abs().atan2(ground_speed);
+
+        let tire = &self.context.tire;
+        let max_load = self.context.normal_force * dt;
+
+        let max_lambda = tire.lateral.grip(self.slip_angle) * max_load;
         let mut lambda = -lateral_speed * self.eff_mass_lateral;
         let old_lambda = self.lateral_lambda;
         self.lateral_lambda = (old_lambda + lambda).clamp(-max_lambda, max_lambda);
@@ -129,6 +227,7 @@ impl TireContact {
 
         body.apply_impulse_at(lateral * lambda, self.context.contact_point, "tire_lateral");
 
+        let max_lambda = tire.longitudinal.grip(self.slip_ratio) * max_load;
         let mut lambda = -forward_speed * self.eff_mass_forward;
         let old_lambda = self.forward_lambda;
         self.forward_lambda = (old_lambda + lambda).clamp(-max_lambda, max_lambda);
@@ -144,3 +243,34 @@ impl TireContact {
         body.apply_impulse_at(normal * lambda, self.context.contact_point, "tire_normal");
     }
 }
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lateral_force_peaks_at_nonzero_slip_angle() {
+        let curve = TireModel::default().lateral;
+        let force = |deg: f32| curve.coefficient(deg.to_radians());
+
+        let (peak_deg, peak) = (0..=40)
+            .map(|deg| (deg as f32, force(deg as f32)))
+            .fold((0.0, 0.0), |best, s| if s.1 > best.1 { s } else { best });
+
+        assert_eq!(force(0.0), 0.0);
+        assert!(peak_deg > 0.0);
+        assert!(force(peak_deg + 5.0) < peak);
+        assert!(force(peak_deg + 20.0) < force(peak_deg + 5.0));
+        assert!(force(90.0) >= curve.sliding);
+    }
+
+    #[test]
+    fn grip_only_falls_off_past_peak() {
+        let curve = TireModel::default().longitudinal;
+        assert_eq!(curve.grip(0.0), curve.peak);
+        assert_eq!(curve.grip(-0.5 * curve.peak_slip), curve.peak);
+        assert!(curve.grip(3.0 * curve.peak_slip) < curve.peak);
+        assert_eq!(curve.grip(-2.0), curve.grip(2.0));
+    }
+}
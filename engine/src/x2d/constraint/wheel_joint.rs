@@ -1,5 +1,6 @@
 #![allow(clippy::needless_range_loop)]
 use crate::v2d::{m3x3::M3x3, v3::V3};
+use crate::x2d::constraint::Constraint;
 use crate::x2d::constraint::softness::Softness;
 use crate::x2d::rigid_body::RigidBody;
 
@@ -95,7 +96,18 @@ impl WheelJoint {
     }
 
     // ------------------------------------------------------------------------
-    pub fn pre_step(&mut self, body_a: &RigidBody, body_b: &RigidBody, dt: f32) {
+    // How far the suspension is compressed beyond its rest length, as of the
+    // last pre-step; negative when extended. The wheel is body a, so the
+    // spring error grows as the wheel is pushed up towards the chassis.
+    pub fn compression(&self) -> f32 {
+        self.error[2]
+    }
+}
+
+// ----------------------------------------------------------------------------
+impl Constraint for WheelJoint {
+    // ------------------------------------------------------------------------
+    fn pre_step(&mut self, body_a: &RigidBody, body_b: &RigidBody, dt: f32) {
         self.world_anchor_a = body_a.to_world(self.local_anchor_a);
         self.world_anchor_b = body_b.to_world(self.local_anchor_b);
 
@@ -155,7 +167,7 @@ impl WheelJoint {
     }
 
     // ------------------------------------------------------------------------
-    pub fn warm_start(&self, body_a: &mut RigidBody, body_b: &mut RigidBody) {
+    fn warm_start(&self, body_a: &mut RigidBody, body_b: &mut RigidBody) {
         for i in 0..3 {
             let impulse = self.n[i] * self.accumulated_lambda[i];
 
@@ -173,7 +185,7 @@ impl WheelJoint {
     }
 
     // ------------------------------------------------------------------------
-    pub fn solve(&mut self, body_a: &mut RigidBody, body_b: &mut RigidBody, dt: f32) {
+    fn solve(&mut self, body_a: &mut RigidBody, body_b: &mut RigidBody, dt: f32) {
         let v_a = body_a.velocity_at(self.world_anchor_a);
         let v_b = body_b.velocity_at(self.world_anchor_b);
 
@@ -240,7 +252,7 @@ impl WheelJoint {
     }
 
     // ------------------------------------------------------------------------
-    pub fn reset(&mut self) {
+    fn reset(&mut self) {
         self.accumulated_lambda = [0.0; 6];
     }
 }
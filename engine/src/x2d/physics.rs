@@ -1,16 +1,23 @@
 use crate::core::gl_renderer::Transform;
+use crate::error::Result;
 use crate::util::obj_pool::ObjPool;
 use crate::x2d::{
     BodyId, ContactId, JointId, constraint::contact::Contact, constraint::joint::Joint,
     rigid_body::RigidBody,
 };
 
+// Default number of sequential impulse passes over all contacts and joints
+// per step
+const SOLVER_ITERATIONS: usize = 10;
+
 // ----------------------------------------------------------------------------
 #[derive(Debug)]
 pub struct Physics {
     bodies: ObjPool<RigidBody>,
     joints: ObjPool<Joint>,
     contacts: ObjPool<Contact>,
+    substeps: usize,
+    solver_iterations: usize,
 }
 
 // ----------------------------------------------------------------------------
@@ -20,6 +27,8 @@ impl Default for Physics {
             bodies: ObjPool::new(),
             joints: ObjPool::new(),
             contacts: ObjPool::new(),
+            substeps: 1,
+            solver_iterations: SOLVER_ITERATIONS,
         }
     }
 }
@@ -99,13 +108,71 @@ impl Physics {
     }
 
     // ------------------------------------------------------------------------
+    pub fn substeps(&self) -> usize {
+        self.substeps
+    }
+
+    // ------------------------------------------------------------------------
+    pub fn set_substeps(&mut self, substeps: usize) {
+        self.substeps = substeps.max(1);
+    }
+
+    // ------------------------------------------------------------------------
+    pub fn solver_iterations(&self) -> usize {
+        self.solver_iterations
+    }
+
+    // ------------------------------------------------------------------------
+    // More iterations let impulses travel further through chains of joints
+    // and contacts, at the cost of solver time.
+    pub fn set_solver_iterations(&mut self, iterations: usize) {
+        self.solver_iterations = iterations.max(1);
+    }
+
+    // ------------------------------------------------------------------------
+    // Splits `dt` into `substeps` smaller steps. `apply` is called before each
+    // step to (re)apply forces and update contacts for the sub-step duration;
+    // anything applied there is seen by the solver in the same step.
+    pub fn step_substeps<F>(&mut self, dt: f32, apply: F) -> Result<()>
+    where
+        F: FnMut(&mut Physics, f32) -> Result<()>,
+    {
+        self.step_substeps_resolved(dt, apply, |_| Ok(()))
+    }
+
+    // ------------------------------------------------------------------------
+    // Like `step_substeps`, with `resolve` called after each step to correct
+    // the bodies against geometry the solver does not know about, such as a
+    // swept test against the terrain.
+    pub fn step_substeps_resolved<F, G>(
+        &mut self,
+        dt: f32,
+        mut apply: F,
+        mut resolve: G,
+    ) -> Result<()>
+    where
+        F: FnMut(&mut Physics, f32) -> Result<()>,
+        G: FnMut(&mut Physics) -> Result<()>,
+    {
+        let h = dt / self.substeps as f32;
+        for _ in 0..self.substeps {
+            apply(self, h)?;
+            self.step(h);
+            resolve(self)?;
+        }
+        Ok(())
+    }
+
+    // ------------------------------------------------------------------------
+    // Forces applied since the last step are turned into velocities first, so
+    // that joints and contacts correct the velocities the bodies are about to
+    // move with. Only then are positions advanced.
     pub fn step(&mut self, dt: f32) {
         self.integrate_forces(dt);
         self.pre_step(dt);
         self.warm_start();
 
-        let solver_iterations = 10;
-        for _ in 0..solver_iterations {
+        for _ in 0..self.solver_iterations {
             self.solve_contacts(dt);
             self.solve_constraints(dt);
         }
@@ -161,3 +228,211 @@ impl Physics {
         }
     }
 }
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v2d::{m3x3::M3x3, q::Q, v3::V3};
+    use crate::x2d::constraint::tire_contact::{TireContext, TireModel};
+    use crate::x2d::{Material, mass::Mass};
+
+    // ------------------------------------------------------------------------
+    // Drops a sphere onto a thin ground slab (y in [-0.2, 0]). The contact is
+    // only created when the sphere center is found inside the slab at the start
+    // of a step, so a single large step skips over it.
+    fn drop_onto_slab(substeps: usize) -> V3 {
+        let mut physics = Physics::new();
+        physics.set_substeps(substeps);
+
+        let mass = Mass::from_sphere(1.0, 0.1).unwrap();
+        let pos = V3::new([0.0, 0.5, 0.0]);
+        let mut body = RigidBody::new(
+            String::from("ball"),
+            mass,
+            Material::default(),
+            pos,
+            Q::identity(),
+        );
+        body.apply_impulse(V3::new([0.0, -20.0 * mass.mass(), 0.0]), "launch");
+        let id = physics.add_body(body);
+
+        let mut contact = None;
+        for _ in 0..10 {
+            physics
+                .step_substeps(0.1, |physics, _h| {
+                    let pos = physics.get_body(id).unwrap().position();
+                    if contact.is_none() && pos.x1() <= 0.0 && pos.x1() >= -0.2 {
+                        let context = TireContext {
+                            wheel_radius: 0.1,
+                            contact_point: pos,
+                            world_basis: M3x3::identity(),
+                            normal: V3::X1,
+                            penetration: 0.0,
+                            normal_force: 0.0,
+                            tire: TireModel::default(),
+                        };
+                        contact = Some(physics.add_contact(Contact::new_tire(id, context)));
+                    }
+                    Ok(())
+                })
+                .unwrap();
+        }
+
+        physics.get_body(id).unwrap().position()
+    }
+
+    // ------------------------------------------------------------------------
+    fn free_body(physics: &mut Physics, pos: V3) -> BodyId {
+        let mass = Mass::new(2.0, V3::new([1.0, 2.0, 3.0])).unwrap();
+        let body = RigidBody::new(
+            String::from("free"),
+            mass,
+            Material::default(),
+            pos,
+            Q::identity(),
+        );
+        physics.add_body(body)
+    }
+
+    // ------------------------------------------------------------------------
+    #[test]
+    fn free_spinning_body_conserves_energy_and_momentum() {
+        let mut physics = Physics::new();
+        let id = free_body(&mut physics, V3::ZERO);
+        let body = physics.get_body_mut(id).unwrap();
+        body.set_velocity(V3::new([1.0, 0.0, -2.0]));
+        body.set_angular_velocity(V3::new([0.0, 3.0, 0.0]));
+
+        let body = physics.get_body(id).unwrap();
+        let (energy, momentum) = (body.kinetic_energy(), body.linear_momentum());
+        let angular = body.angular_momentum();
+
+        for _ in 0..1000 {
+            physics.step(0.01);
+        }
+
+        let body = physics.get_body(id).unwrap();
+        assert_eq!(body.linear_momentum(), momentum);
+        assert_eq!(body.angular_momentum(), angular);
+        let drift = (body.kinetic_energy() - energy).abs() / energy;
+        assert!(drift < 1e-3, "energy drifted by {drift}");
+    }
+
+    // ------------------------------------------------------------------------
+    #[test]
+    fn joint_impulses_conserve_linear_momentum() {
+        let mut physics = Physics::new();
+        let a = free_body(&mut physics, V3::ZERO);
+        let b = free_body(&mut physics, V3::new([2.0, 0.0, 0.0]));
+        physics.add_joint(Joint::new_distance(a, b, V3::ZERO, V3::ZERO, 2.0));
+        physics
+            .get_body_mut(a)
+            .unwrap()
+            .set_velocity(V3::new([0.0, 1.0, 0.0]));
+
+        let total = |physics: &Physics| {
+            physics.get_body(a).unwrap().linear_momentum()
+                + physics.get_body(b).unwrap().linear_momentum()
+        };
+        let momentum = total(&physics);
+
+        for _ in 0..200 {
+            physics.step(0.01);
+        }
+        assert_eq!(total(&physics), momentum);
+    }
+
+    // ------------------------------------------------------------------------
+    #[test]
+    fn constraints_solve_after_forces() {
+        let mut physics = Physics::new();
+        let a = free_body(&mut physics, V3::ZERO);
+        let b = free_body(&mut physics, V3::new([2.0, 0.0, 0.0]));
+        physics.add_joint(Joint::new_distance(a, b, V3::ZERO, V3::ZERO, 2.0));
+
+        // Pulling the bodies apart within the step must not separate them
+        physics
+            .step_substeps(0.01, |physics, _h| {
+                let body = physics.get_body_mut(b).unwrap();
+                body.apply_force(V3::new([100.0, 0.0, 0.0]));
+                Ok(())
+            })
+            .unwrap();
+
+        let (a, b) = (physics.get_body(a).unwrap(), physics.get_body(b).unwrap());
+        let separation = (b.linear_velocity() - a.linear_velocity()).x0();
+        assert!(separation.abs() < 1e-3, "bodies separate at {separation}");
+        assert!(a.linear_velocity().x0() > 0.0, "force did not reach a");
+    }
+
+    // ------------------------------------------------------------------------
+    #[test]
+    fn substeps_prevent_tunneling() {
+        let single = drop_onto_slab(1);
+        assert!(single.x1() < -0.2, "expected tunneling, got {single}");
+
+        let sub = drop_onto_slab(16);
+        assert!(sub.x1() >= -0.2, "expected to be caught, got {sub}");
+    }
+
+    // ------------------------------------------------------------------------
+    // Relative velocity left along a chain of jointed bodies after one step
+    // with the last body pulled away.
+    fn chain_residual(iterations: usize) -> f32 {
+        let mut physics = Physics::new();
+        physics.set_solver_iterations(iterations);
+
+        let bodies: Vec<_> = (0..8)
+            .map(|i| free_body(&mut physics, V3::new([i as f32, 0.0, 0.0])))
+            .collect();
+        for pair in bodies.windows(2) {
+            physics.add_joint(Joint::new_distance(
+                pair[0],
+                pair[1],
+                V3::ZERO,
+                V3::ZERO,
+                1.0,
+            ));
+        }
+
+        let last = *bodies.last().unwrap();
+        physics
+            .step_substeps(0.01, |physics, _h| {
+                let body = physics.get_body_mut(last).unwrap();
+                body.apply_force(V3::new([1000.0, 0.0, 0.0]));
+                Ok(())
+            })
+            .unwrap();
+
+        let velocity = |id| physics.get_body(id).unwrap().linear_velocity().x0();
+        bodies
+            .windows(2)
+            .map(|pair| (velocity(pair[1]) - velocity(pair[0])).abs())
+            .sum()
+    }
+
+    // ------------------------------------------------------------------------
+    #[test]
+    fn more_iterations_reduce_joint_error() {
+        let few = chain_residual(1);
+        let default = chain_residual(SOLVER_ITERATIONS);
+        let many = chain_residual(50);
+        assert!(many < default && default < few, "{few} {default} {many}");
+    }
+
+    // ------------------------------------------------------------------------
+    #[test]
+    fn substeps_are_clamped() {
+        let mut physics = Physics::new();
+        assert_eq!(physics.substeps(), 1);
+        physics.set_substeps(0);
+        assert_eq!(physics.substeps(), 1);
+        physics.set_substeps(4);
+        assert_eq!(physics.substeps(), 4);
+
+        assert_eq!(physics.solver_iterations(), SOLVER_ITERATIONS);
+        physics.set_solver_iterations(0);
+        assert_eq!(physics.solver_iterations(), 1);
+    }
+}
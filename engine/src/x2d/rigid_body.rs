@@ -1,4 +1,5 @@
 use crate::core::gl_renderer::Transform;
+use crate::debug_assert_finite;
 use crate::v2d::{m3x3::M3x3, q::Q, v3::V3, v4::V4};
 use crate::x2d::{Material, mass::Mass};
 
@@ -11,6 +12,16 @@ use crate::x2d::{Material, mass::Mass};
 // https://gafferongames.com/post/physics_in_3d/
 // https://www.cs.cmu.edu/~baraff/sigcourse/notesd1.pdf
 
+// ----------------------------------------------------------------------------
+// A body whose speeds stay below the sleep thresholds for SLEEP_TIME seconds
+// falls asleep: forces are ignored and it is not integrated. Only a velocity
+// change above WAKE_SPEED, e.g. from a hit, wakes it again, so the small
+// impulses of a resting contact leave it asleep.
+const SLEEP_LINEAR_SPEED: f32 = 0.05;
+const SLEEP_ANGULAR_SPEED: f32 = 0.05;
+const SLEEP_TIME: f32 = 0.5;
+const WAKE_SPEED: f32 = 0.5;
+
 // ----------------------------------------------------------------------------
 pub fn from_angular_velocity(omega_dt: V3) -> Q {
     let angle2 = omega_dt.length2();
@@ -29,6 +40,25 @@ pub fn from_angular_velocity(omega_dt: V3) -> Q {
     }
 }
 
+// ----------------------------------------------------------------------------
+fn finite_or_zero(v: V3, name: &str, what: &str) -> V3 {
+    if v.is_finite() {
+        return v;
+    }
+    log::warn!("[{name}] non-finite {what}: {v}, resetting");
+    V3::new(v.as_array().map(|x| if x.is_finite() { x } else { 0.0 }))
+}
+
+// ----------------------------------------------------------------------------
+fn clamp_length(v: V3, max_length: f32) -> V3 {
+    let length = v.length();
+    if length > max_length {
+        v * (max_length / length)
+    } else {
+        v
+    }
+}
+
 // ----------------------------------------------------------------------------
 #[derive(Debug, Clone)]
 pub struct RigidBody {
@@ -38,6 +68,7 @@ pub struct RigidBody {
     material: Material,
 
     position: V3,
+    prev_position: V3,
     orientation: Q,
 
     linear_vel: V3,
@@ -47,6 +78,12 @@ pub struct RigidBody {
     torque_accu: V3,
 
     inv_inertia_world: M3x3,
+
+    max_linear_speed: f32,
+    max_angular_speed: f32,
+
+    rest_time: f32,
+    sleeping: bool,
 }
 
 // ----------------------------------------------------------------------------
@@ -58,12 +95,17 @@ impl RigidBody {
             mass,
             material,
             position: pos,
+            prev_position: pos,
             orientation: rot,
             linear_vel: V3::zero(),
             angular_vel: V3::zero(),
             force_accu: V3::zero(),
             torque_accu: V3::zero(),
             inv_inertia_world: Self::update_inertia_world(rot, mass.inv_inertia()),
+            max_linear_speed: f32::INFINITY,
+            max_angular_speed: f32::INFINITY,
+            rest_time: 0.0,
+            sleeping: false,
         }
     }
 
@@ -102,6 +144,19 @@ impl RigidBody {
         self.position
     }
 
+    // ------------------------------------------------------------------------
+    pub fn set_position(&mut self, position: V3) {
+        self.position = position;
+        self.prev_position = position;
+    }
+
+    // ------------------------------------------------------------------------
+    // Position before the last `integrate_velocities`, the start of the
+    // segment a continuous collision test sweeps.
+    pub fn prev_position(&self) -> V3 {
+        self.prev_position
+    }
+
     // ------------------------------------------------------------------------
     pub fn orientation(&self) -> Q {
         self.orientation
@@ -112,11 +167,30 @@ impl RigidBody {
         self.linear_vel
     }
 
+    // ------------------------------------------------------------------------
+    pub fn set_velocity(&mut self, velocity: V3) {
+        self.wake();
+        self.linear_vel = velocity;
+    }
+
     // ------------------------------------------------------------------------
     pub fn angular_velocity(&self) -> V3 {
         self.angular_vel
     }
 
+    // ------------------------------------------------------------------------
+    pub fn set_angular_velocity(&mut self, angular_velocity: V3) {
+        self.wake();
+        self.angular_vel = angular_velocity;
+    }
+
+    // ------------------------------------------------------------------------
+    // Upper bounds for the speeds after integration, unbounded by default.
+    pub fn set_max_speeds(&mut self, max_linear_speed: f32, max_angular_speed: f32) {
+        self.max_linear_speed = max_linear_speed;
+        self.max_angular_speed = max_angular_speed;
+    }
+
     // ------------------------------------------------------------------------
     pub fn to_local(&self, world: V3) -> V3 {
         let r = world - self.position;
@@ -128,20 +202,84 @@ impl RigidBody {
         self.orientation.rotate(local) + self.position
     }
 
+    // ------------------------------------------------------------------------
+    // Rotation only, for directions such as normals and axes.
+    pub fn to_local_dir(&self, world: V3) -> V3 {
+        self.orientation.inv_rotate(world)
+    }
+
+    // ------------------------------------------------------------------------
+    pub fn to_world_dir(&self, local: V3) -> V3 {
+        self.orientation.rotate(local)
+    }
+
     // ------------------------------------------------------------------------
     pub fn velocity_at(&self, world_pt: V3) -> V3 {
         let r = world_pt - self.position;
         self.linear_vel + self.angular_vel.cross(r)
     }
 
+    // ------------------------------------------------------------------------
+    // Velocity of `self` at `world_pt` relative to `other` at the same point.
+    pub fn relative_velocity_at(&self, other: &RigidBody, world_pt: V3) -> V3 {
+        self.relative_velocity(other, world_pt, world_pt)
+    }
+
+    // ------------------------------------------------------------------------
+    // Same as `relative_velocity_at`, for joints whose anchors have drifted
+    // apart.
+    pub fn relative_velocity(&self, other: &RigidBody, self_pt: V3, other_pt: V3) -> V3 {
+        self.velocity_at(self_pt) - other.velocity_at(other_pt)
+    }
+
+    // ------------------------------------------------------------------------
+    pub fn is_sleeping(&self) -> bool {
+        self.sleeping
+    }
+
+    // ------------------------------------------------------------------------
+    pub fn wake(&mut self) {
+        self.sleeping = false;
+        self.rest_time = 0.0;
+    }
+
+    // ------------------------------------------------------------------------
+    // Whether a velocity change may be applied, waking the body if it is
+    // large enough.
+    fn accepts_velocity_change(&mut self, dv: V3, dw: V3) -> bool {
+        if self.sleeping && dv.length().max(dw.length()) > WAKE_SPEED {
+            self.wake();
+        }
+        !self.sleeping
+    }
+
+    // ------------------------------------------------------------------------
+    fn update_sleep(&mut self, dt: f32) {
+        let resting = self.linear_vel.length() < SLEEP_LINEAR_SPEED
+            && self.angular_vel.length() < SLEEP_ANGULAR_SPEED;
+        self.rest_time = if resting { self.rest_time + dt } else { 0.0 };
+        if self.rest_time >= SLEEP_TIME {
+            log::info!("[{}]::sleep", self.name);
+            self.sleeping = true;
+            self.linear_vel = V3::zero();
+            self.angular_vel = V3::zero();
+        }
+    }
+
     // ------------------------------------------------------------------------
     pub fn apply_force(&mut self, force: V3) {
+        if self.sleeping {
+            return;
+        }
         log::info!("[{name}]::apply_force(force: {force})", name = self.name);
         self.force_accu += force;
     }
 
     // ------------------------------------------------------------------------
     pub fn apply_force_at(&mut self, force: V3, world_pt: V3) {
+        if self.sleeping {
+            return;
+        }
         log::info!(
             "[{name}]::apply_force_at(force: {force}, world_pt: {world_pt})",
             name = self.name
@@ -154,46 +292,65 @@ impl RigidBody {
 
     // ------------------------------------------------------------------------
     pub fn apply_impulse(&mut self, impulse: V3, reason: &str) {
+        let dv = impulse * self.inv_mass();
+        if !self.accepts_velocity_change(dv, V3::zero()) {
+            return;
+        }
         log::info!(
             "[{name}]::impulse[{reason}](impulse: {impulse})",
             name = self.name
         );
-        self.linear_vel += impulse * self.inv_mass();
+        self.linear_vel += dv;
+        debug_assert_finite!(self.linear_vel);
     }
 
     // ------------------------------------------------------------------------
     pub fn apply_impulse_at(&mut self, impulse: V3, world_pt: V3, reason: &str) {
+        let r = world_pt - self.position;
+        let dv = impulse * self.inv_mass();
+        let dw = self.inv_inertia_world * r.cross(impulse);
+        if !self.accepts_velocity_change(dv, dw) {
+            return;
+        }
         log::info!(
             "[{name}]::impulse[{reason}](impulse: {impulse}, pt: {world_pt})",
             name = self.name
         );
 
-        // Linear velocity
-        self.linear_vel += impulse * self.inv_mass();
-
-        // Angular velocity
-        let r = world_pt - self.position;
-        let angular_impulse = r.cross(impulse);
-
-        self.angular_vel += self.inv_inertia_world * angular_impulse;
+        self.linear_vel += dv;
+        self.angular_vel += dw;
+        debug_assert_finite!(self.linear_vel);
+        debug_assert_finite!(self.angular_vel);
     }
 
     // ------------------------------------------------------------------------
     pub fn apply_angular_impulse(&mut self, impulse: V3, reason: &str) {
+        let dw = self.inv_inertia() * impulse;
+        if !self.accepts_velocity_change(V3::zero(), dw) {
+            return;
+        }
         log::info!(
             "[{name}]::angular_impulse[{reason}](impulse: {impulse})",
             name = self.name
         );
-        self.angular_vel += self.inv_inertia() * impulse;
+        self.angular_vel += dw;
+        debug_assert_finite!(self.angular_vel);
     }
 
     // ------------------------------------------------------------------------
     pub fn integrate_forces(&mut self, dt: f32) {
+        if self.sleeping {
+            self.force_accu = V3::zero();
+            self.torque_accu = V3::zero();
+            return;
+        }
+
         let lin_accel = self.force_accu * self.inv_mass();
         let ang_accel = self.inv_inertia_world * self.torque_accu;
 
         self.linear_vel += lin_accel * dt;
         self.angular_vel += ang_accel * dt;
+        self.guard_velocities();
 
         log::info!(
             "[{}]::integrate_forces(dt: {dt}) → force: {}, torque: {}, linear_vel: {}, angular_vel: {}",
@@ -210,10 +367,18 @@ impl RigidBody {
 
     // ------------------------------------------------------------------------
     pub fn integrate_velocities(&mut self, dt: f32) {
+        self.prev_position = self.position;
+        if self.sleeping {
+            return;
+        }
+
+        self.guard_velocities();
         self.position += self.linear_vel * dt;
 
         let dq = from_angular_velocity(self.angular_vel * dt);
         self.orientation = (dq * self.orientation).norm();
+        debug_assert_finite!(self.position);
+        debug_assert_finite!(self.orientation);
 
         self.inv_inertia_world =
             Self::update_inertia_world(self.orientation, self.mass.inv_inertia());
@@ -224,6 +389,13 @@ impl RigidBody {
             self.position,
             self.orientation,
         );
+
+        self.update_sleep(dt);
+    }
+
+    // ------------------------------------------------------------------------
+    pub fn linear_momentum(&self) -> V3 {
+        self.mass() * self.linear_vel
     }
 
     // ------------------------------------------------------------------------
@@ -250,11 +422,35 @@ impl RigidBody {
         }
     }
 
+    // ------------------------------------------------------------------------
+    // Render transform of a mesh modelled at unit size, e.g. the unit cube for
+    // a box shaped body.
+    pub fn transform_with_scale(&self, scale: V3) -> Transform {
+        Transform {
+            size: V4::from_v3(scale, 1.0),
+            ..self.transform()
+        }
+    }
+
     // ------------------------------------------------------------------------
     pub fn log(&self) {
         log::info!("RigidBody: {self:?}");
     }
 
+    // ------------------------------------------------------------------------
+    // Keeps a diverging solve from propagating garbage into the rest of the
+    // game: non-finite components are zeroed, speeds are clamped.
+    fn guard_velocities(&mut self) {
+        self.linear_vel = clamp_length(
+            finite_or_zero(self.linear_vel, &self.name, "linear_vel"),
+            self.max_linear_speed,
+        );
+        self.angular_vel = clamp_length(
+            finite_or_zero(self.angular_vel, &self.name, "angular_vel"),
+            self.max_angular_speed,
+        );
+    }
+
     // ------------------------------------------------------------------------
     fn update_inertia_world(orientation: Q, inv_inertia_body: V3) -> M3x3 {
         let r = orientation.as_mat3x3();
@@ -265,6 +461,7 @@ impl RigidBody {
 #[cfg(test)]
 mod tests {
     use crate::assert_float_eq;
+    use crate::v2d::m4x4::M4x4;
 
     use super::*;
 
@@ -549,6 +746,69 @@ mod tests {
         assert_float_eq!(inv_inertia.x12(), inv_inertia.x21());
     }
 
+    #[test]
+    fn transform_places_body_in_world() {
+        let rotation = Q::from_axis_angle(V3::X1, std::f32::consts::FRAC_PI_2);
+        let body = RigidBody::new(
+            String::from("test"),
+            Mass::new(1.0, V3::one()).unwrap(),
+            Material::default(),
+            V3::new([1.0, 2.0, 3.0]),
+            rotation,
+        );
+
+        let m = M4x4::from(body.transform());
+        assert_eq!(
+            m * V4::new([0.0, 0.0, 0.0, 1.0]),
+            V4::new([1.0, 2.0, 3.0, 1.0])
+        );
+        let axis = V4::from_v3(rotation.rotate(V3::X2), 0.0);
+        assert_eq!(m * V4::new([0.0, 0.0, 1.0, 0.0]), axis);
+
+        let m = M4x4::from(body.transform_with_scale(V3::new([2.0, 1.0, 0.5])));
+        assert_eq!(m * V4::new([0.0, 0.0, 2.0, 0.0]), axis);
+        assert_eq!(
+            m * V4::new([0.0, 0.0, 0.0, 1.0]),
+            V4::new([1.0, 2.0, 3.0, 1.0])
+        );
+    }
+
+    #[test]
+    fn resting_body_falls_asleep() {
+        let mut body = RigidBody::new(
+            String::from("test"),
+            Mass::new(1.0, V3::one()).unwrap(),
+            Material::default(),
+            V3::zero(),
+            Q::identity(),
+        );
+        body.set_velocity(V3::new([0.01, 0.0, 0.0]));
+
+        let dt = 0.1;
+        let steps = (SLEEP_TIME / dt).ceil() as usize;
+        for _ in 0..steps - 1 {
+            body.integrate_velocities(dt);
+            assert!(!body.is_sleeping());
+        }
+        body.integrate_velocities(dt);
+        assert!(body.is_sleeping());
+
+        // Asleep, forces and small impulses leave the body where it is
+        let position = body.position();
+        body.apply_force(V3::new([0.0, -9.81, 0.0]));
+        body.apply_impulse(V3::new([0.1, 0.0, 0.0]), "test");
+        body.integrate_forces(dt);
+        body.integrate_velocities(dt);
+        assert_eq!(body.position(), position);
+        assert_eq!(body.linear_velocity(), V3::zero());
+
+        // A hit wakes it up
+        body.apply_impulse(V3::new([2.0, 0.0, 0.0]), "test");
+        assert!(!body.is_sleeping());
+        body.integrate_velocities(dt);
+        assert!(body.position().x0() > position.x0());
+    }
+
     #[test]
     fn asymmetric_body_free_spin_conserves_angular_momentum() {
         let mut body = RigidBody::new(
@@ -635,4 +895,121 @@ mod tests {
         // Quaternion should remain normalized
         assert!(max_q_error < 1e-5);
     }
+
+    #[test]
+    fn nan_force_keeps_state_finite() {
+        let mut body = RigidBody::new(
+            String::from("test"),
+            Mass::new(1.0, V3::new([1.0, 1.0, 1.0])).unwrap(),
+            Material::default(),
+            V3::zero(),
+            Q::identity(),
+        );
+
+        body.linear_vel = V3::new([1.0, 0.0, 0.0]);
+        body.apply_force_at(V3::new([f32::NAN, 2.0, 0.0]), V3::new([0.0, 0.0, 1.0]));
+        body.integrate_forces(0.01);
+        body.integrate_velocities(0.01);
+
+        assert!(
+            body.linear_velocity()
+                .as_array()
+                .iter()
+                .all(|x| x.is_finite())
+        );
+        assert!(
+            body.angular_velocity()
+                .as_array()
+                .iter()
+                .all(|x| x.is_finite())
+        );
+        assert!(body.position().as_array().iter().all(|x| x.is_finite()));
+        assert!(body.orientation().length().is_finite());
+        assert_float_eq!(body.linear_velocity().x1(), 0.02);
+    }
+
+    #[test]
+    fn speeds_are_clamped() {
+        let mut body = RigidBody::new(
+            String::from("test"),
+            Mass::new(1.0, V3::new([1.0, 1.0, 1.0])).unwrap(),
+            Material::default(),
+            V3::zero(),
+            Q::identity(),
+        );
+        body.set_max_speeds(5.0, 2.0);
+
+        body.linear_vel = V3::new([30.0, 40.0, 0.0]);
+        body.angular_vel = V3::new([0.0, 10.0, 0.0]);
+        body.integrate_velocities(0.01);
+
+        assert_float_eq!(body.linear_velocity().length(), 5.0);
+        assert_eq!(body.linear_velocity(), V3::new([3.0, 4.0, 0.0]));
+        assert_eq!(body.angular_velocity(), V3::new([0.0, 2.0, 0.0]));
+    }
+
+    #[test]
+    fn directions_ignore_translation() {
+        let body = RigidBody::new(
+            String::from("test"),
+            Mass::new(1.0, V3::new([1.0, 1.0, 1.0])).unwrap(),
+            Material::default(),
+            V3::new([5.0, -3.0, 2.0]),
+            Q::identity(),
+        );
+
+        let dir = V3::new([0.0, 0.0, 1.0]);
+        assert_eq!(body.to_world_dir(dir), dir);
+        assert_eq!(body.to_local_dir(dir), dir);
+        assert_eq!(body.to_world(dir), V3::new([5.0, -3.0, 3.0]));
+    }
+
+    #[test]
+    fn directions_follow_rotation() {
+        let body = RigidBody::new(
+            String::from("test"),
+            Mass::new(1.0, V3::new([1.0, 1.0, 1.0])).unwrap(),
+            Material::default(),
+            V3::new([5.0, -3.0, 2.0]),
+            Q::from_axis_angle(V3::X1, std::f32::consts::FRAC_PI_2),
+        );
+
+        let world = body.to_world_dir(V3::X2);
+        assert_eq!(world, V3::new([1.0, 0.0, 0.0]));
+        assert_eq!(body.to_local_dir(world), V3::X2);
+    }
+
+    #[test]
+    fn relative_velocity_of_opposite_bodies() {
+        let mut a = RigidBody::new(
+            String::from("a"),
+            Mass::new(1.0, V3::new([1.0, 1.0, 1.0])).unwrap(),
+            Material::default(),
+            V3::new([-1.0, 0.0, 0.0]),
+            Q::identity(),
+        );
+        let mut b = RigidBody::new(
+            String::from("b"),
+            Mass::new(1.0, V3::new([1.0, 1.0, 1.0])).unwrap(),
+            Material::default(),
+            V3::new([1.0, 0.0, 0.0]),
+            Q::identity(),
+        );
+
+        a.linear_vel = V3::new([0.0, 0.0, 2.0]);
+        b.linear_vel = V3::new([0.0, 0.0, -2.0]);
+        b.angular_vel = V3::new([0.0, 1.0, 0.0]);
+
+        // b spins about y, so its material point at the origin moves along +z
+        let midpoint = V3::zero();
+        assert_eq!(b.velocity_at(midpoint), V3::new([0.0, 0.0, -1.0]));
+        assert_eq!(
+            a.relative_velocity_at(&b, midpoint),
+            V3::new([0.0, 0.0, 3.0])
+        );
+        assert_eq!(
+            b.relative_velocity_at(&a, midpoint),
+            V3::new([0.0, 0.0, -3.0])
+        );
+    }
 }
@@ -1,8 +1,10 @@
+use engine::core::game_input::GameKey;
 use engine::core::gl_renderer::Renderer;
 use engine::core::world::World;
 use engine::core::{IGame, IRenderer, input};
 use engine::error::{Error, Result};
 use engine::sys::opengl as gl;
+use std::path::Path;
 use std::rc::Rc;
 
 pub struct Game {
@@ -11,9 +13,10 @@ pub struct Game {
 }
 
 impl IGame for Game {
-    fn input(&mut self, events: input::Events, state: input::State) -> Result<()> {
-        self.world.input(&events, state)?;
-        self.input_events(&events)
+    fn input(&mut self, events: &input::Events, state: &input::State) -> Result<()> {
+        self.renderer.input(events);
+        self.world.input(events, state)?;
+        self.input_events(events)
     }
 
     fn update(&mut self, dt: &std::time::Duration) -> Result<()> {
@@ -22,41 +25,79 @@ impl IGame for Game {
     }
 
     fn render(&mut self) -> Result<()> {
-        let render_context = self.world.render_context();
-        let camera = self.world.camera();
-        let objects = self.world.objects();
-        self.renderer.render(camera, objects, render_context)?;
-        Ok(())
+        self.world.render(&self.renderer)
     }
 }
 
 impl Game {
     pub fn new(gl: gl::OpenGlFunctions) -> Result<Self> {
+        Self::with_assets(gl, Path::new("assets"))
+    }
+
+    fn with_assets(gl: gl::OpenGlFunctions, assets: &Path) -> Result<Self> {
         let gl = Rc::new(gl);
         let renderer = Renderer::new(Rc::clone(&gl))?;
-        let world = World::new(Rc::clone(&gl))?;
+        let world = World::with_assets(Rc::clone(&gl), assets)?;
         Ok(Self { renderer, world })
     }
 
-    pub fn resize(&mut self, cx: i32, cy: i32) {
-        self.renderer.resize(cx, cy);
-    }
-
     fn input_events(&mut self, events: &input::Events) -> Result<()> {
-        // Process input events, e.g., keyboard, mouse, etc.
-        for event in events {
-            match event {
-                input::Event::KeyUp {
-                    key: input::Key::k_Escape,
-                } => {
-                    return Err(Error::GameOver);
-                }
-                input::Event::ButtonUp { button: 3 } => {
-                    return Err(Error::GameOver);
-                }
-                _ => {}
-            }
+        let menu = self.world.input_context().mapped_key(GameKey::Menu);
+        if events.iter().any(|event| is_exit_event(event, menu)) {
+            return Err(Error::GameOver);
         }
         Ok(())
     }
 }
+
+// ----------------------------------------------------------------------------
+fn is_exit_event(event: &input::Event, menu: input::Key) -> bool {
+    match event {
+        input::Event::KeyUp { key } => *key == menu,
+        input::Event::ButtonUp { button: 3 } => true,
+        _ => false,
+    }
+}
+
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use engine::core::game_input::InputContext;
+    use engine::core::input::{Event, Key};
+
+    #[test]
+    fn menu_key_exits() {
+        let menu = InputContext::default().mapped_key(GameKey::Menu);
+        assert_eq!(menu, Key::k_Escape);
+
+        assert!(is_exit_event(&Event::KeyUp { key: menu }, menu));
+        assert!(!is_exit_event(&Event::KeyDown { key: menu }, menu));
+        assert!(!is_exit_event(&Event::KeyUp { key: Key::k_W }, menu));
+
+        let mut game = headless_game();
+        let state = input::State::default();
+        let events = vec![Event::KeyUp { key: menu }];
+        assert_eq!(game.input(&events, &state).err(), Some(Error::GameOver));
+    }
+
+    // Game on the mock OpenGL functions, with the assets of the repository.
+    fn headless_game() -> Game {
+        let assets = Path::new(env!("CARGO_MANIFEST_DIR")).join("../assets");
+        Game::with_assets(gl::tests::mock_gl(), &assets).unwrap()
+    }
+
+    #[test]
+    fn headless_game_runs_a_frame() {
+        let mut game = headless_game();
+
+        let state = input::State::default();
+        game.input(&vec![Event::KeyDown { key: Key::k_W }], &state)
+            .unwrap();
+        game.update(&std::time::Duration::from_millis(16)).unwrap();
+
+        let draw_calls = gl::tests::draw_calls();
+        game.render().unwrap();
+        assert!(gl::tests::draw_calls() > draw_calls);
+    }
+}
@@ -20,7 +20,7 @@ pub fn main() {
 #[cfg(target_os = "windows")]
 mod win32 {
     use engine::core::clock::Clock;
-    use engine::core::game_loop::GameLoop;
+    use engine::core::game_loop::{GameConfig, GameLoop};
     use engine::core::input;
     use engine::core::input::Key;
     use engine::error::{Error, Result};
@@ -70,9 +70,8 @@ mod win32 {
                 .map_err(Error::from)?
             };
 
-            let t_update = std::time::Duration::from_millis(10);
             let win32 = Win32GLContext::from_hwnd(hwnd).unwrap();
-            let game_loop = GameLoop::new(t_update);
+            let game_loop = GameLoop::new(GameConfig::default());
             let gl = win32.load()?;
 
             let _ = unsafe { gl.SwapIntervalEXT(0) }; // Disable vsync
@@ -97,7 +96,7 @@ mod win32 {
         }
 
         fn on_size(&mut self, cx: i32, cy: i32) -> LRESULT {
-            self.game.resize(cx, cy);
+            self.input.add_event(input::Event::Resize { cx, cy });
             LRESULT(0)
         }
 
@@ -200,6 +199,12 @@ mod win32 {
             }
             LRESULT(0)
         }
+
+        // Keys released in another window never send WM_KEYUP here.
+        fn on_focus_lost(&mut self) -> LRESULT {
+            self.input.clear_state();
+            LRESULT(0)
+        }
     }
 
     const VK_MAP: [Option<Key>; 256] = {
@@ -313,20 +318,20 @@ mod win32 {
 #[cfg(target_os = "linux")]
 mod linux {
     use engine::core::clock::Clock;
-    use engine::core::game_loop::GameLoop;
+    use engine::core::game_loop::{GameConfig, GameLoop};
     use engine::core::input;
     use engine::core::input::Key;
     use engine::error::{Error, Result};
     use engine::sys::linux::LinuxGLContext;
     use engine::util::logger;
+    use std::collections::HashMap;
     use std::ptr::NonNull;
     use x11::xlib::{
-        XCloseDisplay, XCreateSimpleWindow, XDefaultScreen, XDestroyWindow, XEvent, XLookupKeysym,
-        XMapWindow, XNextEvent, XOpenDisplay, XPending, XQueryKeymap, XRaiseWindow, XRootWindow,
-        XSelectInput, XkbKeycodeToKeysym,
+        Atom, False, XCloseDisplay, XCreateSimpleWindow, XDefaultScreen, XDestroyWindow,
+        XDisplayHeight, XDisplayWidth, XEvent, XInternAtom, XLookupKeysym, XMapWindow, XNextEvent,
+        XOpenDisplay, XPending, XQueryKeymap, XRaiseWindow, XRootWindow, XSelectInput,
+        XSetWMProtocols, XkbKeycodeToKeysym, XkbSetDetectableAutoRepeat,
     };
-    //use x11::xlib::{XDisplayHeight, XDisplayWidth};
-    use std::collections::HashMap;
 
     // ------------------------------------------------------------------------
     pub fn main() -> Result<()> {
@@ -338,34 +343,60 @@ mod linux {
         let screen = unsafe { XDefaultScreen(display.as_ptr()) };
         let root = unsafe { XRootWindow(display.as_ptr(), screen) };
 
-        let cx = 1280; // unsafe { XDisplayWidth(display.as_ptr(), screen) as u32 };
-        let cy = 720; // unsafe { XDisplayHeight(display.as_ptr(), screen) as u32 };
-        let win = unsafe { XCreateSimpleWindow(display.as_ptr(), root, 0, 0, cx, cy, 0, 0, 0) };
+        let cx = unsafe { XDisplayWidth(display.as_ptr(), screen) };
+        let cy = unsafe { XDisplayHeight(display.as_ptr(), screen) };
+        let win = unsafe {
+            XCreateSimpleWindow(display.as_ptr(), root, 0, 0, cx as u32, cy as u32, 0, 0, 0)
+        };
 
         unsafe {
             XSelectInput(
                 display.as_ptr(),
                 win,
-                x11::xlib::ExposureMask | x11::xlib::KeyPressMask | x11::xlib::KeyReleaseMask,
+                x11::xlib::ExposureMask
+                    | x11::xlib::KeyPressMask
+                    | x11::xlib::KeyReleaseMask
+                    | x11::xlib::ButtonPressMask
+                    | x11::xlib::ButtonReleaseMask
+                    | x11::xlib::PointerMotionMask
+                    | x11::xlib::FocusChangeMask
+                    | x11::xlib::StructureNotifyMask,
             );
             XMapWindow(display.as_ptr(), win);
             XRaiseWindow(display.as_ptr(), win);
         }
 
+        // Ask the window manager to send a message instead of killing the
+        // connection when the window is closed.
+        let wm_protocols =
+            unsafe { XInternAtom(display.as_ptr(), c"WM_PROTOCOLS".as_ptr(), False) };
+        let mut wm_delete_window =
+            unsafe { XInternAtom(display.as_ptr(), c"WM_DELETE_WINDOW".as_ptr(), False) };
+        unsafe {
+            XSetWMProtocols(display.as_ptr(), win, &mut wm_delete_window, 1);
+
+            // Without this, auto-repeat sends a KeyRelease/KeyPress pair for
+            // every repeat and held keys flicker in the input state.
+            XkbSetDetectableAutoRepeat(display.as_ptr(), 1, std::ptr::null_mut());
+        }
+
         let context = LinuxGLContext::from_window(display, screen, win)?;
         let gl = context.load()?;
         let clock = Clock::new();
 
-        let t_update = std::time::Duration::from_millis(10);
-        let mut game_loop = GameLoop::new(t_update);
+        let mut game_loop = GameLoop::new(GameConfig::default());
         let mut game = super::game::Game::new(gl)?;
         let mut input = input::Input::new();
 
-        game.resize(cx as i32, cy as i32);
+        let mut size = (0, 0);
+        if let Some(event) = resize_event(&mut size, cx, cy) {
+            input.add_event(event);
+        }
 
         let keysym_map = keysym_map();
         let _keycode_map = keycode_map(display.as_ptr(), &keysym_map);
-        loop {
+        let mut pointer: Option<(i32, i32)> = None;
+        'running: loop {
             while unsafe { XPending(display.as_ptr()) } > 0 {
                 let mut event: XEvent = unsafe { std::mem::zeroed() };
                 unsafe { XNextEvent(display.as_ptr(), &mut event) };
@@ -376,13 +407,51 @@ mod linux {
                     x11::xlib::KeyPress | x11::xlib::KeyRelease => {
                         let keysym = unsafe { XLookupKeysym(&mut event.key as *mut _, 0) } as u32;
                         if let Some(key) = keysym_map.get(&keysym).copied() {
-                            let (event, state) = if event_type == x11::xlib::KeyPress {
-                                (input::Event::KeyDown { key }, 0x80)
+                            if event_type == x11::xlib::KeyPress {
+                                input.key_down(key);
                             } else {
-                                (input::Event::KeyUp { key }, 0x00)
-                            };
+                                input.key_up(key);
+                            }
+                        }
+                    }
+                    x11::xlib::ButtonPress | x11::xlib::ButtonRelease => {
+                        let button = unsafe { event.button.button };
+                        let pressed = event_type == x11::xlib::ButtonPress;
+                        if let Some(event) = button_event(button, pressed) {
                             input.add_event(event);
-                            input.set_state(key, state);
+                        }
+                    }
+                    // Key releases while unfocused go to another window.
+                    x11::xlib::FocusOut => input.clear_state(),
+                    x11::xlib::ClientMessage => {
+                        let message = unsafe { event.client_message };
+                        let data = message.data.get_long(0) as Atom;
+                        if is_close_request(
+                            message.message_type,
+                            data,
+                            wm_protocols,
+                            wm_delete_window,
+                        ) {
+                            break 'running;
+                        }
+                    }
+                    x11::xlib::ConfigureNotify => {
+                        let (cx, cy) = unsafe { (event.configure.width, event.configure.height) };
+                        if let Some(event) = resize_event(&mut size, cx, cy) {
+                            input.add_event(event);
+                        }
+                    }
+                    x11::xlib::MotionNotify => {
+                        // X11 reports absolute pointer positions, the engine
+                        // expects relative movement like win32 raw input.
+                        let (x, y) = unsafe { (event.motion.x, event.motion.y) };
+                        if let Some((x0, y0)) = pointer.replace((x, y)) {
+                            if x != x0 || y != y0 {
+                                input.add_event(input::Event::MouseMove {
+                                    x: x - x0,
+                                    y: y - y0,
+                                });
+                            }
                         }
                     }
                     _ => {}
@@ -394,15 +463,66 @@ mod linux {
 
             if let Err(e) = game_loop.step(&mut game, &clock, &events, &state) {
                 eprintln!("Game loop exited with: {e:?}");
-                unsafe {
-                    XDestroyWindow(display.as_ptr(), win);
-                    XCloseDisplay(display.as_ptr());
-                }
-                return Ok(());
+                break;
             }
 
             context.swap_buffers();
         }
+
+        // GL resources go first, while the context is still current; the
+        // context needs the display to be destroyed.
+        drop(game);
+        drop(context);
+        unsafe {
+            XDestroyWindow(display.as_ptr(), win);
+            XCloseDisplay(display.as_ptr());
+        }
+        Ok(())
+    }
+
+    // ------------------------------------------------------------------------
+    // The window manager's close button arrives as a WM_PROTOCOLS client
+    // message carrying WM_DELETE_WINDOW.
+    fn is_close_request(
+        message_type: Atom,
+        data: Atom,
+        wm_protocols: Atom,
+        wm_delete_window: Atom,
+    ) -> bool {
+        message_type == wm_protocols && data == wm_delete_window
+    }
+
+    // ------------------------------------------------------------------------
+    // ConfigureNotify also arrives when the window is only moved or restacked,
+    // so a resize event is only sent when the size actually changed.
+    fn resize_event(size: &mut (i32, i32), cx: i32, cy: i32) -> Option<input::Event> {
+        if *size == (cx, cy) {
+            return None;
+        }
+        *size = (cx, cy);
+        Some(input::Event::Resize { cx, cy })
+    }
+
+    // ------------------------------------------------------------------------
+    // Translates X11 pointer buttons into engine events. Buttons use the same
+    // numbering as the win32 backend (1 = left, 2 = right, 3 = middle); wheel
+    // notches arrive as presses of buttons 4 and 5.
+    #[allow(non_upper_case_globals)]
+    fn button_event(button: u32, pressed: bool) -> Option<input::Event> {
+        use x11::xlib::{Button1, Button2, Button3, Button4, Button5};
+        let button = match button {
+            Button1 => 1,
+            Button3 => 2,
+            Button2 => 3,
+            Button4 if pressed => return Some(input::Event::Wheel { delta: 120 }),
+            Button5 if pressed => return Some(input::Event::Wheel { delta: -120 }),
+            _ => return None,
+        };
+        if pressed {
+            Some(input::Event::ButtonDown { button })
+        } else {
+            Some(input::Event::ButtonUp { button })
+        }
     }
 
     // ------------------------------------------------------------------------
@@ -534,7 +654,7 @@ mod linux {
         keycode_map: &[Option<Key>; 256],
         input: &mut input::Input,
     ) {
-        input.reset_state();
+        input.clear_state();
 
         let mut keys = [0; 32];
         unsafe { XQueryKeymap(display, keys.as_mut_ptr()) };
@@ -554,4 +674,59 @@ mod linux {
             }
         }
     }
+
+    // ------------------------------------------------------------------------
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use x11::keysym::*;
+
+        #[test]
+        fn keysyms_map_to_keys() {
+            let map = keysym_map();
+            assert_eq!(map.get(&XK_Escape), Some(&Key::k_Escape));
+            assert_eq!(map.get(&XK_w), Some(&Key::k_W));
+            assert_eq!(map.get(&XK_W), Some(&Key::k_W));
+            assert_eq!(map.get(&XK_space), Some(&Key::k_Space));
+            assert_eq!(map.get(&XK_Shift_L), Some(&Key::k_LeftShift));
+            assert_eq!(map.get(&XK_Up), Some(&Key::k_Up));
+        }
+
+        #[test]
+        fn buttons_map_to_events() {
+            use input::Event::*;
+            assert_eq!(button_event(1, true), Some(ButtonDown { button: 1 }));
+            assert_eq!(button_event(1, false), Some(ButtonUp { button: 1 }));
+            assert_eq!(button_event(3, true), Some(ButtonDown { button: 2 }));
+            assert_eq!(button_event(2, false), Some(ButtonUp { button: 3 }));
+            assert_eq!(button_event(4, true), Some(Wheel { delta: 120 }));
+            assert_eq!(button_event(5, true), Some(Wheel { delta: -120 }));
+            assert_eq!(button_event(4, false), None);
+            assert_eq!(button_event(9, true), None);
+        }
+
+        #[test]
+        fn close_button_is_recognized() {
+            let (protocols, delete, other) = (10, 20, 30);
+            assert!(is_close_request(protocols, delete, protocols, delete));
+            assert!(!is_close_request(other, delete, protocols, delete));
+            assert!(!is_close_request(protocols, other, protocols, delete));
+        }
+
+        #[test]
+        fn only_size_changes_are_reported() {
+            use input::Event::*;
+            let mut size = (0, 0);
+            assert_eq!(
+                resize_event(&mut size, 1280, 720),
+                Some(Resize { cx: 1280, cy: 720 })
+            );
+            // moved, not resized
+            assert_eq!(resize_event(&mut size, 1280, 720), None);
+            assert_eq!(
+                resize_event(&mut size, 800, 720),
+                Some(Resize { cx: 800, cy: 720 })
+            );
+        }
+    }
 }